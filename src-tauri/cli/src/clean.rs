@@ -0,0 +1,106 @@
+use std::{path::PathBuf, sync::Arc};
+
+use serde::Serialize;
+use tokio::runtime::Runtime;
+use tokio_util::sync::CancellationToken;
+
+use nodemodules_cleaner_core::{
+    delete_backend::{self, DeleteHost, RemoveOptions},
+    deletion::{self, DeleteBackend, DeleteResult},
+    error::Error,
+    legitimacy::{self, SafetyCheckOptions},
+    protect_rules::ProtectRules,
+};
+
+use crate::walk::Found;
+
+// What a dry run would do with one folder
+#[derive(Debug, Serialize, Clone)]
+pub struct Planned {
+    pub path: String,
+    pub size: Option<u64>,
+    // Why it would be left alone; None when it would be deleted
+    pub reason: Option<Error>,
+}
+
+struct CliDeleteHost;
+
+impl DeleteHost for CliDeleteHost {
+    // Quarantine is an app setting; the CLI only offers the trash and
+    // permanent removal
+    fn quarantine_dir(&self) -> Result<PathBuf, String> {
+        Err("The command line tool has no quarantine folder".to_string())
+    }
+}
+
+fn runtime() -> Result<Runtime, String> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .map_err(|e| format!("Failed to start the delete: {}", e))
+}
+
+// Runs the same checks the app does before deleting: legitimacy, then
+// everything check_deletable looks at. The failure for each blocked folder.
+async fn check(selected: &[Found]) -> Vec<Option<DeleteResult>> {
+    let paths: Vec<String> = selected
+        .iter()
+        .map(|found| found.node_modules_path.clone())
+        .collect();
+    let verdicts = legitimacy::check_batch(
+        &paths,
+        &SafetyCheckOptions::default(),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    // The CLI has no protect list of its own; --exclude keeps projects out
+    let protected = ProtectRules::default();
+    paths
+        .iter()
+        .zip(verdicts)
+        .map(|(path, is_legitimate)| deletion::check_deletable(path, is_legitimate, &protected))
+        .collect()
+}
+
+pub fn plan(selected: &[Found]) -> Result<Vec<Planned>, String> {
+    let checked = runtime()?.block_on(check(selected));
+    Ok(selected
+        .iter()
+        .zip(checked)
+        .map(|(found, blocked)| Planned {
+            path: found.node_modules_path.clone(),
+            size: found.size,
+            reason: blocked.and_then(|result| result.error),
+        })
+        .collect())
+}
+
+// Deletes every folder that passes the checks, one at a time, with the
+// app's retries and leftover checks; blocked ones come back as failures
+// without being touched
+pub fn clean(selected: &[Found], backend: DeleteBackend) -> Result<Vec<DeleteResult>, String> {
+    let host: Arc<dyn DeleteHost> = Arc::new(CliDeleteHost);
+    let options = RemoveOptions::default();
+
+    Ok(runtime()?.block_on(async {
+        let mut results = Vec::with_capacity(selected.len());
+        for (found, blocked) in selected.iter().zip(check(selected).await) {
+            let result = match blocked {
+                Some(blocked) => blocked,
+                None => {
+                    delete_backend::remove_node_modules(
+                        &host,
+                        backend,
+                        options,
+                        &found.node_modules_path,
+                        found.measured,
+                    )
+                    .await
+                }
+            };
+            results.push(result);
+        }
+        results
+    }))
+}
@@ -0,0 +1,460 @@
+// nmclean: the app's scan and delete, for servers and scripts where the
+// desktop window isn't an option
+//
+// Exit codes, for cron jobs and CI scripts:
+//   0  everything asked for was done
+//   1  error: bad arguments, an unreadable root, no trash, ...
+//   2  some folders couldn't be deleted
+//   3  some folders couldn't be deleted for lack of permission
+//   4  nothing was found
+
+mod clean;
+mod tui;
+mod walk;
+
+use std::{io::IsTerminal, path::PathBuf, process::ExitCode};
+
+use clap::{error::ErrorKind, Args, Parser, Subcommand};
+use dialoguer::{Confirm, MultiSelect};
+use serde::Serialize;
+
+use nodemodules_cleaner_core::{
+    delete_backend,
+    deletion::{DeleteBackend, DeleteResult},
+    error::Error,
+    scan_options::ScanOptions,
+    scan_patterns::ScanPatterns,
+    sizing::format_bytes,
+};
+
+use crate::walk::{Found, WalkOptions};
+
+#[derive(Parser)]
+#[command(
+    name = "nmclean",
+    version,
+    about = "Find and remove node_modules folders"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Find node_modules folders and show how much space each takes")]
+    Scan(ScanArgs),
+    #[command(about = "Print the node_modules folders found, one per line, without sizing them")]
+    List(ScanArgs),
+    #[command(about = "Delete node_modules folders, picked interactively unless --yes is given")]
+    Clean(CleanArgs),
+    #[command(
+        about = "Browse what the scan finds in a full-screen list: sort, select with space, delete with enter"
+    )]
+    Tui(TuiArgs),
+}
+
+// Which folders to look at, shared by every command
+#[derive(Args)]
+struct Filters {
+    #[arg(help = "Folders to search; defaults to the current directory")]
+    roots: Vec<PathBuf>,
+    #[arg(
+        long,
+        value_name = "DAYS",
+        help = "Only projects untouched for at least this many days"
+    )]
+    older_than: Option<u64>,
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Only report paths matching this glob (repeatable)"
+    )]
+    include: Vec<String>,
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Skip paths matching this glob (repeatable)"
+    )]
+    exclude: Vec<String>,
+}
+
+#[derive(Args)]
+struct ScanArgs {
+    #[command(flatten)]
+    filters: Filters,
+    #[arg(long, help = "Print JSON instead of a table")]
+    json: bool,
+    #[arg(
+        long,
+        short,
+        conflicts_with = "json",
+        help = "Only print paths (and sizes in bytes), no progress, prompts or totals"
+    )]
+    quiet: bool,
+}
+
+#[derive(Args)]
+struct CleanArgs {
+    #[command(flatten)]
+    scan: ScanArgs,
+    #[arg(long, help = "Show what would be deleted without deleting anything")]
+    dry_run: bool,
+    #[arg(long, short, help = "Delete everything found instead of asking")]
+    yes: bool,
+    #[arg(long, help = "Remove permanently instead of moving to the trash")]
+    permanent: bool,
+    #[arg(
+        long,
+        requires = "permanent",
+        help = "Delete permanently without asking to confirm first"
+    )]
+    force: bool,
+}
+
+#[derive(Args)]
+struct TuiArgs {
+    #[command(flatten)]
+    filters: Filters,
+    #[arg(
+        long,
+        help = "Remove permanently instead of moving to the trash; each delete is confirmed"
+    )]
+    permanent: bool,
+}
+
+// How a command went, beyond plain errors
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Outcome {
+    Done,
+    Partial,
+    PermissionDenied,
+    NothingFound,
+}
+
+impl Outcome {
+    fn of(results: &[DeleteResult]) -> Outcome {
+        let failed = || results.iter().filter(|result| !result.success);
+        if failed().any(|result| matches!(result.error, Some(Error::PermissionDenied(_)))) {
+            Outcome::PermissionDenied
+        } else if failed().next().is_some() {
+            Outcome::Partial
+        } else {
+            Outcome::Done
+        }
+    }
+
+    fn exit_code(self) -> ExitCode {
+        match self {
+            Outcome::Done => ExitCode::SUCCESS,
+            Outcome::Partial => ExitCode::from(2),
+            Outcome::PermissionDenied => ExitCode::from(3),
+            Outcome::NothingFound => ExitCode::from(4),
+        }
+    }
+}
+
+impl Filters {
+    fn walk_options(
+        &self,
+        include_sizes: bool,
+        show_progress: bool,
+    ) -> Result<(Vec<PathBuf>, WalkOptions), String> {
+        let options = ScanOptions {
+            include_patterns: self.include.clone(),
+            exclude_patterns: self.exclude.clone(),
+            ..ScanOptions::default()
+        };
+        options.validate()?;
+        let patterns = ScanPatterns::compile(&options.include_patterns, &options.exclude_patterns)?;
+
+        let roots = if self.roots.is_empty() {
+            vec![std::env::current_dir()
+                .map_err(|e| format!("Failed to read the current directory: {}", e))?]
+        } else {
+            self.roots.clone()
+        };
+        for root in &roots {
+            if !root.is_dir() {
+                return Err(format!("{} is not a directory", root.display()));
+            }
+        }
+
+        let walk = WalkOptions {
+            options,
+            patterns,
+            older_than: self.older_than,
+            include_sizes,
+            show_progress,
+        };
+        Ok((roots, walk))
+    }
+}
+
+impl ScanArgs {
+    fn walk(&self, include_sizes: bool) -> Result<Vec<Found>, String> {
+        let show_progress = !self.quiet && std::io::stderr().is_terminal();
+        let (roots, walk) = self.filters.walk_options(include_sizes, show_progress)?;
+        walk::find(&roots, walk)
+    }
+}
+
+// Checked up front so a server without a trash fails once, not per folder
+fn check_trash() -> Result<(), String> {
+    match delete_backend::trash_unavailable_reason(None) {
+        Some(reason) => Err(format!("{}; pass --permanent to delete instead", reason)),
+        None => Ok(()),
+    }
+}
+
+fn print_json(value: &impl Serialize) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn size_text(size: Option<u64>) -> String {
+    size.map(format_bytes).unwrap_or_else(|| "?".to_string())
+}
+
+fn total(items: &[Found]) -> u64 {
+    items.iter().filter_map(|found| found.size).sum()
+}
+
+fn scan(args: &ScanArgs) -> Result<Outcome, String> {
+    let mut found = args.walk(true)?;
+    found.sort_by_key(|found| std::cmp::Reverse(found.size));
+    if args.json {
+        print_json(&found)?;
+    } else if args.quiet {
+        for item in &found {
+            let size = item
+                .size
+                .map_or_else(|| "-".to_string(), |size| size.to_string());
+            println!("{}\t{}", size, item.node_modules_path);
+        }
+    } else {
+        let width = found
+            .iter()
+            .map(|found| size_text(found.size).len())
+            .max()
+            .unwrap_or(0);
+        for item in &found {
+            println!(
+                "{:>width$}  {}",
+                size_text(item.size),
+                item.node_modules_path,
+                width = width
+            );
+        }
+        println!(
+            "\n{} folders, {} total",
+            found.len(),
+            format_bytes(total(&found))
+        );
+    }
+    Ok(found_anything(&found))
+}
+
+fn list(args: &ScanArgs) -> Result<Outcome, String> {
+    let mut found = args.walk(false)?;
+    found.sort_by(|a, b| a.node_modules_path.cmp(&b.node_modules_path));
+    if args.json {
+        print_json(&found)?;
+    } else {
+        for item in &found {
+            println!("{}", item.node_modules_path);
+        }
+    }
+    Ok(found_anything(&found))
+}
+
+fn found_anything(found: &[Found]) -> Outcome {
+    if found.is_empty() {
+        Outcome::NothingFound
+    } else {
+        Outcome::Done
+    }
+}
+
+// The folders the user ticks, or None if they backed out
+fn pick(found: Vec<Found>) -> Result<Option<Vec<Found>>, String> {
+    let labels: Vec<String> = found
+        .iter()
+        .map(|item| format!("{}  ({})", item.node_modules_path, size_text(item.size)))
+        .collect();
+    let picked = MultiSelect::new()
+        .with_prompt("Select folders to delete (space to toggle, enter to confirm)")
+        .items(&labels)
+        .interact_opt()
+        .map_err(|e| format!("Failed to read the selection: {}", e))?;
+
+    Ok(picked.map(|indices| {
+        found
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| indices.contains(index))
+            .map(|(_, item)| item)
+            .collect()
+    }))
+}
+
+// Permanent removal can't be undone, so it's confirmed even with --yes
+fn confirm_permanent(args: &CleanArgs, selected: &[Found]) -> Result<bool, String> {
+    if args.force {
+        return Ok(true);
+    }
+    if args.scan.quiet || args.scan.json || !std::io::stdin().is_terminal() {
+        return Err(
+            "Pass --force to delete permanently without a terminal to confirm in".to_string(),
+        );
+    }
+    Confirm::new()
+        .with_prompt(format!(
+            "Permanently delete {} folders ({})? This can't be undone",
+            selected.len(),
+            format_bytes(total(selected))
+        ))
+        .default(false)
+        .interact()
+        .map_err(|e| format!("Failed to read the confirmation: {}", e))
+}
+
+fn run_clean(args: &CleanArgs) -> Result<Outcome, String> {
+    let backend = if args.permanent {
+        DeleteBackend::Direct
+    } else {
+        DeleteBackend::Trash
+    };
+    if backend == DeleteBackend::Trash && !args.dry_run {
+        check_trash()?;
+    }
+
+    let mut found = args.scan.walk(true)?;
+    found.sort_by_key(|found| std::cmp::Reverse(found.size));
+    if found.is_empty() {
+        if args.scan.json {
+            print_json(&Vec::<Found>::new())?;
+        } else if !args.scan.quiet {
+            println!("No node_modules folders found");
+        }
+        return Ok(Outcome::NothingFound);
+    }
+
+    let selected = if args.yes || args.dry_run {
+        found
+    } else if std::io::stdin().is_terminal() && !args.scan.json && !args.scan.quiet {
+        match pick(found)? {
+            Some(selected) => selected,
+            None => return Ok(Outcome::Done),
+        }
+    } else {
+        return Err("Pass --yes to delete without a terminal to pick from".to_string());
+    };
+
+    if args.dry_run {
+        let planned = clean::plan(&selected)?;
+        if args.scan.json {
+            print_json(&planned)?;
+        } else if args.scan.quiet {
+            for item in planned.iter().filter(|item| item.reason.is_none()) {
+                println!("{}", item.path);
+            }
+        } else {
+            for item in &planned {
+                match &item.reason {
+                    None => println!("would delete  {}  ({})", item.path, size_text(item.size)),
+                    Some(reason) => println!("would skip    {}: {}", item.path, reason),
+                }
+            }
+        }
+        return Ok(Outcome::Done);
+    }
+
+    if selected.is_empty() {
+        return Ok(Outcome::Done);
+    }
+    if backend == DeleteBackend::Direct && !confirm_permanent(args, &selected)? {
+        return Ok(Outcome::Done);
+    }
+
+    let results = clean::clean(&selected, backend)?;
+    let outcome = Outcome::of(&results);
+    if args.scan.json {
+        print_json(&results)?;
+        return Ok(outcome);
+    }
+
+    let mut freed = 0;
+    for result in &results {
+        match &result.error {
+            None if args.scan.quiet => println!("{}", result.path),
+            None => {
+                freed += result.bytes_freed.unwrap_or(0);
+                println!("deleted  {}", result.path);
+            }
+            Some(error) if args.scan.quiet => eprintln!("{}: {}", result.path, error),
+            Some(error) => eprintln!("failed   {}: {}", result.path, error),
+        }
+    }
+    if !args.scan.quiet {
+        let deleted = results.iter().filter(|result| result.success).count();
+        println!(
+            "\nDeleted {} of {} folders, {} freed",
+            deleted,
+            results.len(),
+            format_bytes(freed)
+        );
+    }
+    Ok(outcome)
+}
+
+fn run_tui(args: &TuiArgs) -> Result<Outcome, String> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Err("The full-screen list needs an interactive terminal".to_string());
+    }
+    let backend = if args.permanent {
+        DeleteBackend::Direct
+    } else {
+        check_trash()?;
+        DeleteBackend::Trash
+    };
+
+    let (roots, walk) = args.filters.walk_options(true, false)?;
+    let (results, found_any) = tui::run(roots, walk, backend)?;
+    if !found_any {
+        return Ok(Outcome::NothingFound);
+    }
+    Ok(Outcome::of(&results))
+}
+
+fn main() -> ExitCode {
+    // Parsed by hand so bad arguments exit with 1 like every other error,
+    // keeping 2 for partial failures
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return match e.kind() {
+                ErrorKind::DisplayHelp | ErrorKind::DisplayVersion => ExitCode::SUCCESS,
+                _ => ExitCode::FAILURE,
+            };
+        }
+    };
+    let outcome = match &cli.command {
+        Command::Scan(args) => scan(args),
+        Command::List(args) => list(args),
+        Command::Clean(args) => run_clean(args),
+        Command::Tui(args) => run_tui(args),
+    };
+
+    match outcome {
+        // Failed folders were already reported one by one
+        Ok(outcome) => outcome.exit_code(),
+        Err(e) => {
+            eprintln!("nmclean: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
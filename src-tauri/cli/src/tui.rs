@@ -0,0 +1,499 @@
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Cell, Paragraph, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+use tokio_util::sync::CancellationToken;
+
+use nodemodules_cleaner_core::{
+    deletion::{DeleteBackend, DeleteResult},
+    sizing::format_bytes,
+};
+
+use crate::{
+    clean,
+    walk::{self, Found, ScanEvent, WalkOptions},
+};
+
+// How long to wait for a key before looking at the scan and deletes again
+const TICK: Duration = Duration::from_millis(100);
+// The core logs to stderr, which lands on top of the list; while work is
+// running the screen is repainted in full this often
+const REPAINT_EVERY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortBy {
+    Size,
+    Age,
+    Path,
+}
+
+impl SortBy {
+    fn next(self) -> SortBy {
+        match self {
+            SortBy::Size => SortBy::Age,
+            SortBy::Age => SortBy::Path,
+            SortBy::Path => SortBy::Size,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortBy::Size => "size",
+            SortBy::Age => "age",
+            SortBy::Path => "path",
+        }
+    }
+}
+
+enum RowState {
+    Found,
+    Deleting,
+    Deleted,
+    Failed(String),
+}
+
+struct Entry {
+    found: Found,
+    // Days since the project was last touched
+    age: Option<i64>,
+    selected: bool,
+    state: RowState,
+}
+
+impl Entry {
+    fn new(found: Found) -> Entry {
+        let age = found
+            .last_modified
+            .as_deref()
+            .and_then(|modified| chrono::DateTime::parse_from_rfc3339(modified).ok())
+            .map(|modified| (chrono::Utc::now() - modified.to_utc()).num_days());
+        Entry {
+            found,
+            age,
+            selected: false,
+            state: RowState::Found,
+        }
+    }
+
+    fn can_delete(&self) -> bool {
+        matches!(self.state, RowState::Found | RowState::Failed(_))
+    }
+}
+
+struct App {
+    entries: Vec<Entry>,
+    table: TableState,
+    sort: SortBy,
+    backend: DeleteBackend,
+    scanning: bool,
+    folders_scanned: usize,
+    // Folders waiting for a yes before they're removed permanently
+    confirming: Option<Vec<String>>,
+    deleting: usize,
+    results: Vec<DeleteResult>,
+    message: Option<String>,
+}
+
+impl App {
+    fn sort(&mut self) {
+        let current = self
+            .current()
+            .map(|entry| entry.found.node_modules_path.clone());
+        match self.sort {
+            SortBy::Size => self
+                .entries
+                .sort_by_key(|entry| std::cmp::Reverse(entry.found.size)),
+            // Oldest first, unknown ages last
+            SortBy::Age => self
+                .entries
+                .sort_by_key(|entry| std::cmp::Reverse(entry.age.unwrap_or(i64::MIN))),
+            SortBy::Path => self
+                .entries
+                .sort_by(|a, b| a.found.node_modules_path.cmp(&b.found.node_modules_path)),
+        }
+
+        // The cursor stays on the same folder
+        let index = current
+            .and_then(|path| self.position(&path))
+            .or((!self.entries.is_empty()).then_some(0));
+        self.table.select(index);
+    }
+
+    fn position(&self, path: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.found.node_modules_path == path)
+    }
+
+    fn current(&self) -> Option<&Entry> {
+        self.entries.get(self.table.selected()?)
+    }
+
+    fn reclaimed(&self) -> u64 {
+        self.results
+            .iter()
+            .filter(|result| result.success)
+            .filter_map(|result| result.bytes_freed)
+            .sum()
+    }
+
+    // Ticked folders, or the one under the cursor when none are ticked
+    fn to_delete(&self) -> Vec<String> {
+        let ticked: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.selected && entry.can_delete())
+            .map(|entry| entry.found.node_modules_path.clone())
+            .collect();
+        if !ticked.is_empty() {
+            return ticked;
+        }
+        self.current()
+            .filter(|entry| entry.can_delete())
+            .map(|entry| vec![entry.found.node_modules_path.clone()])
+            .unwrap_or_default()
+    }
+
+    fn bytes_of(&self, paths: &[String]) -> u64 {
+        paths
+            .iter()
+            .filter_map(|path| self.position(path))
+            .filter_map(|index| self.entries[index].found.size)
+            .sum()
+    }
+
+    fn move_cursor(&mut self, by: isize) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let last = self.entries.len() - 1;
+        let current = self.table.selected().unwrap_or(0);
+        let index = current.saturating_add_signed(by).min(last);
+        self.table.select(Some(index));
+    }
+
+    fn toggle_all(&mut self) {
+        let select = self
+            .entries
+            .iter()
+            .any(|entry| !entry.selected && entry.can_delete());
+        for entry in self.entries.iter_mut().filter(|entry| entry.can_delete()) {
+            entry.selected = select;
+        }
+    }
+
+    // Marks the folders as in progress and removes them one at a time on a
+    // worker thread; each result comes back on the returned channel
+    fn delete(&mut self, paths: Vec<String>) -> Receiver<DeleteResult> {
+        let mut selected = Vec::new();
+        for path in &paths {
+            if let Some(index) = self.position(path) {
+                let entry = &mut self.entries[index];
+                entry.selected = false;
+                entry.state = RowState::Deleting;
+                selected.push(entry.found.clone());
+            }
+        }
+        self.deleting += selected.len();
+        self.message = None;
+
+        let (sender, receiver) = mpsc::channel();
+        let backend = self.backend;
+        thread::spawn(move || {
+            for found in selected {
+                let path = found.node_modules_path.clone();
+                let result = clean::clean(std::slice::from_ref(&found), backend)
+                    .map(|mut results| results.remove(0))
+                    .unwrap_or_else(|e| DeleteResult::failed(&path, e));
+                if sender.send(result).is_err() {
+                    return;
+                }
+            }
+        });
+        receiver
+    }
+
+    fn finish_delete(&mut self, result: DeleteResult) {
+        self.deleting = self.deleting.saturating_sub(1);
+        if let Some(index) = self.position(&result.path) {
+            self.entries[index].state = match &result.error {
+                None => RowState::Deleted,
+                Some(error) => {
+                    self.message = Some(format!("{}: {}", result.path, error));
+                    RowState::Failed(error.to_string())
+                }
+            };
+        }
+        self.results.push(result);
+    }
+}
+
+// Full-screen list of what the scan finds, filled in while it runs. Returns
+// every delete made and whether anything was found, for the exit code.
+pub fn run(
+    roots: Vec<PathBuf>,
+    walk: WalkOptions,
+    backend: DeleteBackend,
+) -> Result<(Vec<DeleteResult>, bool), String> {
+    let cancel = CancellationToken::new();
+    let (events, scan_events) = mpsc::channel();
+    {
+        let cancel = cancel.clone();
+        thread::spawn(move || walk::stream(&roots, walk, events, cancel));
+    }
+
+    let mut app = App {
+        entries: Vec::new(),
+        table: TableState::default(),
+        sort: SortBy::Size,
+        backend,
+        scanning: true,
+        folders_scanned: 0,
+        confirming: None,
+        deleting: 0,
+        results: Vec::new(),
+        message: None,
+    };
+
+    let mut terminal = ratatui::init();
+    let outcome = event_loop(&mut terminal, &mut app, &scan_events);
+    ratatui::restore();
+    cancel.cancel();
+
+    outcome?;
+    let found_any = !app.entries.is_empty();
+    Ok((app.results, found_any))
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    scan_events: &Receiver<ScanEvent>,
+) -> Result<(), String> {
+    let mut deletes: Vec<Receiver<DeleteResult>> = Vec::new();
+    let mut last_repaint = Instant::now();
+
+    loop {
+        let mut found_more = false;
+        loop {
+            match scan_events.try_recv() {
+                Ok(ScanEvent::Found(found)) => {
+                    app.entries.push(Entry::new(found));
+                    found_more = true;
+                }
+                Ok(ScanEvent::Progress { folders_scanned }) => {
+                    app.folders_scanned = folders_scanned;
+                }
+                Ok(ScanEvent::Finished(result)) => {
+                    app.scanning = false;
+                    if let Err(e) = result {
+                        app.message = Some(format!("Scan failed: {}", e));
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    app.scanning = false;
+                    break;
+                }
+            }
+        }
+        if found_more {
+            app.sort();
+        }
+
+        for receiver in &deletes {
+            while let Ok(result) = receiver.try_recv() {
+                app.finish_delete(result);
+            }
+        }
+        if app.deleting == 0 {
+            deletes.clear();
+        }
+
+        let busy = app.scanning || app.deleting > 0;
+        if busy && last_repaint.elapsed() >= REPAINT_EVERY {
+            terminal
+                .clear()
+                .map_err(|e| format!("Failed to redraw: {}", e))?;
+            last_repaint = Instant::now();
+        }
+        terminal
+            .draw(|frame| draw(frame, app))
+            .map_err(|e| format!("Failed to draw: {}", e))?;
+
+        if !event::poll(TICK).map_err(|e| format!("Failed to read input: {}", e))? {
+            continue;
+        }
+        let Event::Key(key) = event::read().map_err(|e| format!("Failed to read input: {}", e))?
+        else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        if let Some(paths) = app.confirming.take() {
+            if key.code == KeyCode::Char('y') {
+                deletes.push(app.delete(paths));
+            }
+            continue;
+        }
+        if handle_key(app, key, &mut deletes) {
+            return Ok(());
+        }
+    }
+}
+
+// True when the user asked to quit
+fn handle_key(app: &mut App, key: KeyEvent, deletes: &mut Vec<Receiver<DeleteResult>>) -> bool {
+    let quit = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+    if quit {
+        // Stopping halfway through a trash move would misreport it
+        if app.deleting > 0 {
+            app.message = Some("Waiting for the deletes in progress to finish".to_string());
+            return false;
+        }
+        return true;
+    }
+
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => app.move_cursor(-1),
+        KeyCode::Down | KeyCode::Char('j') => app.move_cursor(1),
+        KeyCode::PageUp => app.move_cursor(-20),
+        KeyCode::PageDown => app.move_cursor(20),
+        KeyCode::Home | KeyCode::Char('g') => app.move_cursor(isize::MIN),
+        KeyCode::End | KeyCode::Char('G') => app.move_cursor(isize::MAX),
+        KeyCode::Char(' ') => {
+            if let Some(index) = app.table.selected() {
+                let entry = &mut app.entries[index];
+                if entry.can_delete() {
+                    entry.selected = !entry.selected;
+                }
+                app.move_cursor(1);
+            }
+        }
+        KeyCode::Char('a') => app.toggle_all(),
+        KeyCode::Char('s') => {
+            app.sort = app.sort.next();
+            app.sort();
+        }
+        KeyCode::Enter => {
+            let paths = app.to_delete();
+            if paths.is_empty() {
+                return false;
+            }
+            if app.backend == DeleteBackend::Direct {
+                app.confirming = Some(paths);
+            } else {
+                deletes.push(app.delete(paths));
+            }
+        }
+        _ => {}
+    }
+    false
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, list, footer] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let found_total: u64 = app
+        .entries
+        .iter()
+        .filter(|entry| entry.can_delete())
+        .filter_map(|entry| entry.found.size)
+        .sum();
+    let selected: Vec<&Entry> = app.entries.iter().filter(|entry| entry.selected).collect();
+    let scan = if app.scanning {
+        format!("Scanning... {} folders", app.folders_scanned)
+    } else {
+        format!("Scan done, {} folders", app.folders_scanned)
+    };
+    let totals = format!(
+        "Found {} ({})   Selected {} ({})   Reclaimed {}   Sorted by {}",
+        app.entries.len(),
+        format_bytes(found_total),
+        selected.len(),
+        format_bytes(selected.iter().filter_map(|entry| entry.found.size).sum()),
+        format_bytes(app.reclaimed()),
+        app.sort.label()
+    );
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::styled(
+                format!("nmclean   {}", scan),
+                Style::new().add_modifier(Modifier::BOLD),
+            ),
+            Line::from(totals),
+        ]),
+        header,
+    );
+
+    let rows = app.entries.iter().map(|entry| {
+        let mark = if entry.selected { "[x]" } else { "[ ]" };
+        let size = entry
+            .found
+            .size
+            .map(format_bytes)
+            .unwrap_or_else(|| "?".to_string());
+        let age = entry
+            .age
+            .map(|days| format!("{}d", days))
+            .unwrap_or_default();
+        let (status, style) = match &entry.state {
+            RowState::Found => (String::new(), Style::new()),
+            RowState::Deleting => ("deleting".to_string(), Style::new().fg(Color::Yellow)),
+            RowState::Deleted => ("deleted".to_string(), Style::new().fg(Color::DarkGray)),
+            RowState::Failed(error) => (format!("failed: {}", error), Style::new().fg(Color::Red)),
+        };
+        Row::new(vec![
+            Cell::from(mark),
+            Cell::from(Line::from(size).right_aligned()),
+            Cell::from(Line::from(age).right_aligned()),
+            Cell::from(entry.found.node_modules_path.as_str()),
+            Cell::from(status),
+        ])
+        .style(style)
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Fill(3),
+            Constraint::Fill(1),
+        ],
+    )
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, list, &mut app.table);
+
+    let help = match (&app.confirming, &app.message) {
+        (Some(paths), _) => format!(
+            "Permanently delete {} folders ({})? This can't be undone. y to confirm, any other key to cancel",
+            paths.len(),
+            format_bytes(app.bytes_of(paths))
+        ),
+        (None, Some(message)) => message.clone(),
+        (None, None) => {
+            "up/down move   space select   a all   s sort   enter delete   q quit".to_string()
+        }
+    };
+    frame.render_widget(Paragraph::new(help), footer);
+}
@@ -0,0 +1,148 @@
+use std::{
+    io::Write,
+    path::PathBuf,
+    sync::{mpsc::Sender, Arc},
+};
+
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use nodemodules_cleaner_core::{
+    artifacts::ArtifactKind,
+    jobs::{JobKind, JobRegistry},
+    scan::{self, ScanConfig, ScanHost, ScanProgress, ScanSession},
+    scan_options::ScanOptions,
+    scan_patterns::ScanPatterns,
+    sizing::{self, DirectorySize, SizeTierThresholds},
+};
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Found {
+    pub project_path: String,
+    pub node_modules_path: String,
+    pub artifact_type: ArtifactKind,
+    pub size: Option<u64>,
+    pub last_modified: Option<String>,
+    // What the scan measured, so a delete can tell a partial removal apart
+    #[serde(skip)]
+    pub measured: Option<DirectorySize>,
+}
+
+pub struct WalkOptions {
+    pub options: ScanOptions,
+    pub patterns: ScanPatterns,
+    pub older_than: Option<u64>,
+    pub include_sizes: bool,
+    // Running totals on stderr while the scan goes
+    pub show_progress: bool,
+}
+
+// What a scan running in the background reports as it goes
+pub enum ScanEvent {
+    Found(Found),
+    Progress { folders_scanned: usize },
+    Finished(Result<(), String>),
+}
+
+struct CliScanHost {
+    show_progress: bool,
+    events: Option<Sender<ScanEvent>>,
+}
+
+impl ScanHost for CliScanHost {
+    type Item = Found;
+
+    fn describe(&self, found: scan::Found) -> Found {
+        Found {
+            project_path: found.project.to_string_lossy().to_string(),
+            node_modules_path: found.path.to_string_lossy().to_string(),
+            artifact_type: found.artifact_type,
+            size: found.size.map(|size| size.reclaimable),
+            last_modified: found
+                .last_modified
+                .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339()),
+            measured: found.size,
+        }
+    }
+
+    fn found(&self, _session_id: &str, item: &Found) {
+        if let Some(events) = &self.events {
+            let _ = events.send(ScanEvent::Found(item.clone()));
+        }
+    }
+
+    fn progress(&self, progress: ScanProgress) {
+        if let Some(events) = &self.events {
+            let _ = events.send(ScanEvent::Progress {
+                folders_scanned: progress.folders_scanned,
+            });
+        }
+        if self.show_progress {
+            eprint!(
+                "\r{} folders scanned, {} found",
+                progress.folders_scanned, progress.node_modules_found
+            );
+            let _ = std::io::stderr().flush();
+        }
+    }
+}
+
+// Every node_modules (and enabled artifact) below `roots`, found by the
+// same scan the app runs
+pub fn find(roots: &[PathBuf], walk: WalkOptions) -> Result<Vec<Found>, String> {
+    run(roots, walk, None, CancellationToken::new())
+}
+
+// Scans on the calling thread, sending each find to `events` as soon as
+// it's measured; cancelling `cancel` stops it early
+pub fn stream(
+    roots: &[PathBuf],
+    walk: WalkOptions,
+    events: Sender<ScanEvent>,
+    cancel: CancellationToken,
+) {
+    let result = run(roots, walk, Some(events.clone()), cancel).map(|_| ());
+    let _ = events.send(ScanEvent::Finished(result));
+}
+
+fn run(
+    roots: &[PathBuf],
+    walk: WalkOptions,
+    events: Option<Sender<ScanEvent>>,
+    cancel: CancellationToken,
+) -> Result<Vec<Found>, String> {
+    let roots: Vec<String> = roots
+        .iter()
+        .map(|root| root.to_string_lossy().to_string())
+        .collect();
+    let config = ScanConfig {
+        include_sizes: walk.include_sizes,
+        size_tiers: SizeTierThresholds::default(),
+        max_results: None,
+        size_budget: sizing::DEFAULT_SIZE_BUDGET,
+        options: Arc::new(walk.options),
+        patterns: Arc::new(walk.patterns),
+        min_age_days: walk.older_than,
+    };
+    let host = Arc::new(CliScanHost {
+        show_progress: walk.show_progress,
+        events,
+    });
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .map_err(|e| format!("Failed to start the scan: {}", e))?;
+    let outcome = runtime.block_on(async {
+        let jobs = JobRegistry::default();
+        let job = jobs.start(JobKind::Scan);
+        let session = Arc::new(ScanSession::new("nmclean".to_string(), &roots, cancel));
+        scan::start(session, &roots, &config, &host, &job).await
+    });
+
+    if walk.show_progress {
+        // Clears the progress line
+        eprint!("\r\x1b[2K");
+    }
+    Ok(outcome?.items)
+}
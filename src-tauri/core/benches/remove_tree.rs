@@ -0,0 +1,101 @@
+// Times remove_tree, and on Windows the robocopy purge, on a generated tree
+// shaped like a node_modules install. Each run also checks that the tree is
+// gone and that a linked package store survived it.
+//
+//     cargo bench -p nodemodules-cleaner-core --bench remove_tree [files]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use nodemodules_cleaner_core::{long_paths, remover};
+
+const DEFAULT_FILES: usize = 20_000;
+const FILES_PER_PACKAGE: usize = 25;
+
+fn generate(root: &Path, files: usize) {
+    for index in 0..files {
+        let package = root
+            .join(format!("pkg-{}", index / FILES_PER_PACKAGE))
+            .join("lib");
+        if index % FILES_PER_PACKAGE == 0 {
+            fs::create_dir_all(long_paths::extended(&package)).expect("create package");
+        }
+        fs::write(
+            long_paths::extended(&package.join(format!("file-{}.js", index))),
+            b"module.exports = {};\n",
+        )
+        .expect("write file");
+    }
+}
+
+// A package store outside the tree, linked into it like pnpm does
+fn link_store(root: &Path, store: &Path) -> PathBuf {
+    let target = store.join("left-pad");
+    fs::create_dir_all(&target).expect("create store");
+    let kept = target.join("index.js");
+    fs::write(&kept, b"kept").expect("write store file");
+
+    let link = root.join("left-pad");
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, &link).expect("link store");
+    #[cfg(windows)]
+    {
+        let status = std::process::Command::new("cmd")
+            .args(["/c", "mklink", "/J"])
+            .arg(&link)
+            .arg(&target)
+            .stdout(std::process::Stdio::null())
+            .status()
+            .expect("run mklink");
+        assert!(status.success(), "mklink /J failed");
+    }
+    kept
+}
+
+fn run(name: &str, files: usize, remove: impl Fn(&Path)) {
+    let base =
+        std::env::temp_dir().join(format!("nmcleaner-bench-{}-{}", name, std::process::id()));
+    let root = base.join("node_modules");
+    let store = base.join("store");
+    generate(&root, files);
+    let kept = link_store(&root, &store);
+
+    let started = Instant::now();
+    remove(&root);
+    let elapsed = started.elapsed();
+
+    assert!(
+        !long_paths::extended(&root).exists(),
+        "{} left the tree behind",
+        name
+    );
+    assert!(kept.is_file(), "{} removed the linked store", name);
+    println!(
+        "{:<10} {:>7} files  {:>8.1} ms  {:>9.0} files/s",
+        name,
+        files,
+        elapsed.as_secs_f64() * 1000.0,
+        files as f64 / elapsed.as_secs_f64()
+    );
+    let _ = fs::remove_dir_all(&base);
+}
+
+fn main() {
+    // cargo bench passes "--bench"; a number sets the tree size
+    let files = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_FILES);
+
+    run("remove", files, |root| {
+        remover::remove_tree(&long_paths::extended(root), &mut |_| {}).expect("remove_tree");
+    });
+
+    #[cfg(windows)]
+    run("robocopy", files, |root| {
+        remover::remove_tree_mirrored(root).expect("remove_tree_mirrored");
+    });
+}
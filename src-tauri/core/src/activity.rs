@@ -0,0 +1,67 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use crate::{long_paths, scan_options::ScanOptions};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+// Most recent mtime among the project's own files, ignoring node_modules
+// and build outputs
+pub fn last_modified(project: &Path) -> Option<SystemTime> {
+    let max_depth = 4; // Sources rarely live deeper than this
+                       // Activity is judged the same way everywhere, whatever the scan settings
+    let options = ScanOptions::default();
+    let max_entries = 5000; // Cap work for huge projects
+
+    let mut latest: Option<SystemTime> = None;
+    let mut stack = vec![(long_paths::extended(project), 0)]; // (path, depth)
+    let mut visited = 0;
+
+    while let Some((current_path, depth)) = stack.pop() {
+        if let Ok(entries) = fs::read_dir(&current_path) {
+            for entry in entries.flatten() {
+                visited += 1;
+                if visited > max_entries {
+                    return latest;
+                }
+
+                let entry_path = entry.path();
+
+                // Reject symlinks/junctions
+                let Ok(metadata) = fs::symlink_metadata(&entry_path) else {
+                    continue;
+                };
+                if metadata.file_type().is_symlink() {
+                    continue;
+                }
+
+                if metadata.is_dir() {
+                    let name = entry.file_name();
+                    if depth < max_depth && !options.skips(&name.to_string_lossy(), depth + 1) {
+                        stack.push((entry_path, depth + 1));
+                    }
+                } else if let Ok(modified) = metadata.modified() {
+                    if latest.is_none_or(|current| modified > current) {
+                        latest = Some(modified);
+                    }
+                }
+            }
+        }
+    }
+
+    latest
+}
+
+// A project whose activity can't be determined isn't known to be stale, so
+// it doesn't pass
+pub fn is_stale(last_modified: Option<SystemTime>, min_age_days: u64) -> bool {
+    last_modified.is_some_and(|modified| {
+        SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default()
+            >= Duration::from_secs(min_age_days.saturating_mul(DAY.as_secs()))
+    })
+}
@@ -0,0 +1,198 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::long_paths;
+
+// What a scan item is. Yarn Plug'n'Play projects have no node_modules;
+// their packages sit zipped in .yarn/cache, with the few that must be on
+// disk unpacked into .yarn/unplugged. The rest are build outputs and
+// package folders of other toolchains, which the build or install puts back.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    #[default]
+    NodeModules,
+    YarnCache,
+    YarnUnplugged,
+    CargoTarget,
+    PythonVenv,
+    PythonCache,
+    GradleBuild,
+    GradleCache,
+    CocoaPods,
+    XcodeDerivedData,
+}
+
+// Recognises one kind of artifact folder. The name alone proves nothing
+// (plenty of projects keep sources in build/), so each detector also looks
+// for what its toolchain leaves behind.
+struct Detector {
+    kind: ArtifactKind,
+    // The folder the project holds directly, e.g. .yarn or target
+    folder: &'static str,
+    // The artifact inside `folder`, if it isn't the folder itself
+    child: Option<&'static str>,
+    // Given the artifact and its project folder
+    confirms: fn(&Path, &Path) -> bool,
+}
+
+const DETECTORS: [Detector; 9] = [
+    Detector {
+        kind: ArtifactKind::YarnCache,
+        folder: ".yarn",
+        child: Some("cache"),
+        confirms: |_, project| is_yarn_pnp(project),
+    },
+    Detector {
+        kind: ArtifactKind::YarnUnplugged,
+        folder: ".yarn",
+        child: Some("unplugged"),
+        confirms: |_, project| is_yarn_pnp(project),
+    },
+    Detector {
+        kind: ArtifactKind::CargoTarget,
+        folder: "target",
+        child: None,
+        // Cargo tags its target folder; a Maven target has no such files
+        confirms: |target, project| {
+            has_file(project, "Cargo.toml")
+                && (has_file(target, "CACHEDIR.TAG") || has_file(target, ".rustc_info.json"))
+        },
+    },
+    Detector {
+        kind: ArtifactKind::PythonVenv,
+        folder: ".venv",
+        child: None,
+        confirms: |venv, _| has_file(venv, "pyvenv.cfg"),
+    },
+    Detector {
+        kind: ArtifactKind::PythonCache,
+        folder: "__pycache__",
+        child: None,
+        confirms: |cache, _| has_file_ending(cache, &[".pyc"]),
+    },
+    Detector {
+        kind: ArtifactKind::GradleBuild,
+        folder: "build",
+        child: None,
+        confirms: |_, project| {
+            has_file(project, "build.gradle") || has_file(project, "build.gradle.kts")
+        },
+    },
+    Detector {
+        kind: ArtifactKind::GradleCache,
+        folder: ".gradle",
+        child: None,
+        confirms: |_, project| {
+            [
+                "build.gradle",
+                "build.gradle.kts",
+                "settings.gradle",
+                "settings.gradle.kts",
+            ]
+            .iter()
+            .any(|script| has_file(project, script))
+        },
+    },
+    Detector {
+        kind: ArtifactKind::CocoaPods,
+        folder: "Pods",
+        child: None,
+        confirms: |pods, project| has_file(project, "Podfile") && has_file(pods, "Manifest.lock"),
+    },
+    Detector {
+        kind: ArtifactKind::XcodeDerivedData,
+        folder: "DerivedData",
+        child: None,
+        // Next to the project when it builds to a relative location, or
+        // Xcode's own ~/Library/Developer/Xcode/DerivedData
+        confirms: |_, project| {
+            project.file_name().is_some_and(|name| name == "Xcode")
+                || has_file_ending(project, &[".xcodeproj", ".xcworkspace"])
+        },
+    },
+];
+
+fn has_file(dir: &Path, name: &str) -> bool {
+    std::fs::symlink_metadata(long_paths::extended(&dir.join(name)))
+        .is_ok_and(|metadata| metadata.is_file())
+}
+
+// Whether any entry of `dir` ends with one of `suffixes`. Xcode projects
+// are folders, so this doesn't look at the type.
+fn has_file_ending(dir: &Path, suffixes: &[&str]) -> bool {
+    std::fs::read_dir(long_paths::extended(dir)).is_ok_and(|entries| {
+        entries.flatten().any(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            suffixes.iter().any(|suffix| name.ends_with(suffix))
+        })
+    })
+}
+
+// Plug'n'Play writes .pnp.cjs (.pnp.js before Yarn 3) next to package.json
+fn is_yarn_pnp(project: &Path) -> bool {
+    has_file(project, ".pnp.cjs") || has_file(project, ".pnp.js")
+}
+
+fn is_real_directory(path: &Path) -> bool {
+    std::fs::symlink_metadata(long_paths::extended(path)).is_ok_and(|metadata| metadata.is_dir())
+}
+
+// The artifacts of `enabled` kinds that `project` holds in its child folder
+// `name`. With Yarn zero-installs the cache is committed, so git shows it
+// deleted until `yarn install` puts it back.
+pub fn detect(
+    project: &Path,
+    name: &str,
+    enabled: &[ArtifactKind],
+) -> Vec<(ArtifactKind, PathBuf)> {
+    DETECTORS
+        .iter()
+        .filter(|detector| detector.folder == name && enabled.contains(&detector.kind))
+        .filter_map(|detector| {
+            let folder = project.join(detector.folder);
+            let path = match detector.child {
+                Some(child) => folder.join(child),
+                None => folder,
+            };
+            (is_real_directory(&path) && (detector.confirms)(&path, project))
+                .then_some((detector.kind, path))
+        })
+        .collect()
+}
+
+// What kind of artifact `path` names, or None for anything else. Every
+// kind but node_modules also needs its detector's evidence to be there.
+pub fn kind_of(path: &Path) -> Option<ArtifactKind> {
+    let name = path.file_name()?.to_str()?;
+    if name == "node_modules" {
+        return Some(ArtifactKind::NodeModules);
+    }
+
+    let parent = path.parent()?;
+    DETECTORS
+        .iter()
+        .find(|detector| {
+            let (folder, project) = match detector.child {
+                Some(child) if child == name => (parent, parent.parent()),
+                Some(_) => return false,
+                None => (path, Some(parent)),
+            };
+            folder
+                .file_name()
+                .is_some_and(|folder| folder == detector.folder)
+                && project.is_some_and(|project| (detector.confirms)(path, project))
+        })
+        .map(|detector| detector.kind)
+}
+
+// The project folder an artifact belongs to
+pub fn project_of(path: &Path) -> Option<&Path> {
+    let parent = path.parent()?;
+    if parent.file_name().is_some_and(|name| name == ".yarn") {
+        return parent.parent();
+    }
+    Some(parent)
+}
@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use crate::long_paths;
+
+// Folder names the sync clients create for their roots. OneDrive appends the
+// organisation ("OneDrive - Contoso"), so it's matched as a prefix.
+const SYNC_ROOT_NAMES: [&str; 5] = [
+    "Dropbox",
+    "Google Drive",
+    "My Drive",
+    "iCloud Drive",
+    "iCloudDrive",
+];
+
+fn is_sync_root_name(name: &str) -> bool {
+    name.starts_with("OneDrive") || SYNC_ROOT_NAMES.contains(&name)
+}
+
+fn under_known_sync_root(path: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        // Set by the OneDrive client, whatever the folder is called
+        let onedrive_roots = ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"]
+            .into_iter()
+            .filter_map(std::env::var_os);
+        for root in onedrive_roots {
+            if !root.is_empty() && path.starts_with(&root) {
+                return true;
+            }
+        }
+    }
+
+    // File Provider clients on macOS all live under ~/Library/CloudStorage,
+    // and iCloud Drive under ~/Library/Mobile Documents
+    let text = path.to_string_lossy();
+    if text.contains("/Library/CloudStorage/") || text.contains("/Library/Mobile Documents/") {
+        return true;
+    }
+
+    path.ancestors()
+        .filter_map(|ancestor| ancestor.file_name())
+        .any(|name| is_sync_root_name(&name.to_string_lossy()))
+}
+
+// Attributes the Windows cloud files API puts on items in a sync root, and
+// the dataless flag macOS sets on evicted files. Only metadata is read, which
+// doesn't download anything.
+fn has_placeholder_attributes(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::symlink_metadata(long_paths::extended(path)) else {
+        return false;
+    };
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+
+        const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+        const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+        const FILE_ATTRIBUTE_PINNED: u32 = 0x80000;
+        const FILE_ATTRIBUTE_UNPINNED: u32 = 0x100000;
+        const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+
+        metadata.file_attributes()
+            & (FILE_ATTRIBUTE_OFFLINE
+                | FILE_ATTRIBUTE_RECALL_ON_OPEN
+                | FILE_ATTRIBUTE_PINNED
+                | FILE_ATTRIBUTE_UNPINNED
+                | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+            != 0
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::os::macos::fs::MetadataExt;
+
+        const SF_DATALESS: u32 = 0x4000_0000;
+        metadata.st_flags() & SF_DATALESS != 0
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = metadata;
+        false
+    }
+}
+
+// Whether a node_modules folder is kept in sync by OneDrive, Dropbox, Google
+// Drive or iCloud. Its files may be online-only placeholders, so measuring
+// them would download everything just to throw it away.
+pub fn is_cloud_synced(node_modules: &Path) -> bool {
+    under_known_sync_root(node_modules)
+        || has_placeholder_attributes(node_modules)
+        || node_modules
+            .parent()
+            .is_some_and(has_placeholder_attributes)
+}
@@ -0,0 +1,452 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    artifacts,
+    delete_retry::{self, RetryPolicy},
+    delete_verification::{self, Leftovers},
+    deletion::{DeleteBackend, DeleteResult, DeleteStatus},
+    error::Error,
+    lock_holders, long_paths, remover,
+    sizing::DirectorySize,
+    throttle,
+};
+
+// Files and bytes removed so far from one folder. Only permanent removal
+// reports progress; the trash and quarantine move a folder in one go.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteProgress {
+    pub path: String,
+    pub files_removed: u64,
+    pub bytes_removed: u64,
+}
+
+// What removal leaves to the program around it
+pub trait DeleteHost: Send + Sync {
+    // Where the quarantine backend moves folders to; created if missing
+    fn quarantine_dir(&self) -> Result<PathBuf, String>;
+
+    // After `path` was moved to `target` in quarantine
+    fn quarantined(&self, _path: &Path, _target: &Path) {}
+
+    // A few times a second while a folder is removed permanently
+    fn progress(&self, _progress: DeleteProgress) {}
+}
+
+// Returns why the OS trash is known not to work here (or for `path`)
+pub fn trash_unavailable_reason(path: Option<&Path>) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        // Without a desktop session there is usually no trash to browse or restore from
+        let has_session = ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()));
+        if !has_session {
+            return Some("No desktop session detected; the trash isn't available".to_string());
+        }
+
+        // Network mounts rarely have a writable .Trash-$UID, and the home trash
+        // is on another device
+        if let Some(fs_type) = path.and_then(crate::snapshots::filesystem_type) {
+            if crate::snapshots::NETWORK_FILESYSTEMS.contains(&fs_type.as_str()) {
+                return Some(format!("Trash isn't supported on {} mounts", fs_type));
+            }
+        }
+
+        None
+    }
+
+    #[cfg(windows)]
+    {
+        // The Recycle Bin goes through the shell, which still stops at MAX_PATH
+        const MAX_PATH: usize = 260;
+        if path.is_some_and(|path| path.as_os_str().len() >= MAX_PATH) {
+            return Some(format!(
+                "The path is longer than the {} characters the Recycle Bin accepts",
+                MAX_PATH
+            ));
+        }
+        None
+    }
+
+    #[cfg(not(any(target_os = "linux", windows)))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+fn quarantine(host: &dyn DeleteHost, path: &Path) -> Result<(), String> {
+    // Keep the project name around so quarantined folders can be told apart
+    let project = artifacts::project_of(path)
+        .and_then(|parent| parent.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
+    let target = host.quarantine_dir()?.join(format!(
+        "{}-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+        project
+    ));
+
+    let target = long_paths::extended(&target);
+
+    // A rename only works within one volume; copying gigabytes would defeat
+    // the point, so surface that instead
+    std::fs::rename(long_paths::extended(path), &target).map_err(|e| {
+        format!(
+            "Failed to move to quarantine (it must be on the same drive as the app data folder): {}",
+            e
+        )
+    })?;
+
+    host.quarantined(path, &target);
+    Ok(())
+}
+
+fn remove_permanently(host: &dyn DeleteHost, path: &Path, fast: bool) -> Result<(), String> {
+    #[cfg(windows)]
+    if fast {
+        return remover::remove_tree_mirrored(path).map_err(|e| format!("Failed to delete: {}", e));
+    }
+    #[cfg(not(windows))]
+    let _ = fast;
+
+    let throttle = throttle::Throttle::new(throttle::DELETE_EVENTS_PER_SECOND);
+    let display_path = path.to_string_lossy().to_string();
+    remover::remove_tree(&long_paths::extended(path), &mut |progress| {
+        if throttle.claim() {
+            host.progress(DeleteProgress {
+                path: display_path.clone(),
+                files_removed: progress.files_removed,
+                bytes_removed: progress.bytes_removed,
+            });
+        }
+    })
+    .map(|_| ())
+    .map_err(|e| format!("Failed to delete: {}", e))
+}
+
+// `fast` picks the robocopy purge for permanent removal on Windows, which
+// reports no progress
+pub async fn remove(
+    host: &Arc<dyn DeleteHost>,
+    backend: DeleteBackend,
+    path: &Path,
+    fast: bool,
+) -> Result<(), Error> {
+    let path = path.to_path_buf();
+    let host = host.clone();
+
+    tokio::task::spawn_blocking(move || match backend {
+        DeleteBackend::Trash => {
+            if let Some(reason) = trash_unavailable_reason(Some(&path)) {
+                return Err(Error::TrashUnavailable(format!(
+                    "{}; choose another delete backend",
+                    reason
+                )));
+            }
+            // The shell APIs behind the trash don't accept verbatim paths
+            trash::delete(&path).map_err(|e| {
+                match remover::first_blocking_file(&long_paths::extended(&path)) {
+                    Some(file) => Error::PermissionDenied(format!(
+                        "Failed to delete: {} ({} is read-only, hidden or a system file)",
+                        e,
+                        file.display()
+                    )),
+                    None => Error::from(format!("Failed to delete: {}", e)),
+                }
+            })
+        }
+        DeleteBackend::Quarantine => quarantine(&*host, &path).map_err(Error::from),
+        DeleteBackend::Direct => remove_permanently(&*host, &path, fast).map_err(Error::from),
+    })
+    .await
+    .map_err(|e| format!("Delete task failed: {}", e))?
+}
+
+// Opt-in extras around a single removal
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemoveOptions {
+    // Remove permanently what the trash refuses
+    pub permanent_fallback: bool,
+    // Clear read-only, hidden and system attributes below the folder first
+    pub clear_read_only: bool,
+    // Remove permanently with a multi-threaded robocopy purge (Windows)
+    pub fast_remove: bool,
+    // For failures that look like a file was only busy
+    pub retry: RetryPolicy,
+}
+
+// Removes with `backend`, and if that was the trash and it failed, with
+// direct removal when the options allow. Network shares, some Linux
+// filesystems and overlong paths have no working trash. Returns the
+// backend that actually removed the folder.
+pub async fn remove_with_fallback(
+    host: &Arc<dyn DeleteHost>,
+    backend: DeleteBackend,
+    options: RemoveOptions,
+    path: &Path,
+) -> Result<DeleteBackend, Error> {
+    if options.clear_read_only {
+        let root = long_paths::extended(path);
+        tokio::task::spawn_blocking(move || remover::clear_blocking_attributes(&root))
+            .await
+            .map_err(|e| format!("Failed to clear file attributes: {}", e))?
+            .map_err(|e| format!("Failed to clear file attributes: {}", e))?;
+    }
+
+    match remove(host, backend, path, options.fast_remove).await {
+        Ok(()) => Ok(backend),
+        Err(trash_error) if options.permanent_fallback && backend == DeleteBackend::Trash => {
+            eprintln!(
+                "{}; removing {} permanently instead",
+                trash_error,
+                path.display()
+            );
+            remove(host, DeleteBackend::Direct, path, options.fast_remove)
+                .await
+                .map(|()| DeleteBackend::Direct)
+                .map_err(|e| e.context(format!("after: {}", trash_error)))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// Removes one folder that passed check_deletable: retries failures that
+// look like a busy file, then checks what is really left on disk whatever
+// the backend said. `size` is what the folder held beforehand, when known.
+pub async fn remove_node_modules(
+    host: &Arc<dyn DeleteHost>,
+    backend: DeleteBackend,
+    options: RemoveOptions,
+    path: &str,
+    size: Option<DirectorySize>,
+) -> DeleteResult {
+    let path_buf = PathBuf::from(path);
+    let policy = options.retry;
+
+    let mut attempts = 1;
+    let removed = loop {
+        match remove_with_fallback(host, backend, options, &path_buf).await {
+            Err(e) if attempts < policy.max_attempts && delete_retry::is_transient(e.message()) => {
+                eprintln!("Retrying {} after: {}", path, e);
+                tokio::time::sleep(policy.delay(attempts)).await;
+                attempts += 1;
+            }
+            removed => break removed,
+        }
+    };
+
+    // Checked whatever the backend said: the trash can report success for
+    // a move that stopped halfway, and a failed delete may still have
+    // removed most of the tree
+    let leftovers = {
+        let path_buf = path_buf.clone();
+        tokio::task::spawn_blocking(move || delete_verification::leftovers(&path_buf))
+            .await
+            .ok()
+            .flatten()
+    };
+    let files_before = size.map(|size| size.files).filter(|&files| files > 0);
+    // Only partial when less is left than there was; bytes decide when the
+    // files weren't counted
+    let shrank = |leftovers: &Leftovers| match files_before {
+        Some(files) => leftovers.files < files,
+        None => size.is_some_and(|size| leftovers.bytes < size.total),
+    };
+    let partial = match (&removed, leftovers) {
+        (Ok(_), leftovers) => leftovers,
+        (Err(_), Some(leftovers)) if shrank(&leftovers) => Some(leftovers),
+        (Err(_), _) => None,
+    };
+
+    let (e, partial) = match (removed, partial) {
+        (Ok(method), None) => {
+            return DeleteResult {
+                bytes_freed: size.map(|size| size.reclaimable),
+                files_removed: files_before,
+                attempts,
+                ..DeleteResult::deleted(path, method)
+            };
+        }
+        (Ok(_), Some(leftovers)) => (
+            Error::Other(format!(
+                "Deleted, but {}",
+                delete_verification::describe(&leftovers)
+            )),
+            Some(leftovers),
+        ),
+        (Err(e), Some(leftovers)) => (
+            e.context(delete_verification::describe(&leftovers)),
+            Some(leftovers),
+        ),
+        (Err(e), None) => (e, None),
+    };
+
+    // Usually an editor, dev server or antivirus scanner; naming it tells
+    // the user what to close
+    let holders = tokio::task::spawn_blocking(move || lock_holders::describe(&path_buf))
+        .await
+        .ok()
+        .flatten();
+    let e = match holders {
+        Some(holders) => e.context(holders),
+        None => e,
+    };
+    DeleteResult {
+        status: if partial.is_some() {
+            DeleteStatus::Partial
+        } else {
+            DeleteStatus::Failed
+        },
+        attempts,
+        can_elevate: matches!(e, Error::PermissionDenied(_)),
+        leftovers: partial,
+        ..DeleteResult::failed(path, e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::fixtures::{awkward_node_modules, Fixture};
+
+    struct TestHost {
+        quarantine: PathBuf,
+        quarantined: Mutex<Vec<PathBuf>>,
+    }
+
+    impl DeleteHost for TestHost {
+        fn quarantine_dir(&self) -> Result<PathBuf, String> {
+            std::fs::create_dir_all(&self.quarantine).map_err(|e| e.to_string())?;
+            Ok(self.quarantine.clone())
+        }
+
+        fn quarantined(&self, _path: &Path, target: &Path) {
+            self.quarantined.lock().unwrap().push(target.to_path_buf());
+        }
+    }
+
+    fn host(fixture: &Fixture) -> Arc<TestHost> {
+        Arc::new(TestHost {
+            quarantine: fixture.path("quarantine"),
+            quarantined: Mutex::new(Vec::new()),
+        })
+    }
+
+    #[tokio::test]
+    async fn removes_permanently_and_reports_what_was_freed() {
+        let fixture = Fixture::new("remove-direct");
+        let (node_modules, files, bytes) = awkward_node_modules(&fixture, "app", 30);
+        let size = DirectorySize {
+            total: bytes,
+            reclaimable: bytes,
+            files,
+        };
+        let host: Arc<dyn DeleteHost> = host(&fixture);
+
+        let result = remove_node_modules(
+            &host,
+            DeleteBackend::Direct,
+            RemoveOptions::default(),
+            &node_modules.to_string_lossy(),
+            Some(size),
+        )
+        .await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.method, Some(DeleteBackend::Direct));
+        assert_eq!(result.bytes_freed, Some(bytes));
+        assert_eq!(result.files_removed, Some(files));
+        assert_eq!(result.attempts, 1);
+        assert!(!long_paths::extended(&node_modules).exists());
+    }
+
+    #[tokio::test]
+    async fn quarantines_into_the_hosts_folder() {
+        let fixture = Fixture::new("remove-quarantine");
+        let (node_modules, _, _) = awkward_node_modules(&fixture, "app", 3);
+        let test_host = host(&fixture);
+        let host: Arc<dyn DeleteHost> = test_host.clone();
+
+        let result = remove_node_modules(
+            &host,
+            DeleteBackend::Quarantine,
+            RemoveOptions::default(),
+            &node_modules.to_string_lossy(),
+            None,
+        )
+        .await;
+
+        assert!(result.success, "{:?}", result.error);
+        assert!(!long_paths::extended(&node_modules).exists());
+        let quarantined = test_host.quarantined.lock().unwrap().clone();
+        assert_eq!(quarantined.len(), 1);
+        assert!(quarantined[0].join("aux").is_dir());
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_permanent_removal_without_a_trash() {
+        // Only where the trash is known to be missing, so nothing real is trashed
+        if trash_unavailable_reason(None).is_none() {
+            return;
+        }
+        let fixture = Fixture::new("remove-fallback");
+        let (node_modules, _, _) = awkward_node_modules(&fixture, "app", 3);
+        let host: Arc<dyn DeleteHost> = host(&fixture);
+        let path = node_modules.to_string_lossy().to_string();
+
+        let refused = remove_node_modules(
+            &host,
+            DeleteBackend::Trash,
+            RemoveOptions::default(),
+            &path,
+            None,
+        )
+        .await;
+        assert!(matches!(refused.error, Some(Error::TrashUnavailable(_))));
+        assert!(long_paths::extended(&node_modules).exists());
+
+        let options = RemoveOptions {
+            permanent_fallback: true,
+            ..RemoveOptions::default()
+        };
+        let result = remove_node_modules(&host, DeleteBackend::Trash, options, &path, None).await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.method, Some(DeleteBackend::Direct));
+    }
+
+    #[tokio::test]
+    async fn lasting_failures_are_not_retried() {
+        let fixture = Fixture::new("remove-missing");
+        let host: Arc<dyn DeleteHost> = host(&fixture);
+        let options = RemoveOptions {
+            retry: RetryPolicy {
+                max_attempts: 5,
+                initial_delay_ms: 1,
+                max_delay_ms: 1,
+            },
+            ..RemoveOptions::default()
+        };
+
+        let result = remove_node_modules(
+            &host,
+            DeleteBackend::Direct,
+            options,
+            &fixture.path("gone/node_modules").to_string_lossy(),
+            None,
+        )
+        .await;
+
+        assert!(!result.success);
+        assert_eq!(result.status, DeleteStatus::Failed);
+        assert_eq!(result.attempts, 1);
+        assert!(result.leftovers.is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_ATTEMPTS: u32 = 10;
+const MAX_DELAY_MS: u64 = 10_000;
+
+// Antivirus scanners and indexers open freshly touched files for a moment,
+// which makes a delete fail once and succeed a second later. Such failures
+// are retried, waiting twice as long before each further attempt.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct RetryPolicy {
+    // Including the first try; 1 turns retries off
+    pub max_attempts: u32,
+    // Before the first retry
+    pub initial_delay_ms: u64,
+    // No single wait is longer than this
+    pub max_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_delay_ms: 250,
+            max_delay_ms: 2_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_attempts == 0 || self.max_attempts > MAX_ATTEMPTS {
+            return Err(format!(
+                "The number of attempts must be between 1 and {}",
+                MAX_ATTEMPTS
+            ));
+        }
+        if self.initial_delay_ms > self.max_delay_ms || self.max_delay_ms > MAX_DELAY_MS {
+            return Err(format!(
+                "Retry delays must be at most {} ms, the first no longer than the longest",
+                MAX_DELAY_MS
+            ));
+        }
+        Ok(())
+    }
+
+    // How long to wait after `attempt` (1-based) failed
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        Duration::from_millis(
+            self.initial_delay_ms
+                .saturating_mul(factor)
+                .min(self.max_delay_ms),
+        )
+    }
+}
+
+// Failures that say a file was busy rather than that it can't be deleted at
+// all. The backends only hand back messages, so this goes by the OS error
+// codes and texts they contain.
+pub fn is_transient(error: &str) -> bool {
+    #[cfg(windows)]
+    const TRANSIENT: [&str; 6] = [
+        // ERROR_ACCESS_DENIED, also returned for files pending deletion
+        "(os error 5)",
+        // ERROR_SHARING_VIOLATION
+        "(os error 32)",
+        // ERROR_LOCK_VIOLATION
+        "(os error 33)",
+        // ERROR_DIR_NOT_EMPTY, when a scanner still had a file open
+        "(os error 145)",
+        "being used by another process",
+        "0x80070020",
+    ];
+
+    #[cfg(not(windows))]
+    const TRANSIENT: [&str; 3] = [
+        "Device or resource busy",
+        "Resource busy",
+        "Directory not empty",
+    ];
+
+    TRANSIENT.iter().any(|marker| error.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delays_double_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_delay_ms: 250,
+            max_delay_ms: 800,
+        };
+        let delays: Vec<u64> = (1..=4)
+            .map(|attempt| policy.delay(attempt).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, [250, 500, 800, 800]);
+    }
+
+    #[test]
+    fn rejects_policies_out_of_range() {
+        assert!(RetryPolicy::default().validate().is_ok());
+        for policy in [
+            RetryPolicy {
+                max_attempts: 0,
+                ..RetryPolicy::default()
+            },
+            RetryPolicy {
+                max_attempts: MAX_ATTEMPTS + 1,
+                ..RetryPolicy::default()
+            },
+            RetryPolicy {
+                initial_delay_ms: 3_000,
+                max_delay_ms: 2_000,
+                ..RetryPolicy::default()
+            },
+        ] {
+            assert!(policy.validate().is_err(), "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn only_busy_files_count_as_transient() {
+        #[cfg(windows)]
+        let busy = "Failed to delete: The process cannot access the file because it is being used by another process. (os error 32)";
+        #[cfg(not(windows))]
+        let busy = "Failed to delete: Device or resource busy (os error 16)";
+
+        assert!(is_transient(busy));
+        assert!(!is_transient("Failed to delete: No such file or directory"));
+    }
+}
@@ -0,0 +1,62 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::long_paths;
+
+// Survivors listed by name; the rest are only counted
+const MAX_LISTED: usize = 20;
+
+// What is still on disk after a delete. The trash and the removers can
+// report success with files left behind, e.g. when a scanner reopened one
+// mid-delete or the trash only moved part of the tree.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Leftovers {
+    pub files: u64,
+    // Apparent size of the files left
+    #[serde(default)]
+    pub bytes: u64,
+    // Relative to the deleted folder, files first found
+    pub listed: Vec<String>,
+}
+
+// None when `path` is really gone
+pub fn leftovers(path: &Path) -> Option<Leftovers> {
+    let root = long_paths::extended(path);
+    fs::symlink_metadata(&root).ok()?;
+
+    let mut found = Leftovers::default();
+    let mut stack = vec![root.clone()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // Links count as files; what they point at wasn't deleted
+            if file_type.is_dir() {
+                stack.push(entry.path());
+                continue;
+            }
+            found.files += 1;
+            found.bytes += entry.metadata().map_or(0, |metadata| metadata.len());
+            if found.listed.len() < MAX_LISTED {
+                let survivor = entry.path();
+                let relative = survivor.strip_prefix(&root).unwrap_or(&survivor);
+                found.listed.push(relative.to_string_lossy().to_string());
+            }
+        }
+    }
+    Some(found)
+}
+
+// How a result describes what was left
+pub fn describe(leftovers: &Leftovers) -> String {
+    match leftovers.files {
+        0 => "the folder is still there, now empty".to_string(),
+        1 => "1 file was left behind".to_string(),
+        files => format!("{} files were left behind", files),
+    }
+}
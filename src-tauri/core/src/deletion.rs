@@ -0,0 +1,243 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    artifacts, delete_verification::Leftovers, error::Error, long_paths,
+    protect_rules::ProtectRules, system_paths,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteBackend {
+    // Move to the OS trash / recycle bin
+    #[default]
+    Trash,
+    // Move into the app's own quarantine folder
+    Quarantine,
+    // Remove permanently
+    Direct,
+}
+
+// Chosen per delete. Permanent skips the trash whatever backend is selected;
+// a 2 GB tree is slow to move there and frees nothing until it's emptied.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteMode {
+    #[default]
+    Trash,
+    Permanent,
+}
+
+impl DeleteMode {
+    // Permanent deletes can't be undone, so the caller has to confirm them
+    // explicitly rather than just pass the mode
+    pub fn backend(
+        self,
+        selected: DeleteBackend,
+        confirmed: bool,
+    ) -> Result<DeleteBackend, String> {
+        match self {
+            DeleteMode::Trash => Ok(selected),
+            DeleteMode::Permanent if confirmed => Ok(DeleteBackend::Direct),
+            DeleteMode::Permanent => {
+                Err("Permanent deletion has to be confirmed explicitly".to_string())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteStatus {
+    Deleted,
+    Failed,
+    NeedsOverride,
+    // Removed only in part; `leftovers` says what survived
+    Partial,
+    // Never started because the delete was cancelled first
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteResult {
+    pub path: String,
+    pub success: bool,
+    pub status: DeleteStatus,
+    pub error: Option<Error>,
+    // How the folder was removed; differs from the selected backend after a fallback
+    pub method: Option<DeleteBackend>,
+    // Measured just before deleting, or taken from the scan cache
+    pub bytes_freed: Option<u64>,
+    pub files_removed: Option<u64>,
+    // Removal attempts made, more than one after transient failures; 0 when
+    // the folder was never tried
+    #[serde(default)]
+    pub attempts: u32,
+    // Failed on permissions; delete_elevated may get past that
+    #[serde(default)]
+    pub can_elevate: bool,
+    // Removed by the elevated helper
+    #[serde(default)]
+    pub elevated: bool,
+    // Still on disk after the delete ran
+    #[serde(default)]
+    pub leftovers: Option<Leftovers>,
+}
+
+impl DeleteResult {
+    pub fn deleted(path: &str, method: DeleteBackend) -> Self {
+        DeleteResult {
+            path: path.to_string(),
+            success: true,
+            status: DeleteStatus::Deleted,
+            error: None,
+            method: Some(method),
+            bytes_freed: None,
+            files_removed: None,
+            attempts: 1,
+            can_elevate: false,
+            elevated: false,
+            leftovers: None,
+        }
+    }
+
+    pub fn failed(path: &str, error: impl Into<Error>) -> Self {
+        DeleteResult {
+            path: path.to_string(),
+            success: false,
+            status: DeleteStatus::Failed,
+            error: Some(error.into()),
+            method: None,
+            bytes_freed: None,
+            files_removed: None,
+            attempts: 0,
+            can_elevate: false,
+            elevated: false,
+            leftovers: None,
+        }
+    }
+
+    pub fn skipped(path: &str) -> Self {
+        DeleteResult {
+            status: DeleteStatus::Skipped,
+            ..DeleteResult::failed(
+                path,
+                Error::Cancelled("Cancelled before it was deleted".to_string()),
+            )
+        }
+    }
+
+    pub fn needs_override(path: &str, error: impl Into<String>) -> Self {
+        DeleteResult {
+            status: DeleteStatus::NeedsOverride,
+            ..DeleteResult::failed(path, Error::NeedsOverride(error.into()))
+        }
+    }
+}
+
+// Every check a folder has to pass before it may be removed; the failure
+// result if it doesn't
+pub fn check_deletable(
+    path: &str,
+    is_legitimate: bool,
+    protected: &ProtectRules,
+) -> Option<DeleteResult> {
+    let path_buf = PathBuf::from(path);
+    let fs_path = long_paths::extended(&path_buf);
+
+    // Nothing overrides the protect list, not even an explicit request
+    if protected.protects(&path_buf) {
+        return Some(DeleteResult::failed(
+            path,
+            Error::Protected(
+                "Project is protected; remove it from the protect list to delete it".to_string(),
+            ),
+        ));
+    }
+
+    // Enhanced safety checks
+    if !fs_path.exists() {
+        return Some(DeleteResult::failed(
+            path,
+            Error::NotFound("Path does not exist".to_string()),
+        ));
+    }
+
+    if !fs_path.is_dir() {
+        return Some(DeleteResult::failed(
+            path,
+            Error::NotNodeModules("Path is not a directory".to_string()),
+        ));
+    }
+
+    // Reject symlinks/junctions
+    if let Ok(metadata) = fs::symlink_metadata(&fs_path) {
+        if metadata.file_type().is_symlink() {
+            return Some(DeleteResult::failed(
+                path,
+                Error::SymlinkRejected("Cannot delete symlinks/junctions".to_string()),
+            ));
+        }
+    }
+
+    // OS, program and home folders, checked through any links on the way
+    if let Some(reason) = system_paths::denied_reason(&path_buf) {
+        return Some(DeleteResult::failed(path, Error::SystemPath(reason)));
+    }
+
+    // Backup snapshots and trashed items must never be deleted from here
+    #[cfg(target_os = "macos")]
+    {
+        if is_macos_backup_or_trash(&path_buf) {
+            return Some(DeleteResult::failed(
+                path,
+                Error::SystemPath(
+                    "Path is inside a Time Machine backup or trash folder".to_string(),
+                ),
+            ));
+        }
+    }
+
+    // CRITICAL SAFETY CHECK: Ensure it's actually a node_modules directory
+    // (or the cache of a Yarn Plug'n'Play project)
+    if artifacts::kind_of(&path_buf).is_none() {
+        return Some(DeleteResult::failed(
+            path,
+            Error::NotNodeModules(
+                "Path is not a node_modules folder or a recognised build artifact".to_string(),
+            ),
+        ));
+    }
+
+    // Additional safety: Check if this is a legitimate node_modules directory.
+    // The user can still explicitly override this via delete_with_override
+    if !is_legitimate {
+        eprintln!("Legitimacy check failed for: {}", path);
+        return Some(DeleteResult::needs_override(
+            path,
+            "Safety check failed: This doesn't appear to be a legitimate node_modules directory",
+        ));
+    }
+
+    None
+}
+
+// Time Machine snapshots, local backups and trash folders
+#[cfg(target_os = "macos")]
+pub fn is_macos_backup_or_trash(path: &std::path::Path) -> bool {
+    path.components().any(|component| {
+        let name = component.as_os_str().to_string_lossy();
+        matches!(
+            name.as_ref(),
+            ".Trashes"
+                | ".Trash"
+                | ".MobileBackups"
+                | ".timemachine"
+                | "Backups.backupdb"
+                | ".DocumentRevisions-V100"
+        ) || name.starts_with("com.apple.TimeMachine")
+            || name.ends_with(".backupbundle")
+            || name.ends_with(".sparsebundle")
+    })
+}
@@ -0,0 +1,113 @@
+use std::{path::Path, process::Command};
+
+use crate::long_paths;
+
+// Whether a delete failed on permissions, which an administrator could
+// get past
+pub fn is_permission_denied(error: &str) -> bool {
+    [
+        "Permission denied",
+        "Operation not permitted",
+        "Access is denied",
+        "(os error 5)",
+    ]
+    .iter()
+    .any(|marker| error.contains(marker))
+}
+
+// Base64 of UTF-16LE text, the form -EncodedCommand and
+// Text.Encoding.Unicode expect
+#[cfg(windows)]
+fn encode_utf16(units: impl Iterator<Item = u16>) -> String {
+    use base64::Engine;
+
+    let bytes: Vec<u8> = units.flat_map(u16::to_le_bytes).collect();
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+// A helper that removes exactly `path`, run with administrator rights after
+// the OS has asked the user. Links are removed without following them.
+#[cfg(windows)]
+fn elevated_remove(path: &Path) -> Command {
+    use std::os::windows::{ffi::OsStrExt, process::CommandExt};
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    // Nothing from the path is ever parsed as script: it goes in as base64
+    // UTF-16 and is decoded on the elevated side. Quoting it instead isn't
+    // enough, as PowerShell also ends strings at typographic quotes. Links
+    // go first, one at a time, as Windows PowerShell's -Recurse can follow
+    // them.
+    let target = encode_utf16(long_paths::extended(path).as_os_str().encode_wide());
+    let elevated = format!(
+        "$ErrorActionPreference = 'Stop'; \
+         $target = [Text.Encoding]::Unicode.GetString([Convert]::FromBase64String('{0}')); \
+         Get-ChildItem -LiteralPath $target -Recurse -Force -Attributes ReparsePoint | \
+         ForEach-Object {{ $_.Delete() }}; \
+         Remove-Item -LiteralPath $target -Recurse -Force",
+        target
+    );
+    let script = format!(
+        "$p = Start-Process -FilePath powershell.exe \
+         -ArgumentList '-NoProfile','-NonInteractive','-EncodedCommand','{}' \
+         -Verb RunAs -WindowStyle Hidden -Wait -PassThru; exit $p.ExitCode",
+        encode_utf16(elevated.encode_utf16())
+    );
+    let mut command = Command::new("powershell.exe");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn elevated_remove(path: &Path) -> Command {
+    // The path goes in as an argument, so nothing in it is ever parsed
+    let mut command = Command::new("osascript");
+    command
+        .args([
+            "-e",
+            "on run argv",
+            "-e",
+            "do shell script \"/bin/rm -rf -- \" & quoted form of (item 1 of argv) with administrator privileges",
+            "-e",
+            "end run",
+        ])
+        .arg(long_paths::extended(path));
+    command
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn elevated_remove(path: &Path) -> Command {
+    let mut command = Command::new("pkexec");
+    command
+        .args(["rm", "-rf", "--one-file-system", "--"])
+        .arg(long_paths::extended(path));
+    command
+}
+
+// Blocks until the user has answered the prompt and the helper is done.
+// The caller has to have run every safety check on `path` first.
+pub fn remove(path: &Path) -> Result<(), String> {
+    let status = elevated_remove(path)
+        .status()
+        .map_err(|e| format!("Failed to start the elevated delete: {}", e))?;
+    if !status.success() {
+        return Err(match status.code() {
+            Some(code) => format!(
+                "The elevated delete was cancelled or failed (exit code {})",
+                code
+            ),
+            None => "The elevated delete was cancelled or failed".to_string(),
+        });
+    }
+
+    // UAC and polkit can report success for a helper that did nothing
+    if long_paths::extended(path).exists() {
+        return Err(format!(
+            "{} still exists after the elevated delete",
+            path.display()
+        ));
+    }
+    Ok(())
+}
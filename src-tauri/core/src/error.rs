@@ -0,0 +1,124 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// What commands fail with. Serialized as {"code": "not_found", "message":
+// "..."}, so the frontend can act on the code and only shows the message.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "code", content = "message", rename_all = "snake_case")]
+pub enum Error {
+    PermissionDenied(String),
+    NotFound(String),
+    // Not a node_modules folder or recognised build artifact
+    NotNodeModules(String),
+    SymlinkRejected(String),
+    Protected(String),
+    // An OS, program or home folder
+    SystemPath(String),
+    // Failed the legitimacy check; an explicit override can still delete it
+    NeedsOverride(String),
+    TrashUnavailable(String),
+    // Held open by another process
+    Locked(String),
+    Cancelled(String),
+    // A confirmation token that is unknown, used or expired
+    InvalidToken(String),
+    // Arguments or settings out of range
+    InvalidInput(String),
+    // Not available on this platform
+    Unsupported(String),
+    Other(String),
+}
+
+impl Error {
+    pub fn message(&self) -> &str {
+        match self {
+            Error::PermissionDenied(message)
+            | Error::NotFound(message)
+            | Error::NotNodeModules(message)
+            | Error::SymlinkRejected(message)
+            | Error::Protected(message)
+            | Error::SystemPath(message)
+            | Error::NeedsOverride(message)
+            | Error::TrashUnavailable(message)
+            | Error::Locked(message)
+            | Error::Cancelled(message)
+            | Error::InvalidToken(message)
+            | Error::InvalidInput(message)
+            | Error::Unsupported(message)
+            | Error::Other(message) => message,
+        }
+    }
+
+    // The same kind of error with more detail, e.g. who holds a lock
+    pub fn context(self, detail: impl fmt::Display) -> Self {
+        let with = |message: String| format!("{}; {}", message, detail);
+        match self {
+            Error::PermissionDenied(message) => Error::PermissionDenied(with(message)),
+            Error::NotFound(message) => Error::NotFound(with(message)),
+            Error::NotNodeModules(message) => Error::NotNodeModules(with(message)),
+            Error::SymlinkRejected(message) => Error::SymlinkRejected(with(message)),
+            Error::Protected(message) => Error::Protected(with(message)),
+            Error::SystemPath(message) => Error::SystemPath(with(message)),
+            Error::NeedsOverride(message) => Error::NeedsOverride(with(message)),
+            Error::TrashUnavailable(message) => Error::TrashUnavailable(with(message)),
+            Error::Locked(message) => Error::Locked(with(message)),
+            Error::Cancelled(message) => Error::Cancelled(with(message)),
+            Error::InvalidToken(message) => Error::InvalidToken(with(message)),
+            Error::InvalidInput(message) => Error::InvalidInput(with(message)),
+            Error::Unsupported(message) => Error::Unsupported(with(message)),
+            Error::Other(message) => Error::Other(with(message)),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(windows)]
+const NOT_FOUND: [&str; 2] = ["(os error 2)", "(os error 3)"];
+#[cfg(not(windows))]
+const NOT_FOUND: [&str; 1] = ["(os error 2)"];
+
+#[cfg(windows)]
+const LOCKED: [&str; 2] = ["(os error 32)", "(os error 33)"];
+#[cfg(not(windows))]
+const LOCKED: [&str; 1] = ["Resource busy"];
+
+// Most failures start out as messages wrapping an io::Error; the OS error
+// they carry decides the code
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        let has = |markers: &[&str]| markers.iter().any(|marker| message.contains(marker));
+        if crate::elevation::is_permission_denied(&message) {
+            Error::PermissionDenied(message)
+        } else if has(&NOT_FOUND) {
+            Error::NotFound(message)
+        } else if has(&LOCKED) {
+            Error::Locked(message)
+        } else {
+            Error::Other(message)
+        }
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::from(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        match error.kind() {
+            std::io::ErrorKind::PermissionDenied => Error::PermissionDenied(error.to_string()),
+            std::io::ErrorKind::NotFound => Error::NotFound(error.to_string()),
+            _ => Error::Other(error.to_string()),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+// Throwaway folder trees for the tests. Everything is created through
+// long_paths::extended, so on Windows the awkward names and deep paths
+// really end up on disk instead of being mangled by Win32.
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::long_paths;
+
+// Names Win32 path parsing would strip or turn into devices; all of them
+// occur in real node_modules folders
+pub const AWKWARD_NAMES: [&str; 6] = [
+    "trailing space ",
+    "trailing dot.",
+    "aux",
+    "con",
+    "nul.txt",
+    "com1.js",
+];
+
+// Deep enough to pass MAX_PATH several times over, shallow enough for PATH_MAX
+pub const DEEP_LEVELS: usize = 120;
+
+pub struct Fixture {
+    pub root: PathBuf,
+}
+
+impl Fixture {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let root = std::env::temp_dir().join(format!(
+            "nmcleaner-test-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(long_paths::extended(&root)).expect("create fixture root");
+        Fixture { root }
+    }
+
+    // `relative` uses '/' whatever the platform
+    pub fn path(&self, relative: &str) -> PathBuf {
+        relative
+            .split('/')
+            .filter(|part| !part.is_empty())
+            .fold(self.root.clone(), |path, part| path.join(part))
+    }
+
+    pub fn dir(&self, relative: &str) -> PathBuf {
+        let path = self.path(relative);
+        fs::create_dir_all(long_paths::extended(&path)).expect("create fixture directory");
+        path
+    }
+
+    pub fn file(&self, relative: &str, bytes: usize) -> PathBuf {
+        let path = self.path(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(long_paths::extended(parent)).expect("create fixture directory");
+        }
+        fs::write(long_paths::extended(&path), vec![b'x'; bytes]).expect("write fixture file");
+        path
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(long_paths::extended(&self.root));
+    }
+}
+
+// "level-000/level-001/..." down `levels` folders
+pub fn deep_path(levels: usize) -> String {
+    (0..levels)
+        .map(|level| format!("level-{:03}", level))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// A node_modules holding one file under each awkward name, each inside a
+// folder of the same name, plus a package `levels` folders deep. Returns
+// the node_modules path, the file count and the byte count.
+pub fn awkward_node_modules(
+    fixture: &Fixture,
+    project: &str,
+    levels: usize,
+) -> (PathBuf, u64, u64) {
+    let node_modules = fixture.dir(&format!("{}/node_modules", project));
+    let mut files = 0;
+    let mut bytes = 0;
+    for (index, name) in AWKWARD_NAMES.iter().enumerate() {
+        let size = 100 + index;
+        fixture.file(&format!("{}/node_modules/{}/{}", project, name, name), size);
+        files += 1;
+        bytes += size as u64;
+    }
+    fixture.file(
+        &format!("{}/node_modules/{}/index.js", project, deep_path(levels)),
+        1000,
+    );
+    files += 1;
+    bytes += 1000;
+    (node_modules, files, bytes)
+}
@@ -0,0 +1,96 @@
+use std::{fs, path::Path, sync::Arc};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+pub const IGNORE_FILE: &str = ".nmcleanerignore";
+// Honoured only when the scan options ask for it, lowest precedence first
+pub const VCS_IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+// Rules from a `.nmcleanerignore` (and optionally `.gitignore`/`.ignore`)
+// file, chained to the rules of enclosing directories. Patterns use
+// .gitignore syntax relative to the file's folder.
+pub struct IgnoreRules {
+    matcher: Gitignore,
+    parent: Option<Arc<IgnoreRules>>,
+}
+
+impl IgnoreRules {
+    // Loads the ignore files found in `dir` on top of the inherited rules
+    pub fn enter(
+        dir: &Path,
+        ignore_files: &[String],
+        respect_vcs_ignores: bool,
+        parent: Option<Arc<IgnoreRules>>,
+    ) -> Option<Arc<IgnoreRules>> {
+        // Later files win over earlier ones, so our own file goes last
+        let files: Vec<&str> = VCS_IGNORE_FILES
+            .iter()
+            .filter(|_| respect_vcs_ignores)
+            .chain(&[IGNORE_FILE])
+            .copied()
+            .filter(|name| ignore_files.iter().any(|file| file == name))
+            .collect();
+        if files.is_empty() {
+            return parent;
+        }
+
+        let mut builder = GitignoreBuilder::new(dir);
+        for name in &files {
+            if let Some(e) = builder.add(dir.join(name)) {
+                eprintln!("Invalid rule in {}: {}", dir.join(name).display(), e);
+            }
+        }
+
+        match builder.build() {
+            Ok(matcher) => Some(Arc::new(IgnoreRules { matcher, parent })),
+            Err(e) => {
+                eprintln!("Failed to load ignore rules in {}: {}", dir.display(), e);
+                parent
+            }
+        }
+    }
+
+    // The closest ignore file with a matching rule decides, like git does
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.matches(path, is_dir, false)
+    }
+
+    // Rules from VCS ignore files skipped: every .gitignore lists
+    // node_modules, which is exactly what the scan is looking for
+    fn matches(&self, path: &Path, is_dir: bool, own_rules_only: bool) -> bool {
+        let mut rules = Some(self);
+        while let Some(current) = rules {
+            let matched = current.matcher.matched(path, is_dir);
+            let counts = !own_rules_only
+                || matched.inner().is_some_and(|glob| {
+                    glob.from()
+                        .is_some_and(|from| from.file_name() == Some(IGNORE_FILE.as_ref()))
+                });
+            if counts && matched.is_ignore() {
+                return true;
+            }
+            if counts && matched.is_whitelist() {
+                return false;
+            }
+            rules = current.parent.as_deref();
+        }
+        false
+    }
+}
+
+pub fn is_excluded(rules: Option<&Arc<IgnoreRules>>, path: &Path, is_dir: bool) -> bool {
+    rules.is_some_and(|rules| rules.is_excluded(path, is_dir))
+}
+
+pub fn is_node_modules_excluded(rules: Option<&Arc<IgnoreRules>>, path: &Path) -> bool {
+    rules.is_some_and(|rules| rules.matches(path, true, true))
+}
+
+// Names of the ignore files (any kind) present in a directory listing
+pub fn ignore_files_in(entries: &[fs::DirEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name == IGNORE_FILE || VCS_IGNORE_FILES.contains(&name.as_str()))
+        .collect()
+}
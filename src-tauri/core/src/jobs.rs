@@ -0,0 +1,226 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Scan,
+    Size,
+    Delete,
+}
+
+impl JobKind {
+    // A trash move can't be abandoned halfway without misreporting its outcome
+    pub fn can_skip(self) -> bool {
+        self != JobKind::Delete
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobStatus {
+    pub id: u64,
+    pub kind: JobKind,
+    pub current_path: String,
+    pub items_processed: u64,
+    pub seconds_since_progress: u64,
+    pub stalled: bool,
+}
+
+struct JobState {
+    kind: JobKind,
+    current_path: String,
+    items_processed: u64,
+    last_progress: Instant,
+    stalled: bool,
+}
+
+#[derive(Clone)]
+pub struct Job {
+    id: u64,
+    state: Arc<Mutex<JobState>>,
+    skip: Arc<Notify>,
+    cancel: CancellationToken,
+}
+
+impl Job {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn progress(&self, current_path: &Path) {
+        if let Ok(mut state) = self.state.lock() {
+            state.current_path = current_path.to_string_lossy().to_string();
+            state.items_processed += 1;
+            state.last_progress = Instant::now();
+            state.stalled = false;
+        }
+    }
+
+    // Shows the job is alive while one long step (e.g. sizing a huge tree) runs
+    pub fn touch(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.last_progress = Instant::now();
+            state.stalled = false;
+        }
+    }
+
+    // Resolves when the user asks to skip whatever the job is currently stuck on.
+    // Only operations awaiting this at the time of the request are affected.
+    pub async fn skip_requested(&self) {
+        self.skip.notified().await
+    }
+
+    pub fn skip(&self) {
+        self.skip.notify_waiters();
+    }
+
+    // Cancelled by stop_all; blocking work polls it between steps
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    pub fn status(&self) -> Option<JobStatus> {
+        let state = self.state.lock().ok()?;
+        Some(JobStatus {
+            id: self.id,
+            kind: state.kind,
+            current_path: state.current_path.clone(),
+            items_processed: state.items_processed,
+            seconds_since_progress: state.last_progress.elapsed().as_secs(),
+            stalled: state.stalled,
+        })
+    }
+
+    // Flags the job once it has gone `stall_after` without progress; true
+    // only the first time, progress clears the flag again
+    fn check_stalled(&self, stall_after: Duration) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        if state.stalled || state.last_progress.elapsed() < stall_after {
+            return false;
+        }
+        state.stalled = true;
+        true
+    }
+}
+
+#[derive(Default)]
+struct RegistryInner {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, Job>>,
+    // Parent of every token handed out; replaced once it has been cancelled
+    root: Mutex<CancellationToken>,
+}
+
+// Every long-running operation, so they can be watched for stalls and
+// stopped together
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+// Unregisters the job once the operation that owns it finishes
+pub struct JobGuard {
+    registry: JobRegistry,
+    job: Job,
+}
+
+impl std::ops::Deref for JobGuard {
+    type Target = Job;
+
+    fn deref(&self) -> &Job {
+        &self.job
+    }
+}
+
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = self.registry.inner.jobs.lock() {
+            jobs.remove(&self.job.id);
+        }
+    }
+}
+
+impl JobRegistry {
+    pub fn start(&self, kind: JobKind) -> JobGuard {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let job = Job {
+            id,
+            state: Arc::new(Mutex::new(JobState {
+                kind,
+                current_path: String::new(),
+                items_processed: 0,
+                last_progress: Instant::now(),
+                stalled: false,
+            })),
+            skip: Arc::new(Notify::new()),
+            cancel: self.child_token(),
+        };
+
+        if let Ok(mut jobs) = self.inner.jobs.lock() {
+            jobs.insert(id, job.clone());
+        }
+
+        JobGuard {
+            registry: self.clone(),
+            job,
+        }
+    }
+
+    pub fn jobs(&self) -> Vec<Job> {
+        self.inner
+            .jobs
+            .lock()
+            .map(|jobs| jobs.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        self.jobs().iter().filter_map(Job::status).collect()
+    }
+
+    // Jobs that just went `stall_after` without progress; each stall is
+    // reported once
+    pub fn newly_stalled(&self, stall_after: Duration) -> Vec<JobStatus> {
+        self.jobs()
+            .iter()
+            .filter(|job| job.check_stalled(stall_after))
+            .filter_map(Job::status)
+            .collect()
+    }
+
+    pub fn find(&self, id: u64) -> Option<Job> {
+        self.inner.jobs.lock().ok()?.get(&id).cloned()
+    }
+
+    // For work that isn't tracked as a job but should still stop with everything else
+    pub fn child_token(&self) -> CancellationToken {
+        self.inner
+            .root
+            .lock()
+            .map(|root| root.child_token())
+            .unwrap_or_default()
+    }
+
+    // Cancels every token handed out so far; work started afterwards is unaffected.
+    // Returns how many jobs were running.
+    pub fn stop_all(&self) -> usize {
+        if let Ok(mut root) = self.inner.root.lock() {
+            root.cancel();
+            *root = CancellationToken::new();
+        }
+        self.jobs().len()
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{sync::Semaphore, task};
+use tokio_util::sync::CancellationToken;
+
+use crate::long_paths;
+
+const PARENT_INDICATORS: [&str; 5] = [
+    "package.json",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SafetyCheckOptions {
+    // Checks running at once on local disks
+    pub local_concurrency: usize,
+    // Checks running at once against any single network share; SMB servers
+    // slow down sharply once too many requests are in flight
+    pub network_concurrency: usize,
+}
+
+impl Default for SafetyCheckOptions {
+    fn default() -> Self {
+        SafetyCheckOptions {
+            local_concurrency: 32,
+            network_concurrency: 4,
+        }
+    }
+}
+
+impl SafetyCheckOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=256).contains(&self.local_concurrency) {
+            return Err("Local concurrency must be between 1 and 256".to_string());
+        }
+        if !(1..=64).contains(&self.network_concurrency) {
+            return Err("Network concurrency must be between 1 and 64".to_string());
+        }
+        Ok(())
+    }
+}
+
+// The network share holding `path`, or None for local disks
+pub fn network_share(path: &Path) -> Option<String> {
+    #[cfg(windows)]
+    {
+        use std::path::{Component, Prefix};
+        use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+
+        // From WindowsProgramming, which isn't worth a feature for one constant
+        const DRIVE_REMOTE: u32 = 4;
+
+        match path.components().next() {
+            Some(Component::Prefix(prefix)) => match prefix.kind() {
+                Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => Some(
+                    format!(
+                        r"\\{}\{}",
+                        server.to_string_lossy(),
+                        share.to_string_lossy()
+                    )
+                    .to_lowercase(),
+                ),
+                Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                    let root: Vec<u16> = format!("{}:\\", letter as char)
+                        .encode_utf16()
+                        .chain(Some(0))
+                        .collect();
+                    // SAFETY: root is a NUL-terminated drive root
+                    let drive_type = unsafe { GetDriveTypeW(root.as_ptr()) };
+                    (drive_type == DRIVE_REMOTE)
+                        .then(|| format!("{}:", (letter as char).to_ascii_uppercase()))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use std::{
+            ffi::{CStr, CString},
+            os::unix::ffi::OsStrExt,
+        };
+
+        let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        // SAFETY: statfs is plain data, so all zeroes is a valid value
+        let mut stats: libc::statfs = unsafe { std::mem::zeroed() };
+        // SAFETY: path is NUL-terminated and stats is a valid out-param
+        if unsafe { libc::statfs(path.as_ptr(), &mut stats) } != 0 {
+            return None;
+        }
+
+        // SAFETY: the kernel fills both fields with NUL-terminated strings
+        let (fs_type, source) = unsafe {
+            (
+                CStr::from_ptr(stats.f_fstypename.as_ptr()),
+                CStr::from_ptr(stats.f_mntfromname.as_ptr()),
+            )
+        };
+        matches!(fs_type.to_bytes(), b"smbfs" | b"nfs" | b"afpfs" | b"webdav")
+            .then(|| source.to_string_lossy().to_string())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        crate::snapshots::network_source(path)
+    }
+}
+
+// State shared by the checks of one batch
+#[derive(Default)]
+struct CheckSession {
+    // Whether a project folder holds package.json or a lockfile
+    parent_indicators: Mutex<HashMap<PathBuf, bool>>,
+}
+
+impl CheckSession {
+    fn has_parent_indicators(&self, parent: &Path) -> bool {
+        if let Some(found) = self
+            .parent_indicators
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(parent).copied())
+        {
+            return found;
+        }
+
+        // One listing instead of a stat per indicator, which adds up over SMB
+        let found = fs::read_dir(parent)
+            .map(|entries| {
+                entries.flatten().any(|entry| {
+                    PARENT_INDICATORS.contains(&entry.file_name().to_string_lossy().as_ref())
+                })
+            })
+            .unwrap_or(false);
+
+        if let Ok(mut cache) = self.parent_indicators.lock() {
+            cache.insert(parent.to_path_buf(), found);
+        }
+        found
+    }
+}
+
+// Verifies a selection of node_modules folders, with at most
+// `network_concurrency` checks in flight against each network share.
+// Checks still waiting when `cancel` fires count as failed.
+pub async fn check_batch(
+    paths: &[String],
+    options: &SafetyCheckOptions,
+    cancel: &CancellationToken,
+) -> Vec<bool> {
+    let lookup = paths.to_vec();
+    let shares = task::spawn_blocking(move || {
+        lookup
+            .iter()
+            .map(|path| network_share(Path::new(path)))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .unwrap_or_else(|_| vec![None; paths.len()]);
+
+    let session = Arc::new(CheckSession::default());
+    let local = Arc::new(Semaphore::new(options.local_concurrency));
+    let mut per_share: HashMap<String, Arc<Semaphore>> = HashMap::new();
+    let mut checks = task::JoinSet::new();
+
+    for (index, (path, share)) in paths.iter().zip(shares).enumerate() {
+        let limit = match share {
+            Some(share) => per_share
+                .entry(share)
+                .or_insert_with(|| Arc::new(Semaphore::new(options.network_concurrency)))
+                .clone(),
+            None => local.clone(),
+        };
+        let session = session.clone();
+        let path = PathBuf::from(path);
+        let cancel = cancel.clone();
+        checks.spawn(async move {
+            let Ok(_permit) = limit.acquire_owned().await else {
+                return (index, false);
+            };
+            if cancel.is_cancelled() {
+                return (index, false);
+            }
+            (index, is_legitimate_node_modules(&session, &path).await)
+        });
+    }
+
+    // Anything that failed to report back is treated as not legitimate
+    let mut verdicts = vec![false; paths.len()];
+    while let Some(result) = checks.join_next().await {
+        if let Ok((index, is_legitimate)) = result {
+            verdicts[index] = is_legitimate;
+        }
+    }
+
+    verdicts
+}
+
+async fn is_legitimate_node_modules(session: &Arc<CheckSession>, path: &Path) -> bool {
+    let path = long_paths::extended(path);
+    let session = session.clone();
+
+    // Run legitimacy check in a blocking thread pool
+    task::spawn_blocking(move || {
+        // First, check if parent directory has package.json or lockfiles
+        if let Some(parent) = path.parent() {
+            if !session.has_parent_indicators(parent) {
+                eprintln!("No parent indicators found for: {}", path.display());
+                // For debugging, let's be more lenient and continue with the check
+                // return false;
+            }
+        }
+
+        // A pnpm top level is mostly links into .pnpm, which the listing
+        // below skips
+        if crate::pnpm::is_pnpm_layout(&path) {
+            return true;
+        }
+
+        // Not package folders; kind_of has already checked for what marks
+        // each kind as generated
+        if crate::artifacts::kind_of(&path)
+            .is_some_and(|kind| kind != crate::artifacts::ArtifactKind::NodeModules)
+        {
+            return true;
+        }
+
+        // Check if this directory contains typical node_modules contents
+        if let Ok(entries) = fs::read_dir(&path) {
+            let mut has_package_json = false;
+            let mut has_node_modules_structure = false;
+            let mut entry_count = 0;
+
+            for entry in entries.flatten() {
+                entry_count += 1;
+
+                // Reject symlinks/junctions; the listing already knows the type,
+                // so this costs no extra round trip
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_symlink() {
+                    continue;
+                }
+
+                let name = entry.file_name();
+                let name_str = name.to_string_lossy();
+                if file_type.is_dir() {
+                    // Check for common package directories
+                    if name_str.contains('.') && name_str.len() > 3 {
+                        has_node_modules_structure = true;
+                    }
+                } else if file_type.is_file() {
+                    // Check for package.json or similar files
+                    if name_str == "package.json" || name_str == "package-lock.json" {
+                        has_package_json = true;
+                    }
+                }
+
+                // Limit check to first 50 entries for performance
+                if entry_count > 50 {
+                    break;
+                }
+            }
+
+            // Must have either typical structure or package files
+            has_node_modules_structure || has_package_json
+        } else {
+            false
+        }
+    })
+    .await
+    .unwrap_or(false)
+}
@@ -0,0 +1,33 @@
+// Everything that finds, measures, checks and removes node_modules folders.
+// The desktop app wraps these in Tauri commands; nothing here depends on Tauri.
+
+pub mod activity;
+pub mod artifacts;
+pub mod cloud_sync;
+pub mod delete_backend;
+pub mod delete_retry;
+pub mod delete_verification;
+pub mod deletion;
+pub mod elevation;
+pub mod error;
+#[cfg(test)]
+mod fixtures;
+pub mod ignore_rules;
+pub mod jobs;
+pub mod legitimacy;
+pub mod lock_holders;
+pub mod long_paths;
+pub mod nested_installs;
+pub mod pnpm;
+pub mod protect_rules;
+pub mod remover;
+pub mod scan;
+pub mod scan_estimate;
+pub mod scan_options;
+pub mod scan_patterns;
+pub mod scan_summary;
+pub mod sizing;
+pub mod snapshots;
+pub mod system_paths;
+pub mod throttle;
+pub mod traversal;
@@ -0,0 +1,218 @@
+use std::path::Path;
+
+#[cfg(windows)]
+use crate::long_paths;
+
+// A process holding a file open below a folder that failed to delete
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockHolder {
+    pub name: String,
+    pub pid: u32,
+}
+
+// The Restart Manager takes files, not folders. A dev server or editor
+// usually holds a handful near the top, so only that many are asked about;
+// registering a whole node_modules would take longer than the delete.
+#[cfg(windows)]
+const MAX_PROBED_FILES: usize = 4096;
+
+#[cfg(windows)]
+fn probed_files(root: &Path) -> Vec<Vec<u16>> {
+    use std::os::windows::ffi::OsStrExt;
+
+    let mut files = Vec::new();
+    let mut queue = std::collections::VecDeque::from([root.to_path_buf()]);
+    while let Some(dir) = queue.pop_front() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                queue.push_back(entry.path());
+            } else if file_type.is_file() {
+                let wide = entry
+                    .path()
+                    .as_os_str()
+                    .encode_wide()
+                    .chain(Some(0))
+                    .collect();
+                files.push(wide);
+                if files.len() == MAX_PROBED_FILES {
+                    return files;
+                }
+            }
+        }
+    }
+    files
+}
+
+#[cfg(windows)]
+fn image_name(pid: u32) -> Option<String> {
+    use windows_sys::Win32::{
+        Foundation::CloseHandle,
+        System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        },
+    };
+
+    // SAFETY: plain call; a null handle is checked below
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if process.is_null() {
+        return None;
+    }
+    let mut buffer = [0u16; 1024];
+    let mut length = buffer.len() as u32;
+    // SAFETY: process is open and buffer holds `length` characters
+    let ok = unsafe {
+        QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            buffer.as_mut_ptr(),
+            &mut length,
+        )
+    };
+    // SAFETY: process was opened above and isn't used afterwards
+    unsafe { CloseHandle(process) };
+    if ok == 0 {
+        return None;
+    }
+    let image = String::from_utf16_lossy(&buffer[..length as usize]);
+    Path::new(&image)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+// Asks the Restart Manager which processes have files below `root` open
+#[cfg(windows)]
+fn find(root: &Path) -> Vec<LockHolder> {
+    use std::ptr;
+    use windows_sys::Win32::{
+        Foundation::{ERROR_MORE_DATA, ERROR_SUCCESS},
+        System::RestartManager::{
+            RmEndSession, RmGetList, RmRegisterResources, RmStartSession, CCH_RM_SESSION_KEY,
+            RM_PROCESS_INFO,
+        },
+    };
+
+    let files = probed_files(&long_paths::extended(root));
+    if files.is_empty() {
+        return Vec::new();
+    }
+    let names: Vec<*const u16> = files.iter().map(|file| file.as_ptr()).collect();
+
+    let mut session = 0u32;
+    let mut key = [0u16; CCH_RM_SESSION_KEY as usize + 1];
+    // SAFETY: session is a valid out-param and key holds a session key
+    if unsafe { RmStartSession(&mut session, 0, key.as_mut_ptr()) } != ERROR_SUCCESS {
+        return Vec::new();
+    }
+
+    let mut holders = Vec::new();
+    // SAFETY: names points at `names.len()` NUL-terminated paths that
+    // outlive the session
+    let registered = unsafe {
+        RmRegisterResources(
+            session,
+            names.len() as u32,
+            names.as_ptr(),
+            0,
+            ptr::null(),
+            0,
+            ptr::null(),
+        )
+    };
+    if registered == ERROR_SUCCESS {
+        // The list can grow between the two calls, hence the loop
+        let mut processes: Vec<RM_PROCESS_INFO> = Vec::new();
+        loop {
+            let mut needed = 0u32;
+            let mut count = processes.len() as u32;
+            let mut reasons = 0u32;
+            // SAFETY: processes has room for `count` entries
+            let status = unsafe {
+                RmGetList(
+                    session,
+                    &mut needed,
+                    &mut count,
+                    processes.as_mut_ptr(),
+                    &mut reasons,
+                )
+            };
+            if status == ERROR_MORE_DATA {
+                // SAFETY: RM_PROCESS_INFO is plain data, for which zeroes are valid
+                processes = vec![unsafe { std::mem::zeroed() }; needed as usize];
+                continue;
+            }
+            if status == ERROR_SUCCESS {
+                let own_pid = std::process::id();
+                holders = processes[..count as usize]
+                    .iter()
+                    .map(|process| process.Process.dwProcessId)
+                    .filter(|&pid| pid != own_pid)
+                    .map(|pid| LockHolder {
+                        name: image_name(pid).unwrap_or_else(|| "unknown process".to_string()),
+                        pid,
+                    })
+                    .collect();
+            }
+            break;
+        }
+    }
+
+    // SAFETY: session was started above and isn't used afterwards
+    unsafe { RmEndSession(session) };
+    holders
+}
+
+// lsof +D walks the whole tree, which is slow on a big node_modules, but
+// this only runs after a delete has already failed
+#[cfg(unix)]
+fn find(root: &Path) -> Vec<LockHolder> {
+    let Ok(output) = std::process::Command::new("lsof")
+        .args(["-F", "pc", "+D"])
+        .arg(root)
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    // -F prints one "p<pid>" line per process, followed by "c<command>"
+    let own_pid = std::process::id();
+    let mut holders: Vec<LockHolder> = Vec::new();
+    let mut pid = None;
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(value) = line.strip_prefix('p') {
+            pid = value.parse::<u32>().ok().filter(|&pid| pid != own_pid);
+        } else if let (Some(name), Some(pid)) = (line.strip_prefix('c'), pid) {
+            holders.push(LockHolder {
+                name: name.to_string(),
+                pid,
+            });
+        }
+    }
+    holders.dedup();
+    holders
+}
+
+#[cfg(not(any(windows, unix)))]
+fn find(_root: &Path) -> Vec<LockHolder> {
+    Vec::new()
+}
+
+// "locked by node.exe (PID 1234)", or None when no process could be found
+// holding files below `root`
+pub fn describe(root: &Path) -> Option<String> {
+    let holders = find(root);
+    if holders.is_empty() {
+        return None;
+    }
+    let list: Vec<String> = holders
+        .iter()
+        .map(|holder| format!("{} (PID {})", holder.name, holder.pid))
+        .collect();
+    Some(format!("locked by {}", list.join(", ")))
+}
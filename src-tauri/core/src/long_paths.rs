@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+// Win32 path parsing strips trailing spaces and dots, maps names like `aux`
+// or `con` to devices and stops at MAX_PATH. All of these show up inside
+// node_modules, so filesystem calls go through the verbatim `\\?\` form,
+// which hands the path to NTFS untouched and allows ~32k characters.
+// Elsewhere paths are passed through unchanged.
+pub fn extended(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::{
+            ffi::OsString,
+            path::{Component, Prefix},
+        };
+
+        let mut components = path.components();
+        let Some(Component::Prefix(prefix)) = components.next() else {
+            // Relative paths can't be made verbatim without resolving them first
+            return path.to_path_buf();
+        };
+
+        let mut out = match prefix.kind() {
+            Prefix::Disk(_) => {
+                let mut out = OsString::from(r"\\?\");
+                out.push(prefix.as_os_str());
+                out
+            }
+            Prefix::UNC(server, share) => {
+                let mut out = OsString::from(r"\\?\UNC\");
+                out.push(server);
+                out.push(r"\");
+                out.push(share);
+                out
+            }
+            // Already verbatim or a device path
+            _ => return path.to_path_buf(),
+        };
+
+        // Verbatim paths skip normalization, so resolve `.`, `..` and `/` here
+        let mut parts = Vec::new();
+        for component in components {
+            match component {
+                Component::Normal(part) => parts.push(part),
+                Component::ParentDir => {
+                    parts.pop();
+                }
+                _ => {}
+            }
+        }
+
+        if parts.is_empty() {
+            out.push(r"\");
+        }
+        for part in parts {
+            out.push(r"\");
+            out.push(part);
+        }
+        PathBuf::from(out)
+    }
+
+    #[cfg(not(windows))]
+    {
+        path.to_path_buf()
+    }
+}
+
+// The reverse of `extended`: `\\?\C:\x` back to `C:\x` and `\\?\UNC\s\x`
+// to `\\s\x`, e.g. for what fs::canonicalize returns on Windows
+pub fn plain(path: &Path) -> PathBuf {
+    let text = path.to_string_lossy();
+    if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", rest));
+    }
+    match text.strip_prefix(r"\\?\") {
+        Some(rest) => PathBuf::from(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{ffi::OsString, fs};
+
+    use super::*;
+    use crate::fixtures::{self, Fixture, AWKWARD_NAMES};
+
+    #[cfg(windows)]
+    #[test]
+    fn extended_makes_paths_verbatim() {
+        let cases = [
+            (r"C:\work\app\node_modules", r"\\?\C:\work\app\node_modules"),
+            (
+                r"C:\work\.\app\..\web/node_modules",
+                r"\\?\C:\work\web\node_modules",
+            ),
+            (
+                r"C:\work\trailing dot.\aux",
+                r"\\?\C:\work\trailing dot.\aux",
+            ),
+            (r"C:\", r"\\?\C:\"),
+            (r"\\server\share\app", r"\\?\UNC\server\share\app"),
+            (r"\\?\C:\already", r"\\?\C:\already"),
+            (r"relative\app", r"relative\app"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                extended(Path::new(path)),
+                PathBuf::from(expected),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn extended_leaves_paths_alone() {
+        for path in [
+            "/work/trailing dot./aux",
+            "/work/trailing space /con",
+            "relative",
+        ] {
+            assert_eq!(extended(Path::new(path)), PathBuf::from(path));
+        }
+    }
+
+    #[test]
+    fn plain_undoes_extended() {
+        let cases = [
+            (r"\\?\C:\work\app", r"C:\work\app"),
+            (r"\\?\UNC\server\share\app", r"\\server\share\app"),
+            ("/work/app", "/work/app"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(plain(Path::new(path)), PathBuf::from(expected));
+        }
+    }
+
+    #[test]
+    fn awkward_names_survive_a_round_trip() {
+        let fixture = Fixture::new("long-paths");
+        for name in AWKWARD_NAMES {
+            let file = fixture.file(&format!("{}/{}", name, name), 3);
+            assert_eq!(fs::read(extended(&file)).unwrap(), b"xxx", "{}", name);
+
+            let listed: Vec<OsString> = fs::read_dir(extended(file.parent().unwrap()))
+                .unwrap()
+                .flatten()
+                .map(|entry| entry.file_name())
+                .collect();
+            assert_eq!(listed, vec![OsString::from(name)]);
+        }
+    }
+
+    #[test]
+    fn deep_paths_survive_a_round_trip() {
+        let fixture = Fixture::new("long-paths-deep");
+        let deep = fixtures::deep_path(fixtures::DEEP_LEVELS);
+        let file = fixture.file(&format!("{}/index.js", deep), 10);
+        assert!(file.as_os_str().len() > 260);
+        assert_eq!(fs::metadata(extended(&file)).unwrap().len(), 10);
+    }
+}
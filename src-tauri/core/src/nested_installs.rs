@@ -0,0 +1,221 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::long_paths;
+
+// Caps the work for huge trees; old npm layouts can nest thousands deep
+const MAX_PACKAGES: usize = 20_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NestedInstall {
+    pub path: String,
+    // 1 for node_modules/foo/node_modules, 2 one package further down, ...
+    pub depth: usize,
+}
+
+fn real_directories(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        // Reject symlinks/junctions; pnpm and workspaces link packages in
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path(),
+            )
+        })
+        .collect()
+}
+
+// Packages in one node_modules, looking inside @scope folders
+fn packages(node_modules: &Path) -> Vec<PathBuf> {
+    let mut packages = Vec::new();
+    for (name, path) in real_directories(node_modules) {
+        // .bin, .cache and pnpm's .pnpm store aren't packages
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.starts_with('@') {
+            packages.extend(real_directories(&path).into_iter().map(|(_, path)| path));
+        } else {
+            packages.push(path);
+        }
+    }
+    packages
+}
+
+// node_modules folders installed below packages of `node_modules`, up to
+// `max_depth` levels down
+pub fn find(node_modules: &Path, max_depth: usize) -> Vec<NestedInstall> {
+    let root = long_paths::extended(node_modules);
+    let mut found = Vec::new();
+    let mut stack = vec![(root.clone(), 1)];
+    let mut visited = 0;
+
+    while let Some((dir, depth)) = stack.pop() {
+        for package in packages(&dir) {
+            visited += 1;
+            if visited > MAX_PACKAGES {
+                return found;
+            }
+
+            let nested = package.join("node_modules");
+            let is_directory =
+                fs::symlink_metadata(&nested).is_ok_and(|metadata| metadata.file_type().is_dir());
+            if !is_directory {
+                continue;
+            }
+
+            // Reported as given rather than in verbatim form
+            let relative = nested.strip_prefix(&root).unwrap_or(&nested);
+            found.push(NestedInstall {
+                path: node_modules.join(relative).to_string_lossy().to_string(),
+                depth,
+            });
+            if depth < max_depth {
+                stack.push((nested, depth + 1));
+            }
+        }
+    }
+
+    found
+}
+
+// A selected node_modules that sits inside another node_modules
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NestedSelection {
+    pub path: String,
+    // The outermost selected node_modules above it, or the nearest one when
+    // none is selected
+    pub parent: String,
+    // Selected too, so it goes along with the parent instead of on its own
+    pub parent_selected: bool,
+}
+
+fn node_modules_above(path: &Path) -> impl Iterator<Item = &Path> {
+    path.ancestors()
+        .skip(1)
+        .filter(|ancestor| ancestor.file_name() == Some(std::ffi::OsStr::new("node_modules")))
+}
+
+// Deleting foo/node_modules/bar/node_modules while keeping foo/node_modules
+// frees little and breaks bar, so such picks are reported; the caller
+// refuses them unless the parent is picked as well
+pub fn check_selection(paths: &[String]) -> Vec<NestedSelection> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let above: Vec<&Path> = node_modules_above(Path::new(path)).collect();
+            let selected = above
+                .iter()
+                .rev()
+                .find(|ancestor| paths.iter().any(|other| Path::new(other) == **ancestor));
+            let (parent, parent_selected) = match selected {
+                Some(parent) => (parent, true),
+                None => (above.first()?, false),
+            };
+            Some(NestedSelection {
+                path: path.clone(),
+                parent: parent.to_string_lossy().to_string(),
+                parent_selected,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::Fixture;
+
+    fn found_paths(node_modules: &Path, max_depth: usize) -> Vec<(PathBuf, usize)> {
+        let mut found: Vec<(PathBuf, usize)> = find(node_modules, max_depth)
+            .into_iter()
+            .map(|nested| {
+                let relative = Path::new(&nested.path)
+                    .strip_prefix(node_modules)
+                    .unwrap()
+                    .to_path_buf();
+                (relative, nested.depth)
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
+    fn relative(path: &str) -> PathBuf {
+        path.split('/').collect()
+    }
+
+    #[test]
+    fn finds_installs_below_packages_and_scopes() {
+        let fixture = Fixture::new("nested-find");
+        fixture.dir("app/node_modules/left-pad/node_modules/tiny/node_modules");
+        fixture.dir("app/node_modules/@scope/pkg/node_modules");
+        fixture.dir("app/node_modules/.pnpm/store/node_modules");
+        fixture.dir("app/node_modules/plain");
+        let node_modules = fixture.path("app/node_modules");
+
+        assert_eq!(
+            found_paths(&node_modules, 3),
+            [
+                (relative("@scope/pkg/node_modules"), 1),
+                (relative("left-pad/node_modules"), 1),
+                (relative("left-pad/node_modules/tiny/node_modules"), 2),
+            ]
+        );
+        // Deeper levels are left out past the limit
+        assert_eq!(
+            found_paths(&node_modules, 1),
+            [
+                (relative("@scope/pkg/node_modules"), 1),
+                (relative("left-pad/node_modules"), 1),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_packages_are_not_followed() {
+        let fixture = Fixture::new("nested-links");
+        let target = fixture.dir("store/linked");
+        fixture.dir("store/linked/node_modules");
+        let node_modules = fixture.dir("app/node_modules");
+        std::os::unix::fs::symlink(&target, node_modules.join("linked")).unwrap();
+
+        assert!(find(&node_modules, 3).is_empty());
+    }
+
+    fn selection(paths: &[&str]) -> Vec<(String, String, bool)> {
+        let paths: Vec<String> = paths.iter().map(|path| path.to_string()).collect();
+        check_selection(&paths)
+            .into_iter()
+            .map(|nested| (nested.path, nested.parent, nested.parent_selected))
+            .collect()
+    }
+
+    #[test]
+    fn nested_picks_are_reported_with_their_parent() {
+        let outer = "/work/app/node_modules";
+        let inner = "/work/app/node_modules/left-pad/node_modules";
+        let innermost = "/work/app/node_modules/left-pad/node_modules/tiny/node_modules";
+
+        assert!(selection(&[outer, "/work/other/node_modules"]).is_empty());
+        assert_eq!(
+            selection(&[inner]),
+            [(inner.to_string(), outer.to_string(), false)]
+        );
+        // The outermost selected parent wins over a nearer unselected one
+        assert_eq!(
+            selection(&[outer, innermost]),
+            [(innermost.to_string(), outer.to_string(), true)]
+        );
+    }
+}
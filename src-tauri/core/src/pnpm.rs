@@ -0,0 +1,54 @@
+use std::{fs::Metadata, path::Path};
+
+use crate::long_paths;
+
+// pnpm keeps every package in node_modules/.pnpm and links the top level
+// into it; the files there are hard links into the global
+// content-addressable store
+pub fn is_pnpm_layout(node_modules: &Path) -> bool {
+    std::fs::symlink_metadata(long_paths::extended(&node_modules.join(".pnpm")))
+        .is_ok_and(|metadata| metadata.is_dir())
+}
+
+#[cfg(windows)]
+fn link_count(path: &Path) -> Option<u32> {
+    use std::os::windows::{fs::OpenOptionsExt, io::AsRawHandle};
+    use windows_sys::Win32::Storage::FileSystem::{
+        GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+        FILE_FLAG_OPEN_REPARSE_POINT,
+    };
+
+    // No access rights are needed to read the file information
+    let file = std::fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT)
+        .open(path)
+        .ok()?;
+    // SAFETY: BY_HANDLE_FILE_INFORMATION is plain data, all zeroes is valid
+    let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+    // SAFETY: the handle stays open for the call and info is a valid out-param
+    let ok = unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) };
+    (ok != 0).then_some(info.nNumberOfLinks)
+}
+
+// A file with other hard links keeps its data when this name goes, which is
+// how everything in a pnpm .pnpm folder stays in the store. Unix has the
+// link count in the metadata already; Windows needs a handle per file, so
+// there only files under .pnpm are asked.
+pub fn is_shared_with_store(path: &Path, metadata: &Metadata) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let _ = path;
+        metadata.nlink() > 1
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = metadata;
+        path.components()
+            .any(|component| component.as_os_str() == ".pnpm")
+            && link_count(path).is_some_and(|links| links > 1)
+    }
+}
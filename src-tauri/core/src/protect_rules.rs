@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use globset::{GlobSet, GlobSetBuilder};
+
+use crate::{artifacts, scan_patterns};
+
+fn is_glob(entry: &str) -> bool {
+    entry.contains(['*', '?', '[', '{'])
+}
+
+// The protect list compiled for matching. An entry is either an exact
+// project or node_modules path, or a glob like `D:/work/**` matched the
+// same way as scan patterns.
+#[derive(Debug, Default)]
+pub struct ProtectRules {
+    entries: Vec<String>,
+    exact: Vec<Vec<String>>,
+    globs: Option<GlobSet>,
+}
+
+impl ProtectRules {
+    pub fn compile(entries: Vec<String>) -> Result<Self, String> {
+        let mut exact = Vec::new();
+        let mut builder = GlobSetBuilder::new();
+        let mut has_globs = false;
+        for entry in &entries {
+            if is_glob(entry) {
+                builder.add(
+                    scan_patterns::glob(entry)
+                        .map_err(|e| format!("Invalid pattern {}: {}", entry, e))?,
+                );
+                has_globs = true;
+            } else {
+                exact.push(scan_patterns::components(entry));
+            }
+        }
+        let globs = if has_globs {
+            Some(
+                builder
+                    .build()
+                    .map_err(|e| format!("Failed to compile patterns: {}", e))?,
+            )
+        } else {
+            None
+        };
+
+        Ok(ProtectRules {
+            entries,
+            exact,
+            globs,
+        })
+    }
+
+    // The entries as the user wrote them
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    fn matches(&self, path: &Path) -> bool {
+        let path = scan_patterns::normalize(&path.to_string_lossy());
+        let components = scan_patterns::components(&path);
+        self.exact.contains(&components)
+            || self.globs.as_ref().is_some_and(|set| set.is_match(&path))
+    }
+
+    // Whether a node_modules folder, or the project it belongs to, is on
+    // the list
+    pub fn protects(&self, node_modules: &Path) -> bool {
+        self.matches(node_modules)
+            || artifacts::project_of(node_modules).is_some_and(|project| self.matches(project))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn rules(entries: &[&str]) -> ProtectRules {
+        ProtectRules::compile(entries.iter().map(|entry| entry.to_string()).collect()).unwrap()
+    }
+
+    fn node_modules(project: &str) -> PathBuf {
+        Path::new(project).join("node_modules")
+    }
+
+    #[test]
+    fn an_exact_project_protects_its_node_modules() {
+        let rules = rules(&["/work/keep"]);
+        assert!(rules.protects(&node_modules("/work/keep")));
+        assert!(rules.protects(&node_modules("/work/keep/")));
+        assert!(!rules.protects(&node_modules("/work/keep-not")));
+        assert!(!rules.protects(&node_modules("/work/keep/packages/app")));
+    }
+
+    #[test]
+    fn an_exact_node_modules_is_protected() {
+        let rules = rules(&["/work/app/node_modules"]);
+        assert!(rules.protects(&node_modules("/work/app")));
+        assert!(!rules.protects(&node_modules("/work/other")));
+    }
+
+    #[test]
+    fn globs_match_like_scan_patterns() {
+        let clients = rules(&["/work/clients/**"]);
+        assert!(clients.protects(&node_modules("/work/clients/acme")));
+        assert!(clients.protects(&node_modules("/work/clients/acme/packages/web")));
+        assert!(!clients.protects(&node_modules("/work/internal")));
+
+        // A single star stays within one folder
+        let one_level = rules(&["/work/*/node_modules"]);
+        assert!(one_level.protects(&node_modules("/work/app")));
+        assert!(!one_level.protects(&node_modules("/work/app/packages/web")));
+    }
+
+    #[test]
+    fn entries_are_kept_as_written() {
+        let rules = rules(&["/work/keep", "D:/clients/**"]);
+        assert_eq!(rules.entries(), ["/work/keep", "D:/clients/**"]);
+        assert!(ProtectRules::compile(vec!["/work/[".to_string()]).is_err());
+        assert!(!ProtectRules::default().protects(&node_modules("/work/app")));
+    }
+}
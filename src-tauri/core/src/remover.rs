@@ -0,0 +1,284 @@
+use std::{
+    fs::{self, FileType},
+    io,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemoveProgress {
+    pub files_removed: u64,
+    pub bytes_removed: u64,
+}
+
+// Links are removed, never followed. A directory symlink or junction on
+// Windows has to go through remove_dir.
+fn remove_entry(path: &Path, file_type: FileType) -> io::Result<()> {
+    let removed = fs::remove_file(path);
+    if removed.is_err() && file_type.is_symlink() {
+        return fs::remove_dir(path);
+    }
+    removed
+}
+
+// Names the entry an error is about, so a failed delete says which file
+// blocked it
+fn at(path: &Path) -> impl FnOnce(io::Error) -> io::Error + '_ {
+    move |e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
+
+#[cfg(windows)]
+const BLOCKING_ATTRIBUTES: u32 = {
+    use windows_sys::Win32::Storage::FileSystem::{
+        FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_SYSTEM,
+    };
+    FILE_ATTRIBUTE_READONLY | FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM
+};
+
+// Every entry below `root`, links included but not followed, until
+// `visit` returns false
+#[cfg(windows)]
+fn walk(root: &Path, visit: &mut dyn FnMut(&Path, FileType) -> io::Result<bool>) -> io::Result<()> {
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir).map_err(at(&dir))? {
+            let entry = entry.map_err(at(&dir))?;
+            let file_type = entry.file_type().map_err(at(&entry.path()))?;
+            let path = entry.path();
+            if !visit(&path, file_type)? {
+                return Ok(());
+            }
+            if file_type.is_dir() {
+                stack.push(path);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Read-only, hidden and system attributes make both the Recycle Bin and
+// DeleteFile refuse a file on Windows; some packages ship files like that.
+// Clears them on everything below `root`. Unix permissions on a file don't
+// stop it being deleted, so elsewhere this does nothing.
+pub fn clear_blocking_attributes(root: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::{ffi::OsStrExt, fs::MetadataExt};
+        use windows_sys::Win32::Storage::FileSystem::{SetFileAttributesW, FILE_ATTRIBUTE_NORMAL};
+
+        walk(root, &mut |path, _| {
+            let attributes = fs::symlink_metadata(path)
+                .map_err(at(path))?
+                .file_attributes();
+            if attributes & BLOCKING_ATTRIBUTES == 0 {
+                return Ok(true);
+            }
+            let cleared = match attributes & !BLOCKING_ATTRIBUTES {
+                0 => FILE_ATTRIBUTE_NORMAL,
+                cleared => cleared,
+            };
+            let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+            // SAFETY: wide is a NUL-terminated path
+            if unsafe { SetFileAttributesW(wide.as_ptr(), cleared) } == 0 {
+                return Err(at(path)(io::Error::last_os_error()));
+            }
+            Ok(true)
+        })
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = root;
+        Ok(())
+    }
+}
+
+// The first entry below `root` with an attribute that blocks deletion, to
+// explain a failed trash move
+pub fn first_blocking_file(root: &Path) -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+
+        let mut found = None;
+        let _ = walk(root, &mut |path, _| {
+            let blocking = fs::symlink_metadata(path)
+                .is_ok_and(|metadata| metadata.file_attributes() & BLOCKING_ATTRIBUTES != 0);
+            if blocking {
+                found = Some(path.to_path_buf());
+            }
+            Ok(!blocking)
+        });
+        found
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = root;
+        None
+    }
+}
+
+// Removes `root` and everything below it like remove_dir_all, but reports
+// the running file and byte counts after each file so a big tree doesn't
+// look stuck. Iterative, since node_modules can nest deeper than the stack
+// would like.
+pub fn remove_tree(
+    root: &Path,
+    on_progress: &mut dyn FnMut(RemoveProgress),
+) -> io::Result<RemoveProgress> {
+    let mut progress = RemoveProgress::default();
+
+    let root_type = fs::symlink_metadata(root).map_err(at(root))?.file_type();
+    if !root_type.is_dir() {
+        remove_entry(root, root_type).map_err(at(root))?;
+        return Ok(progress);
+    }
+
+    // (directory, whether its contents are already gone)
+    let mut stack: Vec<(PathBuf, bool)> = vec![(root.to_path_buf(), false)];
+    while let Some((dir, emptied)) = stack.pop() {
+        if emptied {
+            fs::remove_dir(&dir).map_err(at(&dir))?;
+            continue;
+        }
+
+        stack.push((dir.clone(), true));
+        for entry in fs::read_dir(&dir).map_err(at(&dir))? {
+            let entry = entry.map_err(at(&dir))?;
+            let path = entry.path();
+            let file_type = entry.file_type().map_err(at(&path))?;
+            if file_type.is_dir() {
+                stack.push((path, false));
+                continue;
+            }
+
+            let bytes = if file_type.is_file() {
+                entry.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+            } else {
+                0
+            };
+            remove_entry(&path, file_type).map_err(at(&path))?;
+            progress.files_removed += 1;
+            progress.bytes_removed += bytes;
+            on_progress(progress);
+        }
+    }
+
+    Ok(progress)
+}
+
+// remove_tree deletes one file at a time; robocopy mirroring an empty
+// folder onto `root` purges it with many threads, which is several times
+// faster on trees of 100k+ files. Whatever robocopy leaves behind, and
+// `root` itself, then go through remove_tree, so its errors still name the
+// blocking file. `root` is the plain path, since robocopy handles long
+// paths itself but not the verbatim form.
+#[cfg(windows)]
+pub fn remove_tree_mirrored(root: &Path) -> io::Result<()> {
+    use std::{os::windows::process::CommandExt, process::Stdio};
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let empty = std::env::temp_dir().join(format!("nmcleaner-empty-{}", uuid::Uuid::new_v4()));
+    fs::create_dir(&empty).map_err(at(&empty))?;
+    // /XJ leaves junctions alone; purging through one would empty the pnpm
+    // store or whatever else it points at. remove_tree drops the links.
+    let mirrored = std::process::Command::new("robocopy")
+        .arg(&empty)
+        .arg(root)
+        .args(["/MIR", "/XJ", "/MT:32", "/R:0", "/W:0"])
+        .args(["/NFL", "/NDL", "/NJH", "/NJS", "/NP"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .creation_flags(CREATE_NO_WINDOW)
+        .status();
+    let _ = fs::remove_dir(&empty);
+    if let Err(e) = mirrored {
+        eprintln!(
+            "robocopy unavailable, removing {} file by file: {}",
+            root.display(),
+            e
+        );
+    }
+
+    remove_tree(&crate::long_paths::extended(root), &mut |_| {}).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{self, Fixture},
+        long_paths,
+    };
+
+    #[test]
+    fn removes_awkward_and_deep_trees() {
+        let fixture = Fixture::new("remover");
+        let (node_modules, files, bytes) =
+            fixtures::awkward_node_modules(&fixture, "app", fixtures::DEEP_LEVELS);
+
+        let mut reports = 0;
+        let removed =
+            remove_tree(&long_paths::extended(&node_modules), &mut |_| reports += 1).unwrap();
+        assert_eq!(removed.files_removed, files);
+        assert_eq!(removed.bytes_removed, bytes);
+        assert_eq!(reports, files);
+        assert!(!long_paths::extended(&node_modules).exists());
+        assert!(long_paths::extended(&fixture.path("app")).is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn removes_links_without_following_them() {
+        let fixture = Fixture::new("remover-links");
+        let target = fixture.file("store/left-pad/index.js", 5);
+        let node_modules = fixture.dir("app/node_modules");
+        std::os::unix::fs::symlink(target.parent().unwrap(), node_modules.join("left-pad"))
+            .unwrap();
+
+        remove_tree(&node_modules, &mut |_| {}).unwrap();
+        assert!(!node_modules.exists());
+        assert!(target.is_file());
+    }
+
+    #[cfg(windows)]
+    fn junction(link: &Path, target: &Path) {
+        let status = std::process::Command::new("cmd")
+            .args(["/c", "mklink", "/J"])
+            .arg(link)
+            .arg(target)
+            .stdout(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "mklink /J failed");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn mirrored_purge_removes_the_tree_but_not_junction_targets() {
+        let fixture = Fixture::new("remover-mirrored");
+        let (node_modules, _, _) = fixtures::awkward_node_modules(&fixture, "app", 40);
+        for index in 0..200 {
+            fixture.file(
+                &format!("app/node_modules/pkg-{}/file-{}.js", index % 20, index),
+                index,
+            );
+        }
+        let target = fixture.file("store/left-pad/index.js", 5);
+        junction(&node_modules.join("left-pad"), target.parent().unwrap());
+
+        remove_tree_mirrored(&node_modules).unwrap();
+        assert!(!long_paths::extended(&node_modules).exists());
+        assert!(target.is_file());
+        assert_eq!(std::fs::read(&target).unwrap(), b"xxxxx");
+    }
+
+    #[test]
+    fn names_the_entry_it_failed_on() {
+        let fixture = Fixture::new("remover-missing");
+        let missing = fixture.path("app/node_modules");
+        let error = remove_tree(&missing, &mut |_| {}).unwrap_err();
+        assert!(error.to_string().contains("node_modules"), "{}", error);
+    }
+}
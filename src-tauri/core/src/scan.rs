@@ -0,0 +1,1084 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::task;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    activity,
+    artifacts::ArtifactKind,
+    cloud_sync, ignore_rules,
+    jobs::Job,
+    long_paths,
+    nested_installs::{self, NestedInstall},
+    pnpm,
+    protect_rules::ProtectRules,
+    scan_estimate,
+    scan_options::ScanOptions,
+    scan_patterns::ScanPatterns,
+    scan_summary,
+    sizing::{self, DirectorySize, SizeTier},
+    throttle::{self, Throttle},
+    traversal::{self, CachedDirectory},
+};
+
+// Listing is IO-bound, so a few more workers than cores still pays off
+const MAX_SCAN_WORKERS: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanProgress {
+    pub session_id: String,
+    pub current_folder: String,
+    pub folders_scanned: usize,
+    pub total_folders_estimated: usize,
+    pub node_modules_found: usize,
+    pub directories_skipped: usize,
+    pub is_complete: bool,
+    pub truncated: bool,
+    pub cancelled: bool,
+    pub paused: bool,
+    // Once enough folders have been listed to judge the rate
+    pub eta_seconds: Option<u64>,
+    // Only on the final event of a scan
+    pub summary: Option<scan_summary::ScanSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SizeProgress {
+    pub session_id: String,
+    pub path: String,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanRootLost {
+    pub session_id: String,
+    pub root: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanConfig {
+    pub include_sizes: bool,
+    pub size_tiers: sizing::SizeTierThresholds,
+    pub max_results: Option<usize>,
+    pub size_budget: Duration,
+    pub options: Arc<ScanOptions>,
+    pub patterns: Arc<ScanPatterns>,
+    // Only report projects untouched for at least this many days
+    pub min_age_days: Option<u64>,
+}
+
+// A node_modules (or other artifact) the walk found, sized and checked
+pub struct Found {
+    pub project: PathBuf,
+    pub path: PathBuf,
+    pub artifact_type: ArtifactKind,
+    // Latest change to the project's own files, node_modules excluded
+    pub last_modified: Option<SystemTime>,
+    pub size: Option<DirectorySize>,
+    pub size_tier: Option<SizeTier>,
+    // Installs below this one's packages, when the scan looked for them
+    pub nested: Vec<NestedInstall>,
+    // Left unmeasured, since reading online-only files downloads them
+    pub is_cloud_synced: bool,
+    pub is_protected: bool,
+    pub is_pnpm: bool,
+}
+
+// An earlier scan to build on: directories unchanged since then aren't
+// listed again, and node_modules unchanged since then keep their size
+pub trait PreviousScan: Send + Sync {
+    fn directory(&self, path: &str) -> Option<CachedDirectory>;
+
+    // None if it wasn't measured, or not completely
+    fn size(&self, node_modules: &str) -> Option<DirectorySize>;
+}
+
+// What a scan leaves to the program running it
+pub trait ScanHost: Send + Sync + 'static {
+    // The program's own result type
+    type Item: Clone + Send + 'static;
+
+    // Builds a result from a find. Runs on a blocking thread, so it may read
+    // more from disk (manifests, git status, ...).
+    fn describe(&self, found: Found) -> Self::Item;
+
+    // Each result as soon as it's made, for showing results as they come in
+    fn found(&self, _session_id: &str, _item: &Self::Item) {}
+
+    // Running totals, a few times a second
+    fn progress(&self, _progress: ScanProgress) {}
+
+    // Bytes counted so far in a folder being measured, a few times a second
+    fn size_progress(&self, _progress: SizeProgress) {}
+
+    // A root whose device went away mid-scan; the other roots carry on
+    fn root_lost(&self, _lost: ScanRootLost) {}
+}
+
+// A directory waiting to be listed
+struct PendingDirectory {
+    // Scan root the directory was reached from
+    root: Arc<PathBuf>,
+    path: PathBuf,
+    depth: usize,
+    // Inherited .nmcleanerignore rules
+    rules: Option<Arc<ignore_rules::IgnoreRules>>,
+}
+
+// Directories shared by all workers of a scan, so even a single large root
+// is walked in parallel
+#[derive(Default)]
+struct ScanQueue {
+    directories: Vec<PendingDirectory>,
+    // Directories being listed right now; each may still queue more
+    busy: usize,
+}
+
+pub struct ScanOutcome<I> {
+    pub items: Vec<I>,
+    pub folders_scanned: usize,
+    pub truncated: bool,
+    pub cancelled: bool,
+    pub paused: bool,
+    pub session: Arc<ScanSession<I>>,
+}
+
+// State shared by the workers of one scan request. A session outlives a
+// pause or the result cap, so the walk can be continued later.
+pub struct ScanSession<I> {
+    pub id: String,
+    pub roots: Vec<String>,
+    pub started_at: SystemTime,
+    folders_scanned: AtomicUsize,
+    node_modules_found: AtomicUsize,
+    directories_skipped: AtomicUsize,
+    skips: scan_summary::SkipCounts,
+    permission_errors: AtomicUsize,
+    progress: Throttle,
+    results: Mutex<Vec<I>>,
+    // Directories already claimed by a worker, so overlapping roots (e.g. a
+    // drive and a folder on it) never walk the same subtree twice
+    visited: Mutex<HashSet<PathBuf>>,
+    // Set once the result cap is reached; workers then stop taking
+    // directories and leave the rest queued so the scan can be continued later
+    truncated: AtomicBool,
+    // Set by pause; workers stop the same way as at the result cap
+    paused: AtomicBool,
+    queue: Mutex<ScanQueue>,
+    queue_changed: tokio::sync::Notify,
+    // Roots whose device went away mid-scan
+    lost_roots: Mutex<HashSet<PathBuf>>,
+    pub cancel: CancellationToken,
+    previous: Option<Arc<dyn PreviousScan>>,
+    // What this scan saw, for the next one to build on
+    listings: Mutex<HashMap<String, CachedDirectory>>,
+    // Folder count of the last complete scan of the same roots, or failing
+    // that the first pass's estimate
+    expected_folders: Mutex<Option<usize>>,
+    // Protect list as it was when the scan started
+    protected: Arc<ProtectRules>,
+}
+
+impl<I> ScanSession<I> {
+    pub fn new(id: String, roots: &[String], cancel: CancellationToken) -> Self {
+        ScanSession {
+            id,
+            roots: roots.to_vec(),
+            started_at: SystemTime::now(),
+            folders_scanned: AtomicUsize::new(0),
+            node_modules_found: AtomicUsize::new(0),
+            directories_skipped: AtomicUsize::new(0),
+            skips: scan_summary::SkipCounts::default(),
+            permission_errors: AtomicUsize::new(0),
+            progress: Throttle::new(throttle::SCAN_EVENTS_PER_SECOND),
+            results: Mutex::new(Vec::new()),
+            visited: Mutex::new(HashSet::new()),
+            truncated: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            queue: Mutex::new(ScanQueue::default()),
+            queue_changed: tokio::sync::Notify::new(),
+            lost_roots: Mutex::new(HashSet::new()),
+            cancel,
+            previous: None,
+            listings: Mutex::new(HashMap::new()),
+            expected_folders: Mutex::new(None),
+            protected: Arc::default(),
+        }
+    }
+
+    // Builds on `previous` instead of listing everything again
+    pub fn with_previous(self, previous: Arc<dyn PreviousScan>) -> Self {
+        ScanSession {
+            previous: Some(previous),
+            ..self
+        }
+    }
+
+    // Skips the first pass that sizes up the tree for the progress
+    pub fn with_expected_folders(self, expected: Option<usize>) -> Self {
+        ScanSession {
+            expected_folders: Mutex::new(expected),
+            ..self
+        }
+    }
+
+    pub fn with_protected(self, protected: Arc<ProtectRules>) -> Self {
+        ScanSession { protected, ..self }
+    }
+
+    pub fn folders_scanned(&self) -> usize {
+        self.folders_scanned.load(Ordering::Relaxed)
+    }
+
+    pub fn node_modules_found(&self) -> usize {
+        self.node_modules_found.load(Ordering::Relaxed)
+    }
+
+    pub fn directories_skipped(&self) -> usize {
+        self.directories_skipped.load(Ordering::Relaxed)
+    }
+
+    fn estimated_folders(&self, folders_scanned: usize) -> usize {
+        let pending = self
+            .queue
+            .lock()
+            .map(|queue| queue.directories.len() + queue.busy)
+            .unwrap_or(0);
+        let expected = self
+            .expected_folders
+            .lock()
+            .ok()
+            .and_then(|expected| *expected);
+        scan_estimate::estimate_total(folders_scanned, pending, expected)
+    }
+
+    fn eta_seconds(&self, folders_scanned: usize, total: usize) -> Option<u64> {
+        let elapsed = self.started_at.elapsed().ok()?;
+        scan_estimate::eta_seconds(folders_scanned, total, elapsed)
+    }
+
+    fn skip(&self, reason: scan_summary::SkipReason) {
+        self.skips.add(reason);
+        self.directories_skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn permission_denied(&self) {
+        self.permission_errors.fetch_add(1, Ordering::Relaxed);
+        self.directories_skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // `reclaimable_bytes` is over the results the caller kept
+    pub fn summary(&self, reclaimable_bytes: u64) -> scan_summary::ScanSummary {
+        scan_summary::ScanSummary {
+            directories_visited: self.folders_scanned(),
+            directories_skipped: self.directories_skipped(),
+            skipped_by_reason: self.skips.snapshot(),
+            permission_errors: self.permission_errors.load(Ordering::Relaxed),
+            elapsed_ms: self
+                .started_at
+                .elapsed()
+                .map(|elapsed| elapsed.as_millis() as u64)
+                .unwrap_or(0),
+            reclaimable_bytes,
+        }
+    }
+
+    fn claim(&self, path: &Path) -> bool {
+        self.visited
+            .lock()
+            .map(|mut visited| visited.insert(path.to_path_buf()))
+            .unwrap_or(true)
+    }
+
+    fn reached_limit(&self, max_results: Option<usize>) -> bool {
+        max_results.is_some_and(|max| {
+            self.results
+                .lock()
+                .map(|results| results.len() >= max)
+                .unwrap_or(false)
+        })
+    }
+
+    // Next directory to list, or None once the walk is done or has to stop
+    async fn next_directory(&self) -> Option<PendingDirectory> {
+        loop {
+            // Created before checking the queue so no wakeup is missed
+            let changed = self.queue_changed.notified();
+
+            if self.cancel.is_cancelled()
+                || self.truncated.load(Ordering::Relaxed)
+                || self.paused.load(Ordering::Relaxed)
+            {
+                return None;
+            }
+            {
+                let mut queue = self.queue.lock().ok()?;
+                if let Some(directory) = queue.directories.pop() {
+                    queue.busy += 1;
+                    return Some(directory);
+                }
+                // Nothing queued and nobody left who could queue more
+                if queue.busy == 0 {
+                    return None;
+                }
+            }
+
+            tokio::select! {
+                _ = changed => {}
+                _ = self.cancel.cancelled() => return None,
+            }
+        }
+    }
+
+    // Stops the walk at the next directory boundaries, keeping what's queued
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+        // Idle workers are waiting for more directories; let them see the flag
+        self.queue_changed.notify_waiters();
+    }
+
+    fn finish_directory(&self, subdirectories: Vec<PendingDirectory>) {
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.busy -= 1;
+            queue.directories.extend(subdirectories);
+        }
+        self.queue_changed.notify_waiters();
+    }
+
+    // Drops everything queued below `root`; true the first time it's reported
+    fn lose_root(&self, root: &Path) -> bool {
+        let first = self
+            .lost_roots
+            .lock()
+            .map(|mut lost| lost.insert(root.to_path_buf()))
+            .unwrap_or(false);
+        if let Ok(mut queue) = self.queue.lock() {
+            queue
+                .directories
+                .retain(|directory| directory.root.as_path() != root);
+        }
+        first
+    }
+
+    pub fn is_root_lost(&self, root: &Path) -> bool {
+        self.lost_roots
+            .lock()
+            .map(|lost| lost.contains(root))
+            .unwrap_or(false)
+    }
+
+    // Directory listings gathered since the last call, keyed by path
+    pub fn take_listings(&self) -> HashMap<String, CachedDirectory> {
+        self.listings
+            .lock()
+            .map(|mut listings| std::mem::take(&mut *listings))
+            .unwrap_or_default()
+    }
+}
+
+// Walks `roots` from the start. Roots that aren't directories are left out.
+pub async fn start<H: ScanHost>(
+    session: Arc<ScanSession<H::Item>>,
+    roots: &[String],
+    config: &ScanConfig,
+    host: &Arc<H>,
+    job: &Job,
+) -> Result<ScanOutcome<H::Item>, String> {
+    let mut unique_roots = HashSet::new();
+    let mut root_paths = Vec::new();
+
+    for root in roots {
+        let root_path = Path::new(root);
+        if !long_paths::extended(root_path).is_dir() {
+            continue;
+        }
+
+        // The same folder may be passed twice under different spellings
+        let key = fs::canonicalize(root).unwrap_or_else(|_| PathBuf::from(root));
+        if !unique_roots.insert(key) {
+            continue;
+        }
+
+        // Claim every root up front so walking an enclosing root doesn't
+        // list it a second time
+        session.claim(root_path);
+        root_paths.push(root_path.to_path_buf());
+        if let Ok(mut queue) = session.queue.lock() {
+            queue.directories.push(PendingDirectory {
+                root: Arc::new(root_path.to_path_buf()),
+                path: root_path.to_path_buf(),
+                depth: 0,
+                rules: None,
+            });
+        }
+    }
+
+    // Without a recorded total, size up the tree first so the progress has
+    // a real denominator from the start
+    let known = session
+        .expected_folders
+        .lock()
+        .map(|expected| expected.is_some())
+        .unwrap_or(true);
+    if !known && !root_paths.is_empty() {
+        let options = config.options.clone();
+        let patterns = config.patterns.clone();
+        let estimate = task::spawn_blocking(move || {
+            scan_estimate::prepass(&root_paths, |path, depth| {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                !options.skips(&name, depth)
+                    && !patterns.prunes(path)
+                    && traversal::should_scan_subdirectory(path, depth - 1, &options)
+            })
+        })
+        .await
+        .ok()
+        .flatten();
+        if let Ok(mut expected) = session.expected_folders.lock() {
+            *expected = estimate;
+        }
+    }
+
+    run(session, config, host, job).await
+}
+
+// Walks whatever is queued in the session until it's done, cancelled,
+// paused or hits the result cap
+pub async fn run<H: ScanHost>(
+    session: Arc<ScanSession<H::Item>>,
+    config: &ScanConfig,
+    host: &Arc<H>,
+    job: &Job,
+) -> Result<ScanOutcome<H::Item>, String> {
+    session.truncated.store(false, Ordering::Relaxed);
+    session.paused.store(false, Ordering::Relaxed);
+    let mut workers = task::JoinSet::new();
+    let worker_count = std::thread::available_parallelism()
+        .map_or(4, |threads| threads.get())
+        .clamp(2, MAX_SCAN_WORKERS);
+
+    for _ in 0..worker_count {
+        let session = session.clone();
+        let config = config.clone();
+        let host = host.clone();
+        let job = job.clone();
+
+        workers.spawn(async move {
+            while let Some(directory) = session.next_directory().await {
+                let subdirectories =
+                    scan_directory(directory, &config, &session, &host, &job).await;
+                session.finish_directory(subdirectories);
+            }
+        });
+    }
+
+    while workers.join_next().await.is_some() {}
+
+    let items = session
+        .results
+        .lock()
+        .map(|mut results| std::mem::take(&mut *results))
+        .map_err(|e| format!("Failed to collect results: {}", e))?;
+
+    Ok(ScanOutcome {
+        items,
+        folders_scanned: session.folders_scanned(),
+        truncated: session.truncated.load(Ordering::Relaxed),
+        cancelled: session.cancel.is_cancelled(),
+        paused: session.paused.load(Ordering::Relaxed),
+        session,
+    })
+}
+
+// Errors meaning the volume itself went away, e.g. a USB disk pulled mid-scan
+fn is_device_lost(error: &std::io::Error, root: &Path) -> bool {
+    // ERROR_NOT_READY, ERROR_DEV_NOT_EXIST, ERROR_DEVICE_NOT_CONNECTED,
+    // ERROR_DEVICE_REMOVED, and for shares ERROR_BAD_NETPATH,
+    // ERROR_UNEXP_NET_ERR, ERROR_NETNAME_DELETED, ERROR_BAD_NET_NAME,
+    // ERROR_NETWORK_UNREACHABLE
+    #[cfg(windows)]
+    const DEVICE_GONE: [i32; 9] = [21, 55, 1167, 1617, 53, 59, 64, 67, 1231];
+    // ENXIO, ENODEV
+    #[cfg(not(windows))]
+    const DEVICE_GONE: [i32; 2] = [6, 19];
+
+    if error
+        .raw_os_error()
+        .is_some_and(|code| DEVICE_GONE.contains(&code))
+    {
+        return true;
+    }
+
+    // Anything else only counts if the whole root vanished along with it
+    fs::metadata(long_paths::extended(root)).is_err()
+}
+
+// Lists one directory and returns the subdirectories worth walking next
+async fn scan_directory<H: ScanHost>(
+    directory: PendingDirectory,
+    config: &ScanConfig,
+    session: &ScanSession<H::Item>,
+    host: &Arc<H>,
+    job: &Job,
+) -> Vec<PendingDirectory> {
+    let PendingDirectory {
+        root,
+        path: current_path,
+        depth,
+        rules: inherited_rules,
+    } = directory;
+    let mut subdirectories = Vec::new();
+
+    // Queued before its device went away
+    if session.is_root_lost(&root) {
+        return subdirectories;
+    }
+
+    // Skip special directories on Unix systems
+    #[cfg(not(target_os = "windows"))]
+    {
+        if let Some(name) = current_path.file_name() {
+            let name_str = name.to_string_lossy();
+            if matches!(name_str.as_ref(), "proc" | "sys" | "dev") {
+                session.skip(scan_summary::SkipReason::System);
+                return subdirectories;
+            }
+        }
+    }
+
+    // Never walk Time Machine backups or trash areas on macOS
+    #[cfg(target_os = "macos")]
+    {
+        if crate::deletion::is_macos_backup_or_trash(&current_path) {
+            session.skip(scan_summary::SkipReason::System);
+            return subdirectories;
+        }
+    }
+
+    // Skip irrelevant directories that won't contain node_modules
+    if let Some(name) = current_path.file_name() {
+        let name_str = name.to_string_lossy();
+        if config.options.skips(&name_str, depth) {
+            session.skip(scan_summary::SkipReason::SkipList);
+            return subdirectories;
+        }
+    }
+
+    // Outside the user's include patterns or inside an excluded path
+    if config.patterns.prunes(&current_path) {
+        session.skip(scan_summary::SkipReason::Pattern);
+        return subdirectories;
+    }
+
+    // Already walked from another root (roots were claimed up front)
+    if depth > 0 && !session.claim(&current_path) {
+        return subdirectories;
+    }
+
+    job.progress(&current_path);
+
+    let path_key = current_path.to_string_lossy().to_string();
+    let cached = session
+        .previous
+        .as_ref()
+        .and_then(|previous| previous.directory(&path_key));
+    let cached_node_modules = cached
+        .as_ref()
+        .and_then(|cached| cached.node_modules_modified);
+
+    // The whole visit runs on a blocking thread, so the workers never stall
+    // the runtime on disk I/O and a dead mount can be skipped by the user
+    // instead of hanging the whole scan
+    let visit = {
+        let dir = current_path.clone();
+        let options = config.options.clone();
+        let patterns = config.patterns.clone();
+        task::spawn_blocking(move || {
+            traversal::visit_directory(&dir, depth, cached, inherited_rules, &options, &patterns)
+        })
+    };
+    let visit = tokio::select! {
+        visit = visit => visit.ok(),
+        _ = job.skip_requested() => {
+            eprintln!("Skipped stalled directory: {}", current_path.display());
+            session.skip(scan_summary::SkipReason::Stalled);
+            None
+        }
+        _ = session.cancel.cancelled() => None,
+    };
+
+    let visit = match visit {
+        Some(Ok(visit)) => Some(visit),
+        Some(Err(e)) if is_device_lost(&e, &root) => {
+            // Give up on this root only; other roots and the results
+            // found so far are unaffected
+            if !session.lose_root(&root) {
+                return subdirectories;
+            }
+            eprintln!("Lost access to {}: {}", root.display(), e);
+            host.root_lost(ScanRootLost {
+                session_id: session.id.clone(),
+                root: root.to_string_lossy().to_string(),
+                error: e.to_string(),
+            });
+            return subdirectories;
+        }
+        Some(Err(e)) => {
+            eprintln!("Failed to read {}: {}", current_path.display(), e);
+            if e.kind() == std::io::ErrorKind::PermissionDenied {
+                session.permission_denied();
+            } else {
+                session.skip(scan_summary::SkipReason::Unreadable);
+            }
+            None
+        }
+        None => None,
+    };
+
+    if let Some(visit) = visit {
+        for reason in &visit.skipped {
+            session.skip(*reason);
+        }
+
+        if let Some(path) = visit.node_modules {
+            let unchanged = visit.node_modules_modified.is_some()
+                && visit.node_modules_modified == cached_node_modules;
+            report(
+                path,
+                ArtifactKind::NodeModules,
+                &current_path,
+                unchanged,
+                config,
+                session,
+                host,
+                job,
+            )
+            .await;
+        }
+        // Never in the scan cache, so always measured afresh
+        for (artifact_type, path) in visit.artifacts {
+            report(
+                path,
+                artifact_type,
+                &current_path,
+                false,
+                config,
+                session,
+                host,
+                job,
+            )
+            .await;
+        }
+
+        subdirectories.extend(
+            visit
+                .subdirectories
+                .into_iter()
+                .map(|path| PendingDirectory {
+                    root: root.clone(),
+                    path,
+                    depth: depth + 1,
+                    rules: visit.rules.clone(),
+                }),
+        );
+
+        if let Some(modified) = visit.modified {
+            if let Ok(mut listings) = session.listings.lock() {
+                listings.insert(
+                    path_key,
+                    CachedDirectory {
+                        modified,
+                        ignore_files: visit.ignore_files,
+                        children: visit.children,
+                        node_modules_modified: visit.node_modules_modified,
+                    },
+                );
+            }
+        }
+    }
+
+    let folders_scanned = session.folders_scanned.fetch_add(1, Ordering::Relaxed) + 1;
+
+    // Stop at directory boundaries so no partially listed folder is lost
+    if session.reached_limit(config.max_results) {
+        session.truncated.store(true, Ordering::Relaxed);
+    }
+
+    // Counters are shared, so whichever worker's turn it is reports the
+    // totals for the whole scan
+    if session.progress.claim() {
+        let total_folders_estimated = session.estimated_folders(folders_scanned);
+        host.progress(ScanProgress {
+            session_id: session.id.clone(),
+            current_folder: current_path.to_string_lossy().to_string(),
+            folders_scanned,
+            total_folders_estimated,
+            node_modules_found: session.node_modules_found(),
+            directories_skipped: session.directories_skipped(),
+            is_complete: false,
+            truncated: false,
+            cancelled: false,
+            paused: false,
+            eta_seconds: session.eta_seconds(folders_scanned, total_folders_estimated),
+            summary: None,
+        });
+    }
+
+    // Give other tasks on this runtime thread a turn between directories
+    task::yield_now().await;
+
+    subdirectories
+}
+
+// Sizes a node_modules (or other artifact) found by the walk and adds it to
+// the results. `unchanged` means it is untouched since the previous scan.
+#[allow(clippy::too_many_arguments)]
+async fn report<H: ScanHost>(
+    path: PathBuf,
+    artifact_type: ArtifactKind,
+    current_path: &Path,
+    unchanged: bool,
+    config: &ScanConfig,
+    session: &ScanSession<H::Item>,
+    host: &Arc<H>,
+    job: &Job,
+) {
+    let last_modified = {
+        let project = current_path.to_path_buf();
+        task::spawn_blocking(move || activity::last_modified(&project))
+            .await
+            .ok()
+            .flatten()
+    };
+    if config
+        .min_age_days
+        .is_some_and(|min_age_days| !activity::is_stale(last_modified, min_age_days))
+    {
+        return;
+    }
+
+    // Untouched since the previous scan, so its size still holds
+    let reused = session
+        .previous
+        .as_ref()
+        .filter(|_| unchanged)
+        .and_then(|previous| previous.size(&path.to_string_lossy()));
+
+    let (is_cloud_synced, is_pnpm) = {
+        let node_modules = path.clone();
+        task::spawn_blocking(move || {
+            (
+                cloud_sync::is_cloud_synced(&node_modules),
+                artifact_type == ArtifactKind::NodeModules && pnpm::is_pnpm_layout(&node_modules),
+            )
+        })
+        .await
+        .unwrap_or_default()
+    };
+
+    let size = if reused.is_some() {
+        reused
+    } else if config.include_sizes && !is_cloud_synced {
+        job.progress(&path);
+        let on_progress = {
+            let host = host.clone();
+            let job = job.clone();
+            let session_id = session.id.clone();
+            let path = path.clone();
+            let throttle = Throttle::new(throttle::SIZE_EVENTS_PER_SECOND);
+            move |bytes| {
+                // Big trees take a while; keep the watchdog happy
+                job.touch();
+                if throttle.claim() {
+                    host.size_progress(SizeProgress {
+                        session_id: session_id.clone(),
+                        path: path.to_string_lossy().to_string(),
+                        bytes,
+                    });
+                }
+            }
+        };
+        let measure_cancel = session.cancel.child_token();
+        tokio::select! {
+            measured = sizing::measure_directory(&path, config.size_budget, measure_cancel.clone(), on_progress) => measured,
+            _ = job.skip_requested() => {
+                // Stop the abandoned measurement instead of leaving it running
+                measure_cancel.cancel();
+                None
+            }
+            _ = session.cancel.cancelled() => None,
+        }
+    } else {
+        None
+    };
+
+    let nested = if config.options.scan_nested && artifact_type == ArtifactKind::NodeModules {
+        let node_modules = path.clone();
+        let max_depth = config.options.nested_max_depth;
+        task::spawn_blocking(move || nested_installs::find(&node_modules, max_depth))
+            .await
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    let found = Found {
+        project: current_path.to_path_buf(),
+        is_protected: session.protected.protects(&path),
+        path,
+        artifact_type,
+        last_modified,
+        size,
+        size_tier: size.map(|size| config.size_tiers.classify(size.reclaimable)),
+        nested,
+        is_cloud_synced,
+        is_pnpm,
+    };
+    let describe = {
+        let host = host.clone();
+        task::spawn_blocking(move || host.describe(found))
+    };
+    let Ok(item) = describe.await else {
+        return;
+    };
+
+    session.node_modules_found.fetch_add(1, Ordering::Relaxed);
+    if let Ok(mut results) = session.results.lock() {
+        results.push(item.clone());
+    }
+    host.found(&session.id, &item);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::Fixture,
+        jobs::{JobKind, JobRegistry},
+    };
+
+    #[derive(Clone, Debug)]
+    struct Item {
+        path: PathBuf,
+        size: Option<DirectorySize>,
+    }
+
+    #[derive(Default)]
+    struct TestHost {
+        reported: Mutex<Vec<PathBuf>>,
+    }
+
+    impl ScanHost for TestHost {
+        type Item = Item;
+
+        fn describe(&self, found: Found) -> Item {
+            Item {
+                path: found.path,
+                size: found.size,
+            }
+        }
+
+        fn found(&self, _session_id: &str, item: &Item) {
+            self.reported.lock().unwrap().push(item.path.clone());
+        }
+    }
+
+    struct Previous {
+        directories: HashMap<String, CachedDirectory>,
+        size: DirectorySize,
+    }
+
+    impl PreviousScan for Previous {
+        fn directory(&self, path: &str) -> Option<CachedDirectory> {
+            self.directories.get(path).cloned()
+        }
+
+        fn size(&self, _node_modules: &str) -> Option<DirectorySize> {
+            Some(self.size)
+        }
+    }
+
+    fn config(max_results: Option<usize>) -> ScanConfig {
+        ScanConfig {
+            include_sizes: true,
+            size_tiers: sizing::SizeTierThresholds::default(),
+            max_results,
+            size_budget: sizing::DEFAULT_SIZE_BUDGET,
+            options: Arc::new(ScanOptions::default()),
+            patterns: Arc::new(ScanPatterns::compile(&[], &[]).unwrap()),
+            min_age_days: None,
+        }
+    }
+
+    fn session(roots: &[String]) -> Arc<ScanSession<Item>> {
+        Arc::new(
+            ScanSession::new("test".to_string(), roots, CancellationToken::new())
+                .with_expected_folders(Some(10)),
+        )
+    }
+
+    fn root(fixture: &Fixture) -> Vec<String> {
+        vec![fixture.root.to_string_lossy().to_string()]
+    }
+
+    fn sorted_paths(items: &[Item]) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = items.iter().map(|item| item.path.clone()).collect();
+        paths.sort();
+        paths
+    }
+
+    #[tokio::test]
+    async fn finds_and_measures_every_node_modules() {
+        let fixture = Fixture::new("scan-find");
+        fixture.file("app-a/node_modules/left-pad/index.js", 300);
+        fixture.file("app-a/node_modules/left-pad/package.json", 200);
+        fixture.file("group/app-b/node_modules/react/index.js", 1000);
+        // Skipped by the default skip list
+        fixture.file(".git/modules/node_modules/x.js", 10);
+        let roots = root(&fixture);
+        let host = Arc::new(TestHost::default());
+        let job = JobRegistry::default().start(JobKind::Scan);
+
+        let outcome = start(session(&roots), &roots, &config(None), &host, &job)
+            .await
+            .unwrap();
+
+        assert!(!outcome.truncated && !outcome.cancelled && !outcome.paused);
+        let expected = vec![
+            fixture.path("app-a/node_modules"),
+            fixture.path("group/app-b/node_modules"),
+        ];
+        assert_eq!(sorted_paths(&outcome.items), expected);
+        let mut reported = host.reported.lock().unwrap().clone();
+        reported.sort();
+        assert_eq!(reported, expected);
+
+        let sizes: HashMap<PathBuf, (u64, u64)> = outcome
+            .items
+            .iter()
+            .map(|item| {
+                let size = item.size.expect("measured");
+                (item.path.clone(), (size.total, size.files))
+            })
+            .collect();
+        assert_eq!(sizes[&expected[0]], (500, 2));
+        assert_eq!(sizes[&expected[1]], (1000, 1));
+
+        let summary = outcome.session.summary(0);
+        assert!(summary
+            .skipped_by_reason
+            .iter()
+            .any(|(reason, count)| *reason == scan_summary::SkipReason::SkipList && *count > 0));
+    }
+
+    #[tokio::test]
+    async fn overlapping_roots_are_walked_once() {
+        let fixture = Fixture::new("scan-overlap");
+        fixture.file("app/node_modules/a.js", 10);
+        let roots = vec![
+            fixture.root.to_string_lossy().to_string(),
+            fixture.path("app").to_string_lossy().to_string(),
+        ];
+        let host = Arc::new(TestHost::default());
+        let job = JobRegistry::default().start(JobKind::Scan);
+
+        let outcome = start(session(&roots), &roots, &config(None), &host, &job)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            sorted_paths(&outcome.items),
+            [fixture.path("app/node_modules")]
+        );
+    }
+
+    #[tokio::test]
+    async fn stops_at_the_result_cap_and_carries_on_later() {
+        let fixture = Fixture::new("scan-cap");
+        for project in ["one", "two", "three", "four"] {
+            fixture.file(&format!("{}/node_modules/index.js", project), 10);
+        }
+        let roots = root(&fixture);
+        let host = Arc::new(TestHost::default());
+        let job = JobRegistry::default().start(JobKind::Scan);
+
+        let first = start(session(&roots), &roots, &config(Some(1)), &host, &job)
+            .await
+            .unwrap();
+        assert!(first.truncated);
+        assert!(!first.items.is_empty() && first.items.len() < 4);
+
+        let rest = run(first.session.clone(), &config(None), &host, &job)
+            .await
+            .unwrap();
+        assert!(!rest.truncated);
+        let mut all = first.items;
+        all.extend(rest.items);
+        assert_eq!(all.len(), 4);
+        assert_eq!(rest.session.node_modules_found(), 4);
+    }
+
+    #[tokio::test]
+    async fn a_cancelled_scan_stops_without_results() {
+        let fixture = Fixture::new("scan-cancel");
+        fixture.file("app/node_modules/a.js", 10);
+        let roots = root(&fixture);
+        let host = Arc::new(TestHost::default());
+        let job = JobRegistry::default().start(JobKind::Scan);
+        let session = session(&roots);
+        session.cancel.cancel();
+
+        let outcome = start(session, &roots, &config(None), &host, &job)
+            .await
+            .unwrap();
+
+        assert!(outcome.cancelled);
+        assert!(outcome.items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unchanged_node_modules_keep_the_previous_size() {
+        let fixture = Fixture::new("scan-previous");
+        fixture.file("app/node_modules/a.js", 10);
+        let roots = root(&fixture);
+        let host = Arc::new(TestHost::default());
+        let job = JobRegistry::default().start(JobKind::Scan);
+
+        let first = start(session(&roots), &roots, &config(None), &host, &job)
+            .await
+            .unwrap();
+        let previous = Previous {
+            directories: first.session.take_listings(),
+            size: DirectorySize {
+                total: 12345,
+                reclaimable: 12345,
+                files: 7,
+            },
+        };
+
+        let session = Arc::new(
+            ScanSession::new("again".to_string(), &roots, CancellationToken::new())
+                .with_previous(Arc::new(previous)),
+        );
+        let again = start(session, &roots, &config(None), &host, &job)
+            .await
+            .unwrap();
+
+        assert_eq!(again.items.len(), 1);
+        assert_eq!(again.items[0].size.map(|size| size.total), Some(12345));
+    }
+}
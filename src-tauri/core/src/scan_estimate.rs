@@ -0,0 +1,105 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::long_paths;
+
+// The first pass gives up after this long or this many folders, so it never
+// holds up the walk noticeably
+const PREPASS_BUDGET: Duration = Duration::from_millis(750);
+const PREPASS_MAX_FOLDERS: usize = 20_000;
+// Levels below the roots counted before extrapolating
+const PREPASS_DEPTH: usize = 2;
+// Deeper levels fan out less than the ones above them
+const BRANCHING_DECAY: f64 = 0.7;
+const MAX_EXTRAPOLATED_LEVELS: usize = 16;
+// Below this the listing rate is too noisy to project from
+const MIN_FOLDERS_FOR_ETA: usize = 50;
+
+// Total folders to expect, given how many were listed so far and how many
+// are still queued. A past scan of the same roots is trusted until this
+// one outgrows it; without one, each queued folder is assumed to hold
+// about one more level below it.
+pub fn estimate_total(scanned: usize, pending: usize, expected: Option<usize>) -> usize {
+    let discovered = scanned + pending;
+    match expected {
+        Some(expected) if expected > discovered => expected,
+        _ => discovered + pending,
+    }
+}
+
+// Seconds left at the rate folders have been listed so far
+pub fn eta_seconds(scanned: usize, total: usize, elapsed: Duration) -> Option<u64> {
+    if scanned < MIN_FOLDERS_FOR_ETA {
+        return None;
+    }
+    let remaining = total.saturating_sub(scanned) as f64;
+    Some((remaining * elapsed.as_secs_f64() / scanned as f64).ceil() as u64)
+}
+
+// Folder counts per level, roots first, extended downwards with a branching
+// factor that shrinks each level
+fn extrapolate(levels: &[usize]) -> usize {
+    let counted: usize = levels.iter().sum();
+    let [.., above, last] = levels else {
+        return counted;
+    };
+    if *above == 0 {
+        return counted;
+    }
+
+    let mut branching = *last as f64 / *above as f64;
+    let mut level = *last as f64;
+    let mut extrapolated = 0.0;
+    for _ in 0..MAX_EXTRAPOLATED_LEVELS {
+        branching *= BRANCHING_DECAY;
+        level *= branching;
+        if level < 1.0 {
+            break;
+        }
+        extrapolated += level;
+    }
+    counted + extrapolated as usize
+}
+
+// Cheap first pass for roots without a recorded total: counts the folders
+// in the top levels below the roots that `walks` accepts (given a folder and
+// its depth), then extrapolates the rest. None if not even the first level
+// fit in the budget.
+pub fn prepass(roots: &[PathBuf], walks: impl Fn(&Path, usize) -> bool) -> Option<usize> {
+    let deadline = Instant::now() + PREPASS_BUDGET;
+    let mut levels = vec![roots.len()];
+    let mut current = roots.to_vec();
+    let mut counted = roots.len();
+
+    'levels: for depth in 1..=PREPASS_DEPTH {
+        let mut next = Vec::new();
+        for dir in &current {
+            if Instant::now() > deadline || counted + next.len() > PREPASS_MAX_FOLDERS {
+                break 'levels;
+            }
+            let Ok(entries) = fs::read_dir(long_paths::extended(dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                // file_type doesn't follow symlinks, which the walk skips too
+                if !entry.file_type().is_ok_and(|file_type| file_type.is_dir())
+                    || entry.file_name() == "node_modules"
+                {
+                    continue;
+                }
+                let path = dir.join(entry.file_name());
+                if walks(&path, depth) {
+                    next.push(path);
+                }
+            }
+        }
+        counted += next.len();
+        levels.push(next.len());
+        current = next;
+    }
+
+    (levels.len() > 1).then(|| extrapolate(&levels))
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{artifacts::ArtifactKind, scan_patterns};
+
+const MAX_SCAN_DEPTH: usize = 64;
+const MAX_NESTED_DEPTH: usize = 8;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+// How far and where a scan walks. Missing fields fall back to the defaults,
+// so older settings files keep working.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScanOptions {
+    // Deepest folder level below a root that is still listed
+    pub max_depth: usize,
+    // Below this level every folder is walked; deeper ones only when they look
+    // like part of a project
+    pub unconditional_depth: usize,
+    // Folder names never walked, at any depth
+    pub skip_directories: Vec<String>,
+    // Folder names only skipped directly inside a root
+    pub skip_root_directories: Vec<String>,
+    pub skip_hidden_at_root: bool,
+    // Files marking a folder as part of a project
+    pub project_markers: Vec<String>,
+    // Folder names always walked within the depth limit
+    pub dev_folders: Vec<String>,
+    // Globs over full paths; when any include is set, only matching
+    // projects are reported
+    pub include_patterns: Vec<String>,
+    pub exclude_patterns: Vec<String>,
+    // Also skip folders listed in .gitignore/.ignore files
+    pub respect_vcs_ignores: bool,
+    // Look inside each node_modules for installs nested below its packages
+    pub scan_nested: bool,
+    // How many node_modules levels down that search goes
+    pub nested_max_depth: usize,
+    // Artifacts reported besides node_modules, e.g. Cargo target folders
+    pub artifact_types: Vec<ArtifactKind>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            max_depth: 6,
+            unconditional_depth: 4,
+            skip_directories: strings(&[
+                ".pnpm-store",
+                ".npm",
+                ".yarn",
+                ".npmrc",
+                ".yarnrc",
+                ".yarn-cache",
+                ".npm-cache",
+                ".git",
+                ".svn",
+                ".hg",
+                ".bzr", // Version control
+                ".vscode",
+                ".idea",
+                ".atom",
+                ".sublime",     // IDE
+                "node_modules", // Already found
+                "dist",
+                "build",
+                ".next",
+                "out",
+                "target", // Build outputs
+                ".cache",
+                ".temp",
+                "tmp",
+                "temp", // Cache/temp
+                "android",
+                "ios",
+                "macos",
+                "windows", // OS specific
+                "bin",
+                "obj",
+                "Debug",
+                "Release", // Binary/compiled
+                "vendor",
+                "composer",
+                "gradle",
+                "maven", // Other package managers
+            ]),
+            skip_root_directories: strings(&[
+                "System Volume Information",
+                "Recovery",
+                "Windows",
+                "Program Files",
+                "Program Files (x86)",
+            ]),
+            skip_hidden_at_root: true,
+            project_markers: strings(&[
+                "package.json",
+                "yarn.lock",
+                "pnpm-lock.yaml",
+                "lerna.json",
+                "tsconfig.json",
+                "webpack.config.js",
+                "vite.config.ts",
+                "angular.json",
+                "vue.config.js",
+                "next.config.js",
+                "Cargo.toml",
+                "pom.xml",
+                "build.gradle",
+                "requirements.txt",
+            ]),
+            dev_folders: strings(&[
+                "src",
+                "lib",
+                "app",
+                "frontend",
+                "backend",
+                "client",
+                "server",
+                "components",
+                "pages",
+                "routes",
+                "api",
+                "services",
+                "utils",
+                "public",
+                "assets",
+                "styles",
+                "scripts",
+                "tests",
+                "docs",
+            ]),
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            respect_vcs_ignores: false,
+            scan_nested: false,
+            nested_max_depth: 3,
+            artifact_types: vec![ArtifactKind::YarnCache, ArtifactKind::YarnUnplugged],
+        }
+    }
+}
+
+impl ScanOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_depth == 0 || self.max_depth > MAX_SCAN_DEPTH {
+            return Err(format!(
+                "The scan depth must be between 1 and {}",
+                MAX_SCAN_DEPTH
+            ));
+        }
+        if self.unconditional_depth > self.max_depth {
+            return Err("The unconditional depth can't exceed the scan depth".to_string());
+        }
+        if self.nested_max_depth == 0 || self.nested_max_depth > MAX_NESTED_DEPTH {
+            return Err(format!(
+                "The nested install depth must be between 1 and {}",
+                MAX_NESTED_DEPTH
+            ));
+        }
+        if let Some(invalid) =
+            scan_patterns::check(&self.include_patterns, &self.exclude_patterns).first()
+        {
+            return Err(format!(
+                "Invalid pattern {}: {}",
+                invalid.pattern, invalid.error
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn skips(&self, name: &str, depth: usize) -> bool {
+        if self.skip_directories.iter().any(|skip| skip == name) {
+            return true;
+        }
+
+        if depth == 0 {
+            // Hidden directories at root level, except config folders
+            if self.skip_hidden_at_root && name.starts_with('.') && name != ".config" {
+                return true;
+            }
+            if self.skip_root_directories.iter().any(|skip| skip == name) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn is_project_marker(&self, file_name: &str) -> bool {
+        self.project_markers
+            .iter()
+            .any(|marker| marker == file_name)
+    }
+
+    pub fn is_dev_folder(&self, name: &str) -> bool {
+        self.dev_folders.iter().any(|folder| folder == name)
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternKind {
+    Include,
+    Exclude,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatternError {
+    pub kind: PatternKind,
+    pub pattern: String,
+    pub error: String,
+}
+
+// User globs matched against full '/'-separated paths, e.g. `**/Dropbox/**`
+// or `D:/work/**`. `*` stays within one folder name, `**` crosses folders.
+#[derive(Debug, Default)]
+pub struct ScanPatterns {
+    include: Option<GlobSet>,
+    // Leading literal folders of each include pattern, used to prune the walk
+    include_prefixes: Vec<Vec<String>>,
+    exclude: Option<GlobSet>,
+}
+
+// Paths are compared with '/' separators everywhere
+pub fn normalize(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+pub fn glob(pattern: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(&normalize(pattern))
+        .literal_separator(true)
+        .case_insensitive(cfg!(any(windows, target_os = "macos")))
+        .build()
+}
+
+pub fn components(path: &str) -> Vec<String> {
+    normalize(path)
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .map(|component| {
+            if cfg!(any(windows, target_os = "macos")) {
+                component.to_lowercase()
+            } else {
+                component.to_string()
+            }
+        })
+        .collect()
+}
+
+fn literal_prefix(pattern: &str) -> Vec<String> {
+    components(pattern)
+        .into_iter()
+        .take_while(|component| !component.contains(['*', '?', '[', '{']))
+        .collect()
+}
+
+fn build_set(patterns: &[String]) -> Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(glob(pattern).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| format!("Failed to compile patterns: {}", e))
+}
+
+pub fn check(include: &[String], exclude: &[String]) -> Vec<PatternError> {
+    let include = include
+        .iter()
+        .map(|pattern| (PatternKind::Include, pattern));
+    let exclude = exclude
+        .iter()
+        .map(|pattern| (PatternKind::Exclude, pattern));
+
+    include
+        .chain(exclude)
+        .filter_map(|(kind, pattern)| {
+            let error = if pattern.trim().is_empty() {
+                "Pattern is empty".to_string()
+            } else {
+                glob(pattern).err()?.to_string()
+            };
+            Some(PatternError {
+                kind,
+                pattern: pattern.clone(),
+                error,
+            })
+        })
+        .collect()
+}
+
+impl ScanPatterns {
+    pub fn compile(include: &[String], exclude: &[String]) -> Result<Self, String> {
+        Ok(ScanPatterns {
+            include: build_set(include)?,
+            include_prefixes: include
+                .iter()
+                .map(|pattern| literal_prefix(pattern))
+                .collect(),
+            exclude: build_set(exclude)?,
+        })
+    }
+
+    fn is_excluded(&self, path: &str) -> bool {
+        self.exclude.as_ref().is_some_and(|set| set.is_match(path))
+    }
+
+    // True if nothing below `dir` can be reported, so it needn't be listed
+    pub fn prunes(&self, dir: &Path) -> bool {
+        let path = normalize(&dir.to_string_lossy());
+        if self.is_excluded(&path) {
+            return true;
+        }
+        if self.include.is_none() {
+            return false;
+        }
+
+        // Walk on while the folder leads towards, or lies within, an include
+        let dir = components(&path);
+        !self.include_prefixes.iter().any(|prefix| {
+            let shared = prefix.len().min(dir.len());
+            prefix[..shared] == dir[..shared]
+        })
+    }
+
+    pub fn reports(&self, node_modules: &Path) -> bool {
+        let path = normalize(&node_modules.to_string_lossy());
+        if self.is_excluded(&path) {
+            return false;
+        }
+
+        let project = node_modules
+            .parent()
+            .map(|parent| normalize(&parent.to_string_lossy()))
+            .unwrap_or_default();
+        self.include
+            .as_ref()
+            .is_none_or(|set| set.is_match(&path) || set.is_match(&project))
+    }
+}
@@ -0,0 +1,54 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    // proc/sys/dev, Time Machine backups and trash folders
+    System,
+    // Named in the skip settings, or hidden at the root
+    SkipList,
+    // Outside the include patterns or under an excluded path
+    Pattern,
+    // Excluded by an ignore file
+    IgnoreFile,
+    // Deeper than the configured maximum depth
+    MaxDepth,
+    // Judged unlikely to contain projects
+    NotRelevant,
+    // Skipped from the UI while listing it hung
+    Stalled,
+    // Listing failed for a reason other than permissions
+    Unreadable,
+}
+
+#[derive(Debug, Default)]
+pub struct SkipCounts(Mutex<BTreeMap<SkipReason, usize>>);
+
+impl SkipCounts {
+    pub fn add(&self, reason: SkipReason) {
+        if let Ok(mut counts) = self.0.lock() {
+            *counts.entry(reason).or_default() += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<SkipReason, usize> {
+        self.0
+            .lock()
+            .map(|counts| counts.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanSummary {
+    pub directories_visited: usize,
+    // Includes the permission errors
+    pub directories_skipped: usize,
+    pub skipped_by_reason: BTreeMap<SkipReason, usize>,
+    pub permission_errors: usize,
+    // Since the scan started, so a resumed scan counts its time paused too
+    pub elapsed_ms: u64,
+    pub reclaimable_bytes: u64,
+}
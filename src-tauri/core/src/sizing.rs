@@ -0,0 +1,430 @@
+use std::{
+    fs::{self, Metadata},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Condvar, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use crate::long_paths;
+
+pub const DEFAULT_SIZE_BUDGET: Duration = Duration::from_secs(30);
+// Progress is reported roughly every this many bytes
+const PROGRESS_STEP: u64 = 64 * 1024 * 1024;
+const MAX_SIZE_THREADS: usize = 8;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DirectorySize {
+    pub total: u64,
+    // What deleting the tree actually frees, e.g. minus APFS clone-shared extents
+    pub reclaimable: u64,
+    // Everything but directories, links included
+    pub files: u64,
+}
+
+// 1536 -> "1.5 KB"
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+struct WorkQueue {
+    dirs: Vec<PathBuf>,
+    // Directories being listed right now; each may still queue more
+    busy: usize,
+}
+
+fn next_directory(
+    queue: &Mutex<WorkQueue>,
+    ready: &Condvar,
+    timed_out: &AtomicBool,
+) -> Option<PathBuf> {
+    let mut queue = queue.lock().ok()?;
+    loop {
+        if timed_out.load(Ordering::Relaxed) {
+            return None;
+        }
+        if let Some(dir) = queue.dirs.pop() {
+            queue.busy += 1;
+            return Some(dir);
+        }
+        // Nothing queued and nobody left who could queue more
+        if queue.busy == 0 {
+            return None;
+        }
+        queue = ready.wait(queue).ok()?;
+    }
+}
+
+fn finish_directory(queue: &Mutex<WorkQueue>, ready: &Condvar, found: Vec<PathBuf>) {
+    if let Ok(mut queue) = queue.lock() {
+        queue.busy -= 1;
+        queue.dirs.extend(found);
+    }
+    ready.notify_all();
+}
+
+// Sums a whole tree on several threads, however deep it goes. Symlinks and
+// junctions are never followed. `on_progress` gets the running byte total;
+// returns None if `budget` runs out or `cancel` fires first.
+pub fn measure_tree(
+    root: &Path,
+    budget: Duration,
+    cancel: &CancellationToken,
+    on_progress: &(dyn Fn(u64) + Sync),
+) -> Option<DirectorySize> {
+    if fs::symlink_metadata(root).ok()?.file_type().is_symlink() {
+        return Some(DirectorySize::default());
+    }
+
+    // Looked up once for the whole tree; it never crosses onto another
+    // volume since junctions aren't followed
+    #[cfg(windows)]
+    let cluster = cluster_size(root);
+    #[cfg(not(windows))]
+    let cluster = None;
+
+    let started = Instant::now();
+    let queue = Mutex::new(WorkQueue {
+        dirs: vec![root.to_path_buf()],
+        busy: 0,
+    });
+    let ready = Condvar::new();
+    let total = AtomicU64::new(0);
+    let reclaimable = AtomicU64::new(0);
+    let files = AtomicU64::new(0);
+    let next_report = AtomicU64::new(PROGRESS_STEP);
+    let timed_out = AtomicBool::new(false);
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |threads| threads.get())
+        .min(MAX_SIZE_THREADS);
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                while let Some(dir) = next_directory(&queue, &ready, &timed_out) {
+                    let mut found = Vec::new();
+                    let mut size = DirectorySize::default();
+
+                    if let Ok(entries) = fs::read_dir(&dir) {
+                        for entry in entries.flatten() {
+                            // Reject symlinks/junctions
+                            let Ok(file_type) = entry.file_type() else {
+                                continue;
+                            };
+                            if file_type.is_symlink() {
+                                size.files += 1;
+                                continue;
+                            }
+
+                            if file_type.is_dir() {
+                                found.push(entry.path());
+                            } else if let Ok(metadata) = entry.metadata() {
+                                size.files += 1;
+                                size.total += metadata.len();
+                                size.reclaimable +=
+                                    reclaimable_file_size(&entry.path(), &metadata, cluster);
+                            }
+                        }
+                    }
+
+                    let bytes = total.fetch_add(size.total, Ordering::Relaxed) + size.total;
+                    reclaimable.fetch_add(size.reclaimable, Ordering::Relaxed);
+                    files.fetch_add(size.files, Ordering::Relaxed);
+                    if bytes >= next_report.load(Ordering::Relaxed) {
+                        next_report.store(bytes + PROGRESS_STEP, Ordering::Relaxed);
+                        on_progress(bytes);
+                    }
+
+                    // Stopping early works the same way whatever the reason
+                    if started.elapsed() > budget || cancel.is_cancelled() {
+                        timed_out.store(true, Ordering::Relaxed);
+                    }
+                    finish_directory(&queue, &ready, found);
+                }
+            });
+        }
+    });
+
+    if cancel.is_cancelled() {
+        return None;
+    }
+    if timed_out.load(Ordering::Relaxed) {
+        eprintln!("Size calculation timed out for: {}", root.display());
+        return None;
+    }
+
+    Some(DirectorySize {
+        total: total.into_inner(),
+        reclaimable: reclaimable.into_inner(),
+        files: files.into_inner(),
+    })
+}
+
+// measure_tree on a blocking thread, for async callers; takes any path
+pub async fn measure_directory(
+    path: &Path,
+    budget: Duration,
+    cancel: CancellationToken,
+    on_progress: impl Fn(u64) + Send + Sync + 'static,
+) -> Option<DirectorySize> {
+    let path = long_paths::extended(path);
+    tokio::task::spawn_blocking(move || measure_tree(&path, budget, &cancel, &on_progress))
+        .await
+        .ok()
+        .flatten()
+}
+
+// Bytes that deleting this file would actually give back to the filesystem.
+// `cluster` is the volume's allocation unit, only used on Windows.
+pub fn reclaimable_file_size(path: &Path, metadata: &Metadata, cluster: Option<u64>) -> u64 {
+    // Still linked from the pnpm store (or elsewhere), so nothing is freed
+    if crate::pnpm::is_shared_with_store(path, metadata) {
+        return 0;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if let Some(size) = apfs_private_size(path) {
+            return size;
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        if let Some(size) = ntfs_size_on_disk(path, cluster) {
+            return size;
+        }
+    }
+
+    let _ = (path, cluster);
+    metadata.len()
+}
+
+#[cfg(target_os = "macos")]
+#[repr(C)]
+struct PrivateSizeAttrs {
+    length: u32,
+    returned: libc::attribute_set_t,
+    private_size: libc::off_t,
+}
+
+// On APFS, copy-on-write clones share extents with their source. The
+// "private size" only counts blocks no other file references, which is what a
+// delete frees. Returns None on filesystems that don't support the attribute.
+#[cfg(target_os = "macos")]
+fn apfs_private_size(path: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+
+    let mut request = libc::attrlist {
+        bitmapcount: libc::ATTR_BIT_MAP_COUNT,
+        reserved: 0,
+        commonattr: libc::ATTR_CMN_RETURNED_ATTRS,
+        volattr: 0,
+        dirattr: 0,
+        fileattr: 0,
+        forkattr: libc::ATTR_CMNEXT_PRIVATESIZE,
+    };
+
+    // SAFETY: PrivateSizeAttrs is plain old data, all-zero is a valid value
+    let mut attrs: PrivateSizeAttrs = unsafe { std::mem::zeroed() };
+
+    // SAFETY: both pointers refer to live, correctly sized buffers for the
+    // duration of the call
+    let result = unsafe {
+        libc::getattrlist(
+            c_path.as_ptr(),
+            &mut request as *mut libc::attrlist as *mut libc::c_void,
+            &mut attrs as *mut PrivateSizeAttrs as *mut libc::c_void,
+            std::mem::size_of::<PrivateSizeAttrs>(),
+            libc::FSOPT_ATTR_CMN_EXTENDED | libc::FSOPT_NOFOLLOW,
+        )
+    };
+
+    if result != 0 || attrs.returned.forkattr & libc::ATTR_CMNEXT_PRIVATESIZE == 0 {
+        return None;
+    }
+
+    Some(attrs.private_size.max(0) as u64)
+}
+
+#[cfg(windows)]
+fn to_wide(path: &Path) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+
+    path.as_os_str().encode_wide().chain(Some(0)).collect()
+}
+
+#[cfg(windows)]
+pub fn cluster_size(path: &Path) -> Option<u64> {
+    use windows_sys::Win32::Storage::FileSystem::{GetDiskFreeSpaceW, GetVolumePathNameW};
+
+    let wide_path = to_wide(path);
+    let mut volume = vec![0u16; 1024];
+
+    // SAFETY: wide_path is NUL-terminated and volume is writable for its length
+    let found =
+        unsafe { GetVolumePathNameW(wide_path.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) };
+    if found == 0 {
+        return None;
+    }
+
+    let mut sectors_per_cluster = 0u32;
+    let mut bytes_per_sector = 0u32;
+    let mut free_clusters = 0u32;
+    let mut total_clusters = 0u32;
+
+    // SAFETY: volume is a NUL-terminated root path and all out-params are valid
+    let ok = unsafe {
+        GetDiskFreeSpaceW(
+            volume.as_ptr(),
+            &mut sectors_per_cluster,
+            &mut bytes_per_sector,
+            &mut free_clusters,
+            &mut total_clusters,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+
+    Some(sectors_per_cluster as u64 * bytes_per_sector as u64)
+}
+
+// Matches Explorer's "size on disk": NTFS-compressed and sparse files only
+// occupy the clusters actually allocated, rounded up to the cluster size
+#[cfg(windows)]
+fn ntfs_size_on_disk(path: &Path, cluster: Option<u64>) -> Option<u64> {
+    use windows_sys::Win32::{
+        Foundation::{GetLastError, NO_ERROR},
+        Storage::FileSystem::{GetCompressedFileSizeW, INVALID_FILE_SIZE},
+    };
+
+    let wide_path = to_wide(path);
+    let mut high = 0u32;
+
+    // SAFETY: wide_path is NUL-terminated and high is a valid out-param
+    let low = unsafe { GetCompressedFileSizeW(wide_path.as_ptr(), &mut high) };
+    if low == INVALID_FILE_SIZE && unsafe { GetLastError() } != NO_ERROR {
+        return None;
+    }
+
+    let size = ((high as u64) << 32) | low as u64;
+    match cluster {
+        Some(cluster) if cluster > 0 => Some(size.div_ceil(cluster) * cluster),
+        _ => Some(size),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeTier {
+    Tiny,
+    Small,
+    Large,
+    Huge,
+}
+
+// Lower bounds (in bytes) of each tier above "tiny"
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct SizeTierThresholds {
+    pub small: u64,
+    pub large: u64,
+    pub huge: u64,
+}
+
+impl Default for SizeTierThresholds {
+    fn default() -> Self {
+        SizeTierThresholds {
+            small: 10 * 1024 * 1024,
+            large: 100 * 1024 * 1024,
+            huge: 500 * 1024 * 1024,
+        }
+    }
+}
+
+impl SizeTierThresholds {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.small < self.large && self.large < self.huge {
+            Ok(())
+        } else {
+            Err("Size tier thresholds must be increasing (small < large < huge)".to_string())
+        }
+    }
+
+    pub fn classify(&self, size: u64) -> SizeTier {
+        if size >= self.huge {
+            SizeTier::Huge
+        } else if size >= self.large {
+            SizeTier::Large
+        } else if size >= self.small {
+            SizeTier::Small
+        } else {
+            SizeTier::Tiny
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, Fixture};
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(
+            format_bytes(22 * 1024 * 1024 * 1024 + 400 * 1024 * 1024),
+            "22.4 GB"
+        );
+    }
+
+    #[test]
+    fn measures_awkward_and_deep_trees() {
+        let fixture = Fixture::new("sizing");
+        let (node_modules, files, bytes) =
+            fixtures::awkward_node_modules(&fixture, "app", fixtures::DEEP_LEVELS);
+
+        let size = measure_tree(
+            &long_paths::extended(&node_modules),
+            DEFAULT_SIZE_BUDGET,
+            &CancellationToken::new(),
+            &|_| {},
+        )
+        .unwrap();
+        assert_eq!(size.files, files);
+        assert_eq!(size.total, bytes);
+        // Windows rounds up to whole clusters
+        #[cfg(not(windows))]
+        assert_eq!(size.reclaimable, bytes);
+    }
+
+    #[test]
+    fn gives_up_when_cancelled() {
+        let fixture = Fixture::new("sizing-cancel");
+        let (node_modules, _, _) = fixtures::awkward_node_modules(&fixture, "app", 3);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        assert!(measure_tree(&node_modules, DEFAULT_SIZE_BUDGET, &cancel, &|_| {}).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SnapshotWarning {
+    pub root: String,
+    pub filesystem: String,
+    pub mount_point: String,
+    // None when snapshots can't be listed (e.g. missing tools or privileges)
+    pub has_snapshots: Option<bool>,
+    pub message: String,
+}
+
+#[cfg(target_os = "linux")]
+struct MountEntry {
+    mount_point: String,
+    fs_type: String,
+    source: String,
+}
+
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    // mountinfo escapes spaces, tabs, newlines and backslashes as \ooo
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let octal: String = chars.by_ref().take(3).collect();
+            if let Ok(value) = u8::from_str_radix(&octal, 8) {
+                out.push(value as char);
+                continue;
+            }
+            out.push(c);
+            out.push_str(&octal);
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// Every mount point in the current mount namespace
+#[cfg(target_os = "linux")]
+pub fn mount_points() -> Vec<String> {
+    let Ok(mountinfo) = std::fs::read_to_string("/proc/self/mountinfo") else {
+        return Vec::new();
+    };
+
+    mountinfo
+        .lines()
+        .filter_map(|line| line.split(' ').nth(4))
+        .map(unescape_mount_field)
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn mount_for_path(path: &Path) -> Option<MountEntry> {
+    mount_containing(&std::fs::canonicalize(path).ok()?)
+}
+
+// Like mount_for_path, but takes `canonical` as it is. Only the mount table
+// is read, so a hung network mount at that path can't block the caller.
+#[cfg(target_os = "linux")]
+fn mount_containing(canonical: &Path) -> Option<MountEntry> {
+    let mountinfo = std::fs::read_to_string("/proc/self/mountinfo").ok()?;
+
+    let mut best: Option<MountEntry> = None;
+    for line in mountinfo.lines() {
+        // <id> <parent> <maj:min> <root> <mount point> <options> ... - <fstype> <source> <super options>
+        let Some((left, right)) = line.split_once(" - ") else {
+            continue;
+        };
+        let Some(mount_point) = left.split(' ').nth(4) else {
+            continue;
+        };
+        let mut right = right.split(' ');
+        let (Some(fs_type), Some(source)) = (right.next(), right.next()) else {
+            continue;
+        };
+
+        let mount_point = unescape_mount_field(mount_point);
+        if !canonical.starts_with(&mount_point) {
+            continue;
+        }
+
+        // Longest matching mount point wins; later entries shadow earlier ones
+        if best
+            .as_ref()
+            .is_none_or(|current| mount_point.len() >= current.mount_point.len())
+        {
+            best = Some(MountEntry {
+                mount_point,
+                fs_type: fs_type.to_string(),
+                source: unescape_mount_field(source),
+            });
+        }
+    }
+
+    best
+}
+
+#[cfg(target_os = "linux")]
+fn zfs_has_snapshots(dataset: &str) -> Option<bool> {
+    let output = std::process::Command::new("zfs")
+        .args([
+            "list", "-H", "-t", "snapshot", "-o", "name", "-d", "1", dataset,
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(!String::from_utf8_lossy(&output.stdout).trim().is_empty())
+}
+
+#[cfg(target_os = "linux")]
+fn btrfs_has_snapshots(mount_point: &str) -> Option<bool> {
+    // Listing snapshots needs root; fall back to well-known snapshot layouts
+    if let Ok(output) = std::process::Command::new("btrfs")
+        .args(["subvolume", "list", "-s", mount_point])
+        .output()
+    {
+        if output.status.success() {
+            return Some(!String::from_utf8_lossy(&output.stdout).trim().is_empty());
+        }
+    }
+
+    let mount = Path::new(mount_point);
+    let snapper = mount.join(".snapshots");
+    let timeshift = mount.join("timeshift-btrfs").join("snapshots");
+    for dir in [snapper, timeshift] {
+        if let Ok(mut entries) = std::fs::read_dir(&dir) {
+            return Some(entries.next().is_some());
+        }
+    }
+
+    None
+}
+
+// Filesystem type of the mount holding `path`, e.g. "nfs4" or "btrfs"
+#[cfg(target_os = "linux")]
+pub fn filesystem_type(path: &Path) -> Option<String> {
+    mount_for_path(path).map(|mount| mount.fs_type)
+}
+
+#[cfg(target_os = "linux")]
+pub const NETWORK_FILESYSTEMS: [&str; 6] = ["nfs", "nfs4", "cifs", "smb3", "fuse.sshfs", "9p"];
+
+// Filesystem type and source device of the mount holding `path`
+#[cfg(target_os = "linux")]
+pub fn mount_source(path: &Path) -> Option<(String, String)> {
+    mount_for_path(path).map(|mount| (mount.fs_type, mount.source))
+}
+
+// mount_source for a path that is already canonical, like a mount point
+// from the drive list; never touches the filesystem behind it
+#[cfg(target_os = "linux")]
+pub fn mount_source_of_canonical(path: &Path) -> Option<(String, String)> {
+    mount_containing(path).map(|mount| (mount.fs_type, mount.source))
+}
+
+// Source of the network mount holding `path`, e.g. "//nas/projects"
+#[cfg(target_os = "linux")]
+pub fn network_source(path: &Path) -> Option<String> {
+    mount_for_path(path)
+        .filter(|mount| NETWORK_FILESYSTEMS.contains(&mount.fs_type.as_str()))
+        .map(|mount| mount.source)
+}
+
+pub fn snapshot_warning(root: &str) -> Option<SnapshotWarning> {
+    #[cfg(target_os = "linux")]
+    {
+        let mount = mount_for_path(Path::new(root))?;
+
+        let has_snapshots = match mount.fs_type.as_str() {
+            "zfs" => zfs_has_snapshots(&mount.source),
+            "btrfs" => btrfs_has_snapshots(&mount.mount_point),
+            _ => return None,
+        };
+
+        if has_snapshots == Some(false) {
+            return None;
+        }
+
+        let message = match has_snapshots {
+            Some(_) => format!(
+                "{} has {} snapshots; deleted node_modules keep using disk space until those snapshots expire",
+                mount.mount_point, mount.fs_type
+            ),
+            None => format!(
+                "{} is on {}, which may keep deleted files in snapshots; freed space may not show up immediately",
+                mount.mount_point, mount.fs_type
+            ),
+        };
+
+        Some(SnapshotWarning {
+            root: root.to_string(),
+            filesystem: mount.fs_type,
+            mount_point: mount.mount_point,
+            has_snapshots,
+            message,
+        })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = Path::new(root);
+        None
+    }
+}
@@ -0,0 +1,266 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::{artifacts, long_paths, scan_patterns};
+
+// Fewer folders than this below the root (C:\node_modules, /node_modules)
+// is never a project's install
+const MIN_COMPONENTS: usize = 2;
+
+// Trees owned by the OS or by installed programs. Global npm installs live
+// under some of them, e.g. /usr/local/lib/node_modules.
+#[cfg(windows)]
+fn system_directories() -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = [
+        "SystemRoot",
+        "ProgramFiles",
+        "ProgramFiles(x86)",
+        "ProgramW6432",
+        "ProgramData",
+    ]
+    .into_iter()
+    .filter_map(std::env::var_os)
+    .map(PathBuf::from)
+    .collect();
+    // Per-user installs, this app's included
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        directories.push(Path::new(&local).join("Programs"));
+    }
+    directories
+}
+
+#[cfg(not(windows))]
+fn system_directories() -> Vec<PathBuf> {
+    let mut directories = vec![
+        "/bin",
+        "/boot",
+        "/dev",
+        "/etc",
+        "/lib",
+        "/lib32",
+        "/lib64",
+        "/nix/store",
+        "/proc",
+        "/sbin",
+        "/snap",
+        "/sys",
+        "/usr",
+        "/var/lib",
+    ];
+    if cfg!(target_os = "macos") {
+        directories.extend([
+            "/Applications",
+            "/Library",
+            "/System",
+            "/opt/homebrew",
+            "/private/etc",
+            "/private/var",
+        ]);
+    }
+    directories.into_iter().map(PathBuf::from).collect()
+}
+
+// Where this app itself runs from
+fn install_directory() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    // The whole bundle on macOS, not just Contents/MacOS
+    #[cfg(target_os = "macos")]
+    if let Some(bundle) = exe.ancestors().find(|ancestor| {
+        ancestor
+            .extension()
+            .is_some_and(|extension| extension == "app")
+    }) {
+        return Some(bundle.to_path_buf());
+    }
+    exe.parent().map(Path::to_path_buf)
+}
+
+fn home_directory() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+// Compared case-insensitively where the filesystem usually is
+fn key(path: &Path) -> Vec<String> {
+    scan_patterns::components(&long_paths::plain(path).to_string_lossy())
+}
+
+fn is_within(path: &[String], directory: &Path) -> bool {
+    let directory = key(directory);
+    !directory.is_empty() && path.starts_with(&directory)
+}
+
+fn check(path: &Path) -> Option<String> {
+    let depth = path
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .count();
+    if depth < MIN_COMPONENTS {
+        return Some(format!(
+            "{} is too close to the root of the drive",
+            path.display()
+        ));
+    }
+
+    let path_key = key(path);
+    if let Some(directory) = system_directories()
+        .into_iter()
+        .find(|directory| is_within(&path_key, directory))
+    {
+        return Some(format!(
+            "{} is inside the system folder {}",
+            path.display(),
+            directory.display()
+        ));
+    }
+    if install_directory().is_some_and(|directory| is_within(&path_key, &directory)) {
+        return Some(format!(
+            "{} is inside this app's install folder",
+            path.display()
+        ));
+    }
+    // ~/node_modules is usually a stray global install, not a project
+    if let (Some(home), Some(project)) = (home_directory(), artifacts::project_of(path)) {
+        if key(project) == key(&home) {
+            return Some(format!(
+                "{} sits directly in the home folder",
+                path.display()
+            ));
+        }
+    }
+    None
+}
+
+// Why `path` must not be deleted whatever else the checks say, or None.
+// Checked as given and as resolved, so a link higher up (a junction at
+// C:\node_modules, say) can't point the delete somewhere else.
+pub fn denied_reason(path: &Path) -> Option<String> {
+    let plain = long_paths::plain(path);
+    if let Some(reason) = check(&plain) {
+        return Some(reason);
+    }
+
+    let resolved = match std::fs::canonicalize(long_paths::extended(path)) {
+        Ok(resolved) => long_paths::plain(&resolved),
+        Err(e) => return Some(format!("Failed to resolve {}: {}", path.display(), e)),
+    };
+    if artifacts::kind_of(&resolved).is_none() {
+        return Some(format!(
+            "{} resolves to {}, which isn't a node_modules folder or build artifact",
+            path.display(),
+            resolved.display()
+        ));
+    }
+    check(&resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(target_os = "macos"))]
+    use crate::fixtures::Fixture;
+
+    fn root_node_modules() -> PathBuf {
+        if cfg!(windows) {
+            PathBuf::from(r"C:\node_modules")
+        } else {
+            PathBuf::from("/node_modules")
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn link(link: &Path, target: &Path) {
+        std::os::unix::fs::symlink(target, link).unwrap();
+    }
+
+    #[cfg(windows)]
+    fn link(link: &Path, target: &Path) {
+        let status = std::process::Command::new("cmd")
+            .args(["/c", "mklink", "/J"])
+            .arg(link)
+            .arg(target)
+            .stdout(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "mklink /J failed");
+    }
+
+    #[test]
+    fn folders_next_to_the_root_are_denied() {
+        let reason = denied_reason(&root_node_modules()).unwrap();
+        assert!(reason.contains("too close to the root"), "{}", reason);
+
+        // One level deeper is up to the other checks
+        let deeper = root_node_modules()
+            .parent()
+            .unwrap()
+            .join("projects")
+            .join("node_modules");
+        assert!(check(&deeper).is_none_or(|reason| !reason.contains("too close")));
+    }
+
+    #[test]
+    fn system_folders_are_denied() {
+        for directory in system_directories() {
+            let path = directory.join("lib").join("node_modules");
+            let reason = check(&path).unwrap();
+            assert!(reason.contains("system folder"), "{}", reason);
+        }
+    }
+
+    #[test]
+    fn the_install_folder_is_denied() {
+        let path = install_directory()
+            .unwrap()
+            .join("resources")
+            .join("node_modules");
+        let reason = check(&path).unwrap();
+        assert!(reason.contains("install folder"), "{}", reason);
+    }
+
+    #[test]
+    fn node_modules_directly_in_home_is_denied() {
+        let Some(home) = home_directory() else {
+            return;
+        };
+        let reason = check(&home.join("node_modules")).unwrap();
+        assert!(reason.contains("home folder"), "{}", reason);
+
+        // A project inside home is fine
+        let project = home.join("projects").join("app").join("node_modules");
+        assert!(check(&project).is_none());
+    }
+
+    // The temp folder is under /private/var on macOS, which is denied there
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn a_project_install_is_allowed() {
+        let fixture = Fixture::new("system-paths-allowed");
+        let node_modules = fixture.dir("app/node_modules");
+        assert_eq!(denied_reason(&node_modules), None);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn links_are_judged_by_where_they_lead() {
+        let fixture = Fixture::new("system-paths-links");
+        let documents = fixture.dir("documents");
+        let node_modules = fixture.dir("app").join("node_modules");
+        link(&node_modules, &documents);
+        let reason = denied_reason(&node_modules).unwrap();
+        assert!(reason.contains("isn't a node_modules folder"), "{}", reason);
+
+        // A link into the install folder is denied like the folder itself
+        let installed = install_directory()
+            .unwrap()
+            .join(format!("system-paths-test-{}", std::process::id()));
+        std::fs::create_dir_all(installed.join("node_modules")).unwrap();
+        let linked = fixture.dir("other").join("node_modules");
+        link(&linked, &installed.join("node_modules"));
+        let reason = denied_reason(&linked);
+        std::fs::remove_dir_all(&installed).unwrap();
+        let reason = reason.unwrap();
+        assert!(reason.contains("install folder"), "{}", reason);
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// Caps per scan and per measured or deleted folder; a UI only repaints so
+// often and a terminal scrolls no faster
+pub const SCAN_EVENTS_PER_SECOND: u32 = 10;
+pub const SIZE_EVENTS_PER_SECOND: u32 = 4;
+pub const DELETE_EVENTS_PER_SECOND: u32 = 4;
+
+// Rate-limits one stream of progress reports. The counters a report carries
+// are running totals, so a dropped report is made up for by the next one.
+pub struct Throttle {
+    interval: Duration,
+    last: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    pub fn new(per_second: u32) -> Self {
+        Throttle {
+            interval: Duration::from_secs(1) / per_second.max(1),
+            last: Mutex::new(None),
+        }
+    }
+
+    // Takes the next slot if one is due, so concurrent workers can't both report
+    pub fn claim(&self) -> bool {
+        let Ok(mut last) = self.last.lock() else {
+            return true;
+        };
+        let now = Instant::now();
+        if last.is_some_and(|last| now.duration_since(last) < self.interval) {
+            return false;
+        }
+        *last = Some(now);
+        true
+    }
+}
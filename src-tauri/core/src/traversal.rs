@@ -0,0 +1,261 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    artifacts, ignore_rules, long_paths, scan_options::ScanOptions, scan_patterns, scan_summary,
+};
+
+// What a scan saw in one directory. While the directory's mtime stays the
+// same its entries haven't changed, so a rescan can skip listing it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CachedDirectory {
+    pub modified: u64,
+    pub ignore_files: Vec<String>,
+    // Names of the subdirectories worth visiting, node_modules included
+    pub children: Vec<String>,
+    pub node_modules_modified: Option<u64>,
+}
+
+// Modification time in nanoseconds; second resolution would miss quick edits
+pub fn modified_nanos(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+// What one directory holds, worked out on a blocking thread
+pub struct DirectoryVisit {
+    pub modified: Option<u64>,
+    pub ignore_files: Vec<String>,
+    pub children: Vec<String>,
+    pub rules: Option<Arc<ignore_rules::IgnoreRules>>,
+    // Its node_modules, unless excluded
+    pub node_modules: Option<PathBuf>,
+    pub node_modules_modified: Option<u64>,
+    // Other artifacts of the enabled kinds
+    pub artifacts: Vec<(artifacts::ArtifactKind, PathBuf)>,
+    // Children to walk next
+    pub subdirectories: Vec<PathBuf>,
+    pub skipped: Vec<scan_summary::SkipReason>,
+}
+
+pub fn visit_directory(
+    current_path: &Path,
+    depth: usize,
+    cached: Option<CachedDirectory>,
+    inherited_rules: Option<Arc<ignore_rules::IgnoreRules>>,
+    options: &ScanOptions,
+    patterns: &scan_patterns::ScanPatterns,
+) -> std::io::Result<DirectoryVisit> {
+    let dir = long_paths::extended(current_path);
+    let modified = modified_nanos(&dir);
+
+    let (ignore_files, children) = match cached {
+        // Unchanged since the cached scan, so its entries are too
+        Some(cached) if modified.is_some() && modified == Some(cached.modified) => {
+            (cached.ignore_files, cached.children)
+        }
+        _ => {
+            let entries = fs::read_dir(&dir)?.flatten().collect::<Vec<_>>();
+            let children = entries
+                .iter()
+                .filter_map(|entry| {
+                    // Reject symlinks/junctions
+                    if fs::symlink_metadata(entry.path())
+                        .is_ok_and(|metadata| metadata.file_type().is_symlink())
+                    {
+                        return None;
+                    }
+                    entry.metadata().ok().filter(|metadata| metadata.is_dir())?;
+                    Some(entry.file_name().to_string_lossy().to_string())
+                })
+                .collect::<Vec<_>>();
+            (ignore_rules::ignore_files_in(&entries), children)
+        }
+    };
+
+    let rules = ignore_rules::IgnoreRules::enter(
+        current_path,
+        &ignore_files,
+        options.respect_vcs_ignores,
+        inherited_rules,
+    );
+    let mut visit = DirectoryVisit {
+        modified,
+        ignore_files: Vec::new(),
+        children: Vec::new(),
+        rules,
+        node_modules: None,
+        node_modules_modified: None,
+        artifacts: Vec::new(),
+        subdirectories: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for name in &children {
+        // Entries carry the verbatim listing path; keep reported paths readable
+        let path = current_path.join(name);
+
+        let found = artifacts::detect(current_path, name, &options.artifact_types);
+        let is_artifact = found.iter().any(|(_, artifact)| *artifact == path);
+
+        // Excluded by an ignore file in this or a parent folder. VCS
+        // ignore files always list node_modules and build outputs, so
+        // only our own file can exclude one
+        let excluded = if name == "node_modules" || is_artifact {
+            ignore_rules::is_node_modules_excluded(visit.rules.as_ref(), &path)
+        } else {
+            ignore_rules::is_excluded(visit.rules.as_ref(), &path, true)
+        };
+        if excluded {
+            visit.skipped.push(scan_summary::SkipReason::IgnoreFile);
+            continue;
+        }
+
+        for (artifact_type, artifact) in found {
+            if patterns.reports(&artifact) {
+                visit.artifacts.push((artifact_type, artifact));
+            } else {
+                visit.skipped.push(scan_summary::SkipReason::Pattern);
+            }
+        }
+        // Don't recurse into artifacts either
+        if is_artifact {
+            continue;
+        }
+
+        if name == "node_modules" {
+            if patterns.reports(&path) {
+                visit.node_modules_modified = modified_nanos(&long_paths::extended(&path));
+                visit.node_modules = Some(path);
+            } else {
+                visit.skipped.push(scan_summary::SkipReason::Pattern);
+            }
+            // Don't recurse into node_modules
+            continue;
+        }
+
+        // Only add subdirectory if it's worth scanning
+        if depth >= options.max_depth {
+            visit.skipped.push(scan_summary::SkipReason::MaxDepth);
+        } else if !should_scan_subdirectory(&path, depth, options) {
+            visit.skipped.push(scan_summary::SkipReason::NotRelevant);
+        } else {
+            visit.subdirectories.push(path);
+        }
+    }
+
+    visit.ignore_files = ignore_files;
+    visit.children = children;
+    Ok(visit)
+}
+
+pub fn should_scan_subdirectory(path: &Path, depth: usize, options: &ScanOptions) -> bool {
+    // Don't go deeper than the configured limit
+    if depth >= options.max_depth {
+        return false;
+    }
+
+    // Check if this directory contains development indicators
+    if let Ok(entries) = fs::read_dir(long_paths::extended(path)) {
+        for entry in entries.flatten() {
+            if let Ok(metadata) = entry.metadata() {
+                if metadata.is_file()
+                    && options.is_project_marker(&entry.file_name().to_string_lossy())
+                {
+                    return true; // This directory is worth scanning
+                }
+            }
+        }
+    }
+
+    // If no development indicators found, only scan if it's a common development folder
+    if let Some(name) = path.file_name() {
+        if options.is_dev_folder(&name.to_string_lossy()) {
+            return true;
+        }
+    }
+
+    // Default: scan if not too deep
+    depth < options.unconditional_depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, Fixture, AWKWARD_NAMES};
+
+    fn visit(dir: &Path) -> DirectoryVisit {
+        let patterns = scan_patterns::ScanPatterns::compile(&[], &[]).unwrap();
+        visit_directory(dir, 1, None, None, &ScanOptions::default(), &patterns).unwrap()
+    }
+
+    #[test]
+    fn walks_into_awkwardly_named_projects() {
+        let fixture = Fixture::new("traversal");
+        for name in AWKWARD_NAMES {
+            fixture.file(&format!("{}/package.json", name), 2);
+            fixture.dir(&format!("{}/node_modules", name));
+        }
+
+        let root = visit(&fixture.root);
+        let mut children = root.children.clone();
+        children.sort();
+        let mut expected: Vec<String> = AWKWARD_NAMES.iter().map(|name| name.to_string()).collect();
+        expected.sort();
+        assert_eq!(children, expected);
+        assert_eq!(root.subdirectories.len(), AWKWARD_NAMES.len());
+
+        for project in &root.subdirectories {
+            let found = visit(project);
+            assert_eq!(found.node_modules, Some(project.join("node_modules")));
+            assert!(found.node_modules_modified.is_some());
+        }
+    }
+
+    #[test]
+    fn finds_node_modules_far_down() {
+        let fixture = Fixture::new("traversal-deep");
+        let deep = fixtures::deep_path(fixtures::DEEP_LEVELS);
+        let project = fixture.dir(&deep);
+        fixture.file(&format!("{}/package.json", deep), 2);
+        fixture.dir(&format!("{}/node_modules/left-pad", deep));
+
+        let found = visit(&project);
+        assert_eq!(found.node_modules, Some(project.join("node_modules")));
+        // Never walked into
+        assert!(found.subdirectories.is_empty());
+    }
+
+    #[test]
+    fn reuses_an_unchanged_listing() {
+        let fixture = Fixture::new("traversal-cache");
+        fixture.file("app/package.json", 2);
+        fixture.dir("app/node_modules");
+
+        let first = visit(&fixture.root);
+        let cached = CachedDirectory {
+            modified: first.modified.unwrap(),
+            ignore_files: first.ignore_files.clone(),
+            children: vec!["app".to_string(), "gone".to_string()],
+            node_modules_modified: None,
+        };
+        let patterns = scan_patterns::ScanPatterns::compile(&[], &[]).unwrap();
+        let again = visit_directory(
+            &fixture.root,
+            1,
+            Some(cached),
+            None,
+            &ScanOptions::default(),
+            &patterns,
+        )
+        .unwrap();
+        // Taken from the cache rather than listed again
+        assert_eq!(again.children, vec!["app".to_string(), "gone".to_string()]);
+    }
+}
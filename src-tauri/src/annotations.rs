@@ -0,0 +1,177 @@
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{error::Error, ScanItem};
+
+const ANNOTATIONS_FILE: &str = "project_annotations.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProjectAnnotation {
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+}
+
+impl ProjectAnnotation {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.note.is_none()
+    }
+}
+
+// User-defined tags and notes, keyed by project path
+#[derive(Default)]
+pub struct ProjectAnnotations(Mutex<HashMap<String, ProjectAnnotation>>);
+
+fn annotations_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(dir.join(ANNOTATIONS_FILE))
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        // Tags compare case-insensitively but keep the spelling first used
+        if !tag.is_empty()
+            && !normalized
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(tag))
+        {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+impl ProjectAnnotations {
+    pub fn load(app: &AppHandle) -> Self {
+        let annotations = annotations_path(app)
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        ProjectAnnotations(Mutex::new(annotations))
+    }
+
+    fn save(
+        app: &AppHandle,
+        annotations: &HashMap<String, ProjectAnnotation>,
+    ) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(annotations)
+            .map_err(|e| format!("Failed to serialize annotations: {}", e))?;
+        std::fs::write(annotations_path(app)?, contents)
+            .map_err(|e| format!("Failed to save annotations: {}", e))
+    }
+
+    // Fills in the current tags and note of each item's project
+    pub fn apply(&self, items: &mut [ScanItem]) {
+        let Ok(annotations) = self.0.lock() else {
+            return;
+        };
+
+        for item in items {
+            let annotation = annotations.get(&item.project_path);
+            item.tags = annotation
+                .map(|annotation| annotation.tags.clone())
+                .unwrap_or_default();
+            item.note = annotation.and_then(|annotation| annotation.note.clone());
+        }
+    }
+}
+
+fn has_all_tags(item: &ScanItem, tags: &[String]) -> bool {
+    tags.iter().all(|tag| {
+        item.tags
+            .iter()
+            .any(|item_tag| item_tag.eq_ignore_ascii_case(tag.trim()))
+    })
+}
+
+#[tauri::command]
+pub async fn set_project_annotation(
+    app: AppHandle,
+    annotations: tauri::State<'_, ProjectAnnotations>,
+    project_path: String,
+    tags: Vec<String>,
+    note: Option<String>,
+) -> Result<ProjectAnnotation, Error> {
+    let annotation = ProjectAnnotation {
+        tags: normalize_tags(tags),
+        note: note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty()),
+    };
+
+    let mut stored = annotations
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to update annotations: {}", e))?;
+    if annotation.is_empty() {
+        stored.remove(&project_path);
+    } else {
+        stored.insert(project_path, annotation.clone());
+    }
+    ProjectAnnotations::save(&app, &stored)?;
+
+    Ok(annotation)
+}
+
+#[tauri::command]
+pub async fn get_project_annotations(
+    annotations: tauri::State<'_, ProjectAnnotations>,
+    project_paths: Option<Vec<String>>,
+) -> Result<HashMap<String, ProjectAnnotation>, Error> {
+    let stored = annotations
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read annotations: {}", e))?;
+
+    Ok(match project_paths {
+        Some(paths) => paths
+            .into_iter()
+            .filter_map(|path| {
+                stored
+                    .get(&path)
+                    .cloned()
+                    .map(|annotation| (path, annotation))
+            })
+            .collect(),
+        None => stored.clone(),
+    })
+}
+
+#[tauri::command]
+pub async fn list_project_tags(
+    annotations: tauri::State<'_, ProjectAnnotations>,
+) -> Result<Vec<String>, Error> {
+    let stored = annotations
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read annotations: {}", e))?;
+
+    let mut tags = normalize_tags(
+        stored
+            .values()
+            .flat_map(|annotation| annotation.tags.iter().cloned())
+            .collect(),
+    );
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    Ok(tags)
+}
+
+#[tauri::command]
+pub async fn filter_by_tags(
+    annotations: tauri::State<'_, ProjectAnnotations>,
+    mut items: Vec<ScanItem>,
+    tags: Vec<String>,
+) -> Result<Vec<ScanItem>, Error> {
+    annotations.apply(&mut items);
+    items.retain(|item| has_all_tags(item, &tags));
+    Ok(items)
+}
@@ -0,0 +1,58 @@
+use std::{fs::OpenOptions, io::Write, path::PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+const AUDIT_FILE: &str = "audit.jsonl";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: String,
+    pub action: String,
+    pub path: String,
+    pub reason: Option<String>,
+    pub success: bool,
+}
+
+impl AuditEntry {
+    pub fn legitimacy_override(path: &str, reason: &str, success: bool) -> Self {
+        AuditEntry {
+            timestamp: Utc::now().to_rfc3339(),
+            action: "legitimacy_override".to_string(),
+            path: path.to_string(),
+            reason: Some(reason.to_string()),
+            success,
+        }
+    }
+}
+
+fn audit_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(dir.join(AUDIT_FILE))
+}
+
+fn append(app: &AppHandle, entry: &AuditEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_path(app)?)
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log: {}", e))
+}
+
+// The audit trail must never block the operation it records
+pub fn record(app: &AppHandle, entry: AuditEntry) {
+    if let Err(e) = append(app, &entry) {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,48 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::ManagerExt;
+
+use crate::error::Error;
+
+// Passed to the app when it is launched at login (Run key, LaunchAgent or
+// XDG autostart entry, depending on the platform)
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_autostart::init(
+        tauri_plugin_autostart::MacosLauncher::LaunchAgent,
+        Some(vec![MINIMIZED_ARG]),
+    )
+}
+
+pub fn apply_startup_args(app: &AppHandle, args: &[String]) {
+    if args.iter().any(|arg| arg == MINIMIZED_ARG) {
+        if let Some(window) = app.get_webview_window("main") {
+            if let Err(e) = window.minimize() {
+                eprintln!("Failed to start minimized: {}", e);
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn set_autostart(app: AppHandle, enabled: bool) -> Result<bool, Error> {
+    let autolaunch = app.autolaunch();
+
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| format!("Failed to update autostart: {}", e))?;
+
+    autolaunch
+        .is_enabled()
+        .map_err(|e| format!("Failed to read autostart state: {}", e).into())
+}
+
+#[tauri::command]
+pub async fn get_autostart(app: AppHandle) -> Result<bool, Error> {
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| format!("Failed to read autostart state: {}", e).into())
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::Error;
+
+// Whether the backend currently runs at lowered CPU/IO priority
+#[derive(Default)]
+pub struct BackgroundMode(AtomicBool);
+
+// Linux priorities are per thread, so every thread of the process is updated.
+// SCHED_IDLE rather than a higher nice value: going back to SCHED_OTHER is
+// allowed without CAP_SYS_NICE, lowering the nice value again is not.
+#[cfg(target_os = "linux")]
+fn apply(enabled: bool) -> Result<(), String> {
+    // ioprio classes: 2 = best effort (the default, level 4), 3 = idle
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    let (policy, ioprio) = if enabled {
+        (libc::SCHED_IDLE, 3 << IOPRIO_CLASS_SHIFT)
+    } else {
+        (libc::SCHED_OTHER, (2 << IOPRIO_CLASS_SHIFT) | 4)
+    };
+    let param = libc::sched_param { sched_priority: 0 };
+
+    let threads = std::fs::read_dir("/proc/self/task")
+        .map_err(|e| format!("Failed to list backend threads: {}", e))?;
+    for thread in threads.flatten() {
+        let Ok(tid) = thread.file_name().to_string_lossy().parse::<libc::pid_t>() else {
+            continue;
+        };
+
+        // SAFETY: plain syscall on a thread id of this process; param outlives it
+        let result = unsafe { libc::sched_setscheduler(tid, policy, &param) };
+        if result != 0 {
+            let e = std::io::Error::last_os_error();
+            // The thread exited since the listing
+            if e.raw_os_error() == Some(libc::ESRCH) {
+                continue;
+            }
+            return Err(format!("Failed to change CPU priority: {}", e));
+        }
+        // Best effort: not every IO scheduler honours IO priorities.
+        // SAFETY: same as above, ioprio_set only takes integers
+        unsafe {
+            libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, ioprio);
+        }
+    }
+    Ok(())
+}
+
+// Darwin's background policy throttles both CPU and disk IO
+#[cfg(target_os = "macos")]
+fn apply(enabled: bool) -> Result<(), String> {
+    let policy = if enabled { libc::PRIO_DARWIN_BG } else { 0 };
+
+    // SAFETY: plain syscall on the current process
+    let result = unsafe { libc::setpriority(libc::PRIO_DARWIN_PROCESS, 0, policy) };
+    if result != 0 {
+        let e = std::io::Error::last_os_error();
+        return Err(format!("Failed to change process priority: {}", e));
+    }
+    Ok(())
+}
+
+// Background processing mode lowers CPU, IO and memory priority together
+#[cfg(windows)]
+fn apply(enabled: bool) -> Result<(), String> {
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentProcess, SetPriorityClass, PROCESS_MODE_BACKGROUND_BEGIN,
+        PROCESS_MODE_BACKGROUND_END,
+    };
+
+    let mode = if enabled {
+        PROCESS_MODE_BACKGROUND_BEGIN
+    } else {
+        PROCESS_MODE_BACKGROUND_END
+    };
+
+    // SAFETY: GetCurrentProcess returns a pseudo handle that needs no cleanup
+    let ok = unsafe { SetPriorityClass(GetCurrentProcess(), mode) };
+    if ok == 0 {
+        let e = std::io::Error::last_os_error();
+        return Err(format!("Failed to change process priority: {}", e));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn apply(_enabled: bool) -> Result<(), String> {
+    Err("Background mode is not supported on this platform".to_string())
+}
+
+#[tauri::command]
+pub async fn set_background_mode(
+    enabled: bool,
+    mode: tauri::State<'_, BackgroundMode>,
+) -> Result<bool, Error> {
+    let previous = mode.0.load(Ordering::Relaxed);
+    tokio::task::spawn_blocking(move || {
+        apply(enabled).inspect_err(|_| {
+            // Undo whatever part did change so the flag still tells the truth
+            let _ = apply(previous);
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to change process priority: {}", e))??;
+
+    mode.0.store(enabled, Ordering::Relaxed);
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub async fn get_background_mode(mode: tauri::State<'_, BackgroundMode>) -> Result<bool, Error> {
+    Ok(mode.0.load(Ordering::Relaxed))
+}
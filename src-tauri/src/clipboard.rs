@@ -0,0 +1,77 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+// Pipes `text` into the first command that accepts it
+fn pipe_to(commands: &[(&str, &[&str])], text: &str) -> Result<(), String> {
+    let mut last_error = "No clipboard tool found".to_string();
+
+    for (program, args) in commands {
+        let child = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                last_error = format!("Failed to run {}: {}", program, e);
+                continue;
+            }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(text.as_bytes()) {
+                last_error = format!("Failed to write to {}: {}", program, e);
+                let _ = child.wait();
+                continue;
+            }
+        }
+
+        match child.wait() {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => last_error = format!("{} exited with {}", program, status),
+            Err(e) => last_error = format!("Failed to run {}: {}", program, e),
+        }
+    }
+
+    Err(last_error)
+}
+
+// Uses the platform's clipboard tools rather than linking a clipboard library
+pub fn write_text(text: &str) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        // clip.exe garbles non-ASCII paths; Set-Clipboard keeps them intact
+        pipe_to(
+            &[(
+                "powershell",
+                &[
+                    "-NoProfile",
+                    "-Command",
+                    "[Console]::InputEncoding = [Text.Encoding]::UTF8; Set-Clipboard -Value ([Console]::In.ReadToEnd())",
+                ],
+            )],
+            text,
+        )
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        pipe_to(&[("pbcopy", &[])], text)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        pipe_to(
+            &[
+                ("wl-copy", &[]),
+                ("xclip", &["-selection", "clipboard"]),
+                ("xsel", &["--clipboard", "--input"]),
+            ],
+            text,
+        )
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub use nodemodules_cleaner_core::{
+    delete_backend::{
+        remove_node_modules, trash_unavailable_reason, DeleteHost, DeleteProgress, RemoveOptions,
+    },
+    deletion::{DeleteBackend, DeleteMode},
+};
+
+use crate::{error::Error, quarantine_manifest, settings};
+
+const QUARANTINE_DIR: &str = "quarantine";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackendAvailability {
+    pub backend: DeleteBackend,
+    pub available: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteBackendStatus {
+    pub platform: String,
+    pub selected: DeleteBackend,
+    pub backends: Vec<BackendAvailability>,
+}
+
+// The choice is kept per platform so a synced app data folder doesn't carry
+// e.g. a Linux-only workaround over to macOS
+#[derive(Default)]
+pub struct DeleteBackendSettings(Mutex<HashMap<String, DeleteBackend>>);
+
+impl DeleteBackendSettings {
+    pub fn load(app: &AppHandle) -> Self {
+        let backends = settings::section(app, "delete_backend").unwrap_or_default();
+        DeleteBackendSettings(Mutex::new(backends))
+    }
+
+    pub fn selected(&self) -> DeleteBackend {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|backends| backends.get(std::env::consts::OS).copied())
+            .unwrap_or_default()
+    }
+
+    pub fn select(&self, app: &AppHandle, backend: DeleteBackend) -> Result<(), String> {
+        let mut backends = self
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to update delete backend: {}", e))?;
+        backends.insert(std::env::consts::OS.to_string(), backend);
+        settings::save_section(app, "delete_backend", &*backends)
+    }
+}
+
+pub fn quarantine_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?
+        .join(QUARANTINE_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create quarantine folder: {}", e))?;
+    Ok(dir)
+}
+
+// Quarantines into the app data folder, with a manifest to verify restores
+// against, and streams permanent removal progress to the window
+pub struct AppDeleteHost(pub AppHandle);
+
+impl DeleteHost for AppDeleteHost {
+    fn quarantine_dir(&self) -> Result<PathBuf, String> {
+        quarantine_dir(&self.0)
+    }
+
+    fn quarantined(&self, path: &Path, target: &Path) {
+        // The tree is already safe in quarantine; without a manifest it just
+        // can't be verified on restore
+        if let Err(e) = quarantine_manifest::write(target, path) {
+            eprintln!("{}", e);
+        }
+    }
+
+    fn progress(&self, progress: DeleteProgress) {
+        if let Err(e) = self.0.emit("delete_progress", progress) {
+            eprintln!("Failed to emit delete_progress: {}", e);
+        }
+    }
+}
+
+pub fn host(app: &AppHandle) -> Arc<dyn DeleteHost> {
+    Arc::new(AppDeleteHost(app.clone()))
+}
+
+fn status(settings: &DeleteBackendSettings) -> DeleteBackendStatus {
+    let trash_reason = trash_unavailable_reason(None);
+
+    DeleteBackendStatus {
+        platform: std::env::consts::OS.to_string(),
+        selected: settings.selected(),
+        backends: vec![
+            BackendAvailability {
+                backend: DeleteBackend::Trash,
+                available: trash_reason.is_none(),
+                reason: trash_reason,
+            },
+            BackendAvailability {
+                backend: DeleteBackend::Quarantine,
+                available: true,
+                reason: None,
+            },
+            BackendAvailability {
+                backend: DeleteBackend::Direct,
+                available: true,
+                reason: None,
+            },
+        ],
+    }
+}
+
+#[tauri::command]
+pub async fn get_delete_backend(
+    settings: tauri::State<'_, DeleteBackendSettings>,
+) -> Result<DeleteBackendStatus, Error> {
+    Ok(status(&settings))
+}
+
+#[tauri::command]
+pub async fn set_delete_backend(
+    app: AppHandle,
+    settings: tauri::State<'_, DeleteBackendSettings>,
+    backend: DeleteBackend,
+) -> Result<DeleteBackendStatus, Error> {
+    settings.select(&app, backend)?;
+    Ok(status(&settings))
+}
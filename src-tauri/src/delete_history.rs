@@ -0,0 +1,112 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{delete_backend::DeleteBackend, error::Error, DeleteResult};
+
+const HISTORY_FILE: &str = "delete_history.jsonl";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteHistoryEntry {
+    pub timestamp: String,
+    pub path: String,
+    pub bytes: Option<u64>,
+    pub method: Option<DeleteBackend>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteHistory {
+    // Oldest first
+    pub entries: Vec<DeleteHistoryEntry>,
+    // Over every successful delete on record
+    pub total_bytes_freed: u64,
+}
+
+pub fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(dir.join(HISTORY_FILE))
+}
+
+fn append(app: &AppHandle, entry: &DeleteHistoryEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize delete history entry: {}", e))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_path(app)?)
+        .map_err(|e| format!("Failed to open delete history: {}", e))?;
+
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write delete history: {}", e))
+}
+
+// Like the audit trail, a failure to log never fails the delete. Skipped
+// folders were never touched, so they aren't logged.
+pub fn record(app: &AppHandle, result: &DeleteResult) {
+    if result.status == crate::DeleteStatus::Skipped {
+        return;
+    }
+    let entry = DeleteHistoryEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        path: result.path.clone(),
+        bytes: result.bytes_freed,
+        method: result.method,
+        success: result.success,
+        error: result.error.as_ref().map(|error| error.to_string()),
+    };
+    if let Err(e) = append(app, &entry) {
+        eprintln!("{}", e);
+    }
+}
+
+pub fn read_entries(path: PathBuf) -> Vec<DeleteHistoryEntry> {
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    // A line cut short by a crash is skipped rather than failing the lot
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_delete_history(app: AppHandle) -> Result<DeleteHistory, Error> {
+    let path = history_path(&app)?;
+    let entries: Vec<DeleteHistoryEntry> = tokio::task::spawn_blocking(move || read_entries(path))
+        .await
+        .map_err(|e| format!("Failed to read delete history: {}", e))?;
+
+    let total_bytes_freed = entries
+        .iter()
+        .filter(|entry| entry.success)
+        .filter_map(|entry| entry.bytes)
+        .sum();
+    Ok(DeleteHistory {
+        entries,
+        total_bytes_freed,
+    })
+}
+
+#[tauri::command]
+pub async fn clear_delete_history(app: AppHandle) -> Result<(), Error> {
+    match std::fs::remove_file(history_path(&app)?) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear delete history: {}", e).into()),
+    }
+}
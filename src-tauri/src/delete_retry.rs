@@ -0,0 +1,52 @@
+use std::sync::Mutex;
+
+use tauri::AppHandle;
+
+use crate::{error::Error, settings};
+
+pub use nodemodules_cleaner_core::delete_retry::RetryPolicy;
+
+pub struct DeleteRetrySettings(Mutex<RetryPolicy>);
+
+impl DeleteRetrySettings {
+    pub fn load(app: &AppHandle) -> Self {
+        let policy = settings::section::<RetryPolicy>(app, "delete_retry")
+            .filter(|policy| policy.validate().is_ok())
+            .unwrap_or_default();
+        DeleteRetrySettings(Mutex::new(policy))
+    }
+
+    pub fn current(&self) -> RetryPolicy {
+        self.0.lock().map(|policy| *policy).unwrap_or_default()
+    }
+
+    pub fn update(&self, app: &AppHandle, policy: RetryPolicy) -> Result<(), String> {
+        policy.validate()?;
+
+        let mut current = self
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to update retry policy: {}", e))?;
+        settings::save_section(app, "delete_retry", &policy)?;
+
+        *current = policy;
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn get_delete_retry_policy(
+    settings: tauri::State<'_, DeleteRetrySettings>,
+) -> Result<RetryPolicy, Error> {
+    Ok(settings.current())
+}
+
+#[tauri::command]
+pub async fn set_delete_retry_policy(
+    app: AppHandle,
+    settings: tauri::State<'_, DeleteRetrySettings>,
+    policy: RetryPolicy,
+) -> Result<RetryPolicy, Error> {
+    settings.update(&app, policy)?;
+    Ok(settings.current())
+}
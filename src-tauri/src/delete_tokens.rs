@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::DeleteResult;
+
+// How long a prepared delete can wait for its confirmation
+pub const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+struct PreparedDelete {
+    paths: Vec<String>,
+    expires_at: Instant,
+}
+
+// Deletes that passed prepare_delete and wait to be carried out. A token
+// only works once, only before it expires, and only for the paths it was
+// issued for, so a single IPC call can't delete whatever it names. The paths
+// never leave the backend, so the token itself is just a random id.
+#[derive(Default)]
+pub struct PendingDeletes {
+    prepared: Mutex<HashMap<String, PreparedDelete>>,
+    // Folders a confirmed delete failed on for lack of permissions, the only
+    // ones that may be retried with administrator rights
+    elevatable: Mutex<HashMap<String, Instant>>,
+}
+
+impl PendingDeletes {
+    // A token for deleting exactly `paths`
+    pub fn issue(&self, paths: Vec<String>) -> Result<String, String> {
+        let token = uuid::Uuid::new_v4().to_string();
+
+        let mut prepared = self
+            .prepared
+            .lock()
+            .map_err(|e| format!("Failed to prepare delete: {}", e))?;
+        let now = Instant::now();
+        prepared.retain(|_, delete| delete.expires_at > now);
+        prepared.insert(
+            token.clone(),
+            PreparedDelete {
+                paths,
+                expires_at: now + CONFIRMATION_TIMEOUT,
+            },
+        );
+        Ok(token)
+    }
+
+    // The paths a token was issued for. Used up even when it has expired.
+    pub fn redeem(&self, token: &str) -> Result<Vec<String>, String> {
+        let delete = self
+            .prepared
+            .lock()
+            .map_err(|e| format!("Failed to confirm delete: {}", e))?
+            .remove(token)
+            .ok_or_else(|| "Invalid or already used confirmation token".to_string())?;
+        if Instant::now() > delete.expires_at {
+            return Err("The confirmation expired; prepare the delete again".to_string());
+        }
+        Ok(delete.paths)
+    }
+
+    // Redeems a token the caller names the paths for again; they must be the
+    // ones it was issued for, in any order
+    pub fn redeem_for(&self, token: &str, paths: &[String]) -> Result<(), String> {
+        let mut issued = self.redeem(token)?;
+        let mut requested = paths.to_vec();
+        issued.sort();
+        issued.dedup();
+        requested.sort();
+        requested.dedup();
+        if issued != requested {
+            return Err(
+                "The confirmation was for other folders; prepare the delete again".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    // Lets delete_elevated retry the folders in `results` that failed on permissions
+    pub fn allow_elevation(&self, results: &[DeleteResult]) {
+        let Ok(mut elevatable) = self.elevatable.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        elevatable.retain(|_, expires_at| *expires_at > now);
+        for result in results.iter().filter(|result| result.can_elevate) {
+            elevatable.insert(result.path.clone(), now + CONFIRMATION_TIMEOUT);
+        }
+    }
+
+    // Used up like a token, so each failure allows a single retry
+    pub fn redeem_elevation(&self, path: &str) -> Result<(), String> {
+        let expires_at = self
+            .elevatable
+            .lock()
+            .map_err(|e| format!("Failed to confirm delete: {}", e))?
+            .remove(path)
+            .ok_or_else(|| {
+                format!(
+                    "{} can only be retried as administrator right after a confirmed delete of it failed on permissions",
+                    path
+                )
+            })?;
+        if Instant::now() > expires_at {
+            return Err("The confirmation expired; delete the folder again".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    // Backdates a token so it has just expired
+    fn expire(pending: &PendingDeletes, token: &str) {
+        let mut prepared = pending.prepared.lock().unwrap();
+        prepared.get_mut(token).unwrap().expires_at = Instant::now() - Duration::from_secs(1);
+    }
+
+    #[test]
+    fn a_token_works_once() {
+        let pending = PendingDeletes::default();
+        let token = pending.issue(paths(&["/a/node_modules"])).unwrap();
+
+        assert_eq!(pending.redeem(&token).unwrap(), paths(&["/a/node_modules"]));
+        assert!(pending.redeem(&token).is_err());
+        assert!(pending.redeem("made-up").is_err());
+    }
+
+    #[test]
+    fn an_expired_token_is_refused_and_used_up() {
+        let pending = PendingDeletes::default();
+        let token = pending.issue(paths(&["/a/node_modules"])).unwrap();
+        expire(&pending, &token);
+
+        let error = pending.redeem(&token).unwrap_err();
+        assert!(error.contains("expired"), "{}", error);
+        assert!(pending.redeem(&token).unwrap_err().contains("already used"));
+    }
+
+    #[test]
+    fn redeem_for_needs_the_same_paths() {
+        let pending = PendingDeletes::default();
+        let issued = paths(&["/a/node_modules", "/b/node_modules"]);
+
+        // Order and repeats don't matter
+        let token = pending.issue(issued.clone()).unwrap();
+        let reordered = paths(&["/b/node_modules", "/a/node_modules", "/b/node_modules"]);
+        assert!(pending.redeem_for(&token, &reordered).is_ok());
+
+        let token = pending.issue(issued.clone()).unwrap();
+        let error = pending
+            .redeem_for(&token, &paths(&["/a/node_modules", "/etc"]))
+            .unwrap_err();
+        assert!(error.contains("other folders"), "{}", error);
+        // Used up by the failed attempt too
+        assert!(pending.redeem_for(&token, &issued).is_err());
+
+        let token = pending.issue(issued).unwrap();
+        assert!(pending
+            .redeem_for(&token, &paths(&["/a/node_modules"]))
+            .is_err());
+    }
+
+    #[test]
+    fn only_permission_failures_may_be_elevated_once() {
+        let pending = PendingDeletes::default();
+        let denied = DeleteResult {
+            can_elevate: true,
+            ..DeleteResult::failed("/a/node_modules", Error::PermissionDenied("denied".into()))
+        };
+        let busy = DeleteResult::failed("/b/node_modules", Error::Other("busy".into()));
+        pending.allow_elevation(&[denied, busy]);
+
+        assert!(pending.redeem_elevation("/a/node_modules").is_ok());
+        assert!(pending.redeem_elevation("/a/node_modules").is_err());
+        assert!(pending.redeem_elevation("/b/node_modules").is_err());
+        assert!(pending.redeem_elevation("/c/node_modules").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{error::Error, settings, volumes::DriveType, DriveInfo};
+
+// Which kinds of drives the drive list offers. Removable, optical and
+// network drives are off by default: a scan over an SMB share or a slow USB
+// disk can run for hours.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct DriveVisibility {
+    pub include_removable: bool,
+    pub include_optical: bool,
+    pub include_network: bool,
+}
+
+impl DriveVisibility {
+    pub fn shows(&self, drive: &DriveInfo) -> bool {
+        match drive.drive_type {
+            DriveType::Removable => self.include_removable,
+            DriveType::Optical => self.include_optical,
+            DriveType::Network => self.include_network,
+            DriveType::Fixed | DriveType::Ram | DriveType::Wsl | DriveType::Unknown => true,
+        }
+    }
+}
+
+pub struct DriveVisibilitySettings(Mutex<DriveVisibility>);
+
+impl DriveVisibilitySettings {
+    pub fn load(app: &AppHandle) -> Self {
+        let visibility = settings::section(app, "drive_visibility").unwrap_or_default();
+        DriveVisibilitySettings(Mutex::new(visibility))
+    }
+
+    pub fn current(&self) -> DriveVisibility {
+        self.0
+            .lock()
+            .map(|visibility| visibility.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn update(&self, app: &AppHandle, visibility: DriveVisibility) -> Result<(), String> {
+        let mut current = self
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to update drive visibility: {}", e))?;
+        settings::save_section(app, "drive_visibility", &visibility)?;
+
+        *current = visibility;
+        Ok(())
+    }
+}
+
+// The drives the saved settings let through
+pub fn visible_drives(app: &AppHandle, drives: Vec<DriveInfo>) -> Vec<DriveInfo> {
+    let visibility = app
+        .try_state::<DriveVisibilitySettings>()
+        .map(|settings| settings.current())
+        .unwrap_or_default();
+    drives
+        .into_iter()
+        .filter(|drive| visibility.shows(drive))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn get_drive_visibility(
+    settings: tauri::State<'_, DriveVisibilitySettings>,
+) -> Result<DriveVisibility, Error> {
+    Ok(settings.current())
+}
+
+#[tauri::command]
+pub async fn set_drive_visibility(
+    app: AppHandle,
+    settings: tauri::State<'_, DriveVisibilitySettings>,
+    visibility: DriveVisibility,
+) -> Result<DriveVisibility, Error> {
+    settings.update(&app, visibility)?;
+    Ok(settings.current())
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter};
+
+use crate::{current_drives, drive_visibility, DriveInfo};
+
+// Cheap enough to poll: a handful of stat calls, or one read of the mount table
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Free space moves all the time; only a change in which drives are there,
+// or what they are, is worth reloading the picker for
+fn same_drives(previous: &[DriveInfo], current: &[DriveInfo]) -> bool {
+    previous.len() == current.len()
+        && previous.iter().zip(current).all(|(previous, current)| {
+            previous.path == current.path
+                && previous.name == current.name
+                && previous.drive_type == current.drive_type
+                && previous.label == current.label
+                && previous.total_bytes == current.total_bytes
+        })
+}
+
+// Emits `drives_changed` with the full list whenever a volume is mounted or
+// unmounted, so the drive picker picks up freshly plugged-in disks
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut known: Option<Vec<DriveInfo>> = None;
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            // A stat on a disconnected network drive can block for a while
+            let Ok(drives) = tokio::task::spawn_blocking(current_drives).await else {
+                continue;
+            };
+            let drives = drive_visibility::visible_drives(&app, drives);
+
+            match &known {
+                Some(previous) if !same_drives(previous, &drives) => {
+                    if let Err(e) = app.emit("drives_changed", &drives) {
+                        eprintln!("Failed to emit drive change: {}", e);
+                    }
+                }
+                _ => {}
+            }
+            known = Some(drives);
+        }
+    });
+}
@@ -0,0 +1,187 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use nodemodules_cleaner_core::sizing::format_bytes;
+
+use crate::{
+    annotations::ProjectAnnotations, error::Error, long_paths, report::escape_markdown_cell,
+    scan_cache::ScanCache, session_exclusions::SessionExclusions, ScanItem,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+// One row of an export; flat so it maps onto a spreadsheet as-is
+#[derive(Debug, Serialize, Clone)]
+struct ExportRow {
+    project_path: String,
+    project_name: Option<String>,
+    node_modules_path: String,
+    artifact_type: String,
+    size: Option<u64>,
+    reclaimable_size: Option<u64>,
+    last_modified: Option<String>,
+    // Whole days since last_modified
+    age_days: Option<i64>,
+    tags: Vec<String>,
+    note: Option<String>,
+}
+
+fn artifact_name(item: &ScanItem) -> String {
+    serde_json::to_value(item.artifact_type)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn row(item: &ScanItem, now: DateTime<Utc>) -> ExportRow {
+    let age_days = item
+        .last_modified
+        .as_deref()
+        .and_then(|modified| DateTime::parse_from_rfc3339(modified).ok())
+        .map(|modified| (now - modified.with_timezone(&Utc)).num_days().max(0));
+
+    ExportRow {
+        project_path: item.project_path.clone(),
+        project_name: item.project_name.clone(),
+        node_modules_path: item.node_modules_path.clone(),
+        artifact_type: artifact_name(item),
+        size: item.size,
+        reclaimable_size: item.reclaimable_size,
+        last_modified: item.last_modified.clone(),
+        age_days,
+        tags: item.tags.clone(),
+        note: item.note.clone(),
+    }
+}
+
+// RFC 4180: quote fields holding a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render(rows: &[ExportRow], format: ExportFormat) -> Result<String, String> {
+    let optional = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(rows)
+            .map_err(|e| format!("Failed to serialize results: {}", e)),
+        ExportFormat::Csv => {
+            let mut out = String::from(
+                "project_path,project_name,node_modules_path,artifact_type,size,reclaimable_size,last_modified,age_days,tags,note\r\n",
+            );
+            for row in rows {
+                let fields = [
+                    csv_field(&row.project_path),
+                    csv_field(row.project_name.as_deref().unwrap_or("")),
+                    csv_field(&row.node_modules_path),
+                    row.artifact_type.clone(),
+                    optional(row.size),
+                    optional(row.reclaimable_size),
+                    row.last_modified.clone().unwrap_or_default(),
+                    row.age_days
+                        .map(|days| days.to_string())
+                        .unwrap_or_default(),
+                    csv_field(&row.tags.join(", ")),
+                    csv_field(row.note.as_deref().unwrap_or("")),
+                ];
+                out.push_str(&fields.join(","));
+                out.push_str("\r\n");
+            }
+            Ok(out)
+        }
+        ExportFormat::Markdown => {
+            let total: u64 = rows.iter().filter_map(|row| row.size).sum();
+            let mut out = format!(
+                "# Scan results\n\n\
+                 Exported: {}\n\n\
+                 - Folders: {}\n\
+                 - Total size: {}\n\n\
+                 | Project | Name | Folder | Size | Age (days) |\n|---|---|---|---:|---:|\n",
+                Utc::now().format("%Y-%m-%d %H:%M"),
+                rows.len(),
+                format_bytes(total)
+            );
+            for row in rows {
+                out.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    escape_markdown_cell(&row.project_path),
+                    escape_markdown_cell(row.project_name.as_deref().unwrap_or("")),
+                    escape_markdown_cell(&row.node_modules_path),
+                    row.size
+                        .map(format_bytes)
+                        .unwrap_or_else(|| "-".to_string()),
+                    row.age_days
+                        .map(|days| days.to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                ));
+            }
+            Ok(out)
+        }
+    }
+}
+
+fn with_extension(path: &str, format: ExportFormat) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path.extension().is_some() {
+        path
+    } else {
+        path.with_extension(format.extension())
+    }
+}
+
+fn write(path: &Path, items: &[ScanItem], format: ExportFormat) -> Result<(), String> {
+    let now = Utc::now();
+    let rows: Vec<ExportRow> = items.iter().map(|item| row(item, now)).collect();
+    fs::write(path, render(&rows, format)?).map_err(|e| format!("Failed to write export: {}", e))
+}
+
+// Writes the current results, biggest first: what the scans found that is
+// still on disk and not hidden for this session. Returns the file written.
+#[tauri::command]
+pub async fn export_results(
+    cache: tauri::State<'_, ScanCache>,
+    annotations: tauri::State<'_, ProjectAnnotations>,
+    exclusions: tauri::State<'_, SessionExclusions>,
+    format: ExportFormat,
+    path: String,
+) -> Result<String, Error> {
+    let mut items: Vec<ScanItem> = cache.snapshot().items.values().cloned().collect();
+    exclusions.remove_hidden(&mut items);
+    annotations.apply(&mut items);
+    let path = with_extension(&path, format);
+
+    tokio::task::spawn_blocking(move || {
+        // Deleted since the scan that found them
+        items.retain(|item| long_paths::extended(Path::new(&item.node_modules_path)).exists());
+        items.sort_by_key(|item| std::cmp::Reverse(item.size));
+        write(&path, &items, format)?;
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Export failed: {}", e))?
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, FilePath};
+use tokio::sync::oneshot;
+
+use crate::error::Error;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum FolderSelection {
+    Picked { paths: Vec<String> },
+    // The user closed the dialog without picking anything
+    Cancelled,
+    // `timeout_secs` ran out first; the dialog may still be open
+    TimedOut,
+}
+
+// Waits for the dialog's callback, however long the user takes unless a
+// timeout is given
+async fn wait(
+    picked: oneshot::Receiver<Option<Vec<FilePath>>>,
+    timeout_secs: Option<u64>,
+) -> Result<FolderSelection, Error> {
+    let picked = match timeout_secs {
+        Some(secs) => match tokio::time::timeout(Duration::from_secs(secs), picked).await {
+            Ok(picked) => picked,
+            Err(_) => return Ok(FolderSelection::TimedOut),
+        },
+        None => picked.await,
+    };
+
+    // The callback is dropped without being called if the dialog never opened
+    let picked =
+        picked.map_err(|_| Error::Unsupported("The folder dialog failed to open".to_string()))?;
+    Ok(match picked {
+        Some(paths) if !paths.is_empty() => FolderSelection::Picked {
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+        },
+        _ => FolderSelection::Cancelled,
+    })
+}
+
+#[tauri::command]
+pub async fn open_folder_dialog(
+    app: AppHandle,
+    timeout_secs: Option<u64>,
+) -> Result<FolderSelection, Error> {
+    let (sender, picked) = oneshot::channel();
+    app.dialog().file().pick_folder(move |path| {
+        // Nobody is listening any more once the wait timed out
+        let _ = sender.send(path.map(|path| vec![path]));
+    });
+    wait(picked, timeout_secs).await
+}
+
+// Like open_folder_dialog, but several folders can be picked at once
+#[tauri::command]
+pub async fn open_folders_dialog(
+    app: AppHandle,
+    timeout_secs: Option<u64>,
+) -> Result<FolderSelection, Error> {
+    let (sender, picked) = oneshot::channel();
+    app.dialog().file().pick_folders(move |paths| {
+        let _ = sender.send(paths);
+    });
+    wait(picked, timeout_secs).await
+}
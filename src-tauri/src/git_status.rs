@@ -0,0 +1,114 @@
+use std::{path::Path, process::Command};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitStatus {
+    // Uncommitted changes to tracked files under the project
+    pub changes: usize,
+    // Commits on the current branch its upstream doesn't have yet
+    pub unpushed: usize,
+}
+
+impl GitStatus {
+    pub fn is_dirty(&self) -> bool {
+        self.changes > 0 || self.unpushed > 0
+    }
+}
+
+pub fn is_repository(project: &Path) -> bool {
+    project.ancestors().any(|dir| dir.join(".git").exists())
+}
+
+fn git(project: &Path) -> Command {
+    let mut command = Command::new("git");
+
+    // Runs once per project, so don't flash a console window for each
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    command
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .arg("--no-optional-locks")
+        .arg("-C")
+        .arg(project);
+    command
+}
+
+// Names of the filter drivers the repository's config defines. Reading
+// config runs nothing.
+fn filter_drivers(project: &Path) -> Vec<String> {
+    let Ok(output) = git(project)
+        .args(["config", "--name-only", "--get-regexp", r"^filter\..*\."])
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    let mut drivers: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        // filter.<driver>.<key>, where the driver name may hold dots
+        .filter_map(|key| key.strip_prefix("filter.")?.rsplit_once('.'))
+        .map(|(driver, _)| driver.to_string())
+        .collect();
+    drivers.sort();
+    drivers.dedup();
+    drivers
+}
+
+// None when the project isn't in a git repository or git isn't installed
+pub fn status(project: &Path) -> Option<GitStatus> {
+    if !is_repository(project) {
+        return None;
+    }
+
+    // Read-only: no index.lock that could trip up the user's own git
+    // commands. A downloaded repo's config can also name commands git runs
+    // during status: the fsmonitor hook, and clean filters for files whose
+    // timestamps changed. Both are switched off, and submodules, which
+    // bring a config of their own, are left out.
+    let mut command = git(project);
+    command.args(["-c", "core.fsmonitor=false"]);
+    for driver in filter_drivers(project) {
+        // An empty command is no filter; not required, so skipping it isn't an error
+        for key in ["clean", "smudge", "process"] {
+            command.arg("-c").arg(format!("filter.{}.{}=", driver, key));
+        }
+        command
+            .arg("-c")
+            .arg(format!("filter.{}.required=false", driver));
+    }
+    let output = command
+        .args([
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "--untracked-files=no",
+            "--ignore-submodules=all",
+            "--",
+            ".",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let mut status = GitStatus::default();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        // "# branch.ab +<ahead> -<behind>", only present with an upstream
+        if let Some(counts) = line.strip_prefix("# branch.ab +") {
+            status.unpushed = counts
+                .split(' ')
+                .next()
+                .and_then(|ahead| ahead.parse().ok())
+                .unwrap_or(0);
+        } else if !line.starts_with('#') {
+            status.changes += 1;
+        }
+    }
+    Some(status)
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{jobs::JobRegistry, sizing, ScanItem};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+// An install counts as finished once its folder stops changing for this long
+const QUIET_PERIOD: Duration = Duration::from_secs(10);
+
+// Written by the package managers when an install completes
+const INSTALL_MARKERS: [&str; 4] = [
+    ".package-lock.json", // npm
+    ".modules.yaml",      // pnpm
+    ".yarn-state.yml",    // yarn berry
+    ".yarn-integrity",    // yarn classic
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ItemSizeUpdated {
+    pub project_path: String,
+    pub node_modules_path: String,
+    pub size: u64,
+    pub reclaimable_size: u64,
+}
+
+struct TrackedInstall {
+    project_path: String,
+    fingerprint: Option<SystemTime>,
+    // When the last change was seen, while an install seems to be running
+    changed_at: Option<Instant>,
+}
+
+// node_modules folders from recent scans whose size should follow reinstalls
+#[derive(Default)]
+pub struct TrackedInstalls(Mutex<HashMap<String, TrackedInstall>>);
+
+// Latest modification among the folder itself and the install markers
+fn fingerprint(node_modules: &Path) -> Option<SystemTime> {
+    std::iter::once(node_modules.to_path_buf())
+        .chain(
+            INSTALL_MARKERS
+                .iter()
+                .map(|marker| node_modules.join(marker)),
+        )
+        .filter_map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .max()
+}
+
+impl TrackedInstalls {
+    pub fn track(&self, items: &[ScanItem]) {
+        let Ok(mut tracked) = self.0.lock() else {
+            return;
+        };
+
+        for item in items {
+            tracked
+                .entry(item.node_modules_path.clone())
+                .or_insert_with(|| TrackedInstall {
+                    project_path: item.project_path.clone(),
+                    fingerprint: fingerprint(Path::new(&item.node_modules_path)),
+                    changed_at: None,
+                });
+        }
+    }
+
+    // Paths whose install just went quiet; drops folders that no longer exist
+    fn settled(&self, fingerprints: HashMap<String, Option<SystemTime>>) -> Vec<(String, String)> {
+        let Ok(mut tracked) = self.0.lock() else {
+            return Vec::new();
+        };
+
+        let mut settled = Vec::new();
+        tracked.retain(|path, install| {
+            let Some(current) = fingerprints.get(path) else {
+                // Tracked after the fingerprints were taken
+                return true;
+            };
+            if current.is_none() {
+                return false;
+            }
+
+            if *current != install.fingerprint {
+                install.fingerprint = *current;
+                install.changed_at = Some(Instant::now());
+            } else if install
+                .changed_at
+                .is_some_and(|changed| changed.elapsed() >= QUIET_PERIOD)
+            {
+                install.changed_at = None;
+                settled.push((install.project_path.clone(), path.clone()));
+            }
+            true
+        });
+
+        settled
+    }
+
+    fn paths(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .map(|tracked| tracked.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let Some(tracked) = app.try_state::<TrackedInstalls>() else {
+                continue;
+            };
+            let paths = tracked.paths();
+            if paths.is_empty() {
+                continue;
+            }
+
+            let Ok(fingerprints) = tokio::task::spawn_blocking(move || {
+                paths
+                    .into_iter()
+                    .map(|path| {
+                        let current = fingerprint(Path::new(&path));
+                        (path, current)
+                    })
+                    .collect::<HashMap<_, _>>()
+            })
+            .await
+            else {
+                continue;
+            };
+
+            for (project_path, node_modules_path) in tracked.settled(fingerprints) {
+                let cancel = app
+                    .try_state::<JobRegistry>()
+                    .map(|jobs| jobs.child_token())
+                    .unwrap_or_default();
+                let Some(size) = sizing::measure_directory(
+                    Path::new(&node_modules_path),
+                    sizing::DEFAULT_SIZE_BUDGET,
+                    cancel,
+                    |_| {},
+                )
+                .await
+                else {
+                    continue;
+                };
+
+                let updated = ItemSizeUpdated {
+                    project_path,
+                    node_modules_path,
+                    size: size.total,
+                    reclaimable_size: size.reclaimable,
+                };
+                if let Err(e) = app.emit("item_size_updated", updated) {
+                    eprintln!("Failed to emit size update: {}", e);
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::error::Error;
+
+pub use nodemodules_cleaner_core::jobs::{JobKind, JobRegistry, JobStatus};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+const STALL_AFTER: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JobStalled {
+    pub id: u64,
+    pub kind: JobKind,
+    pub current_path: String,
+    pub seconds_since_progress: u64,
+    pub can_skip: bool,
+}
+
+pub fn spawn_watchdog(app: AppHandle, registry: JobRegistry) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            for status in registry.newly_stalled(STALL_AFTER) {
+                let stalled = JobStalled {
+                    id: status.id,
+                    kind: status.kind,
+                    current_path: status.current_path,
+                    seconds_since_progress: status.seconds_since_progress,
+                    can_skip: status.kind.can_skip(),
+                };
+                if let Err(e) = app.emit("job_stalled", stalled) {
+                    eprintln!("Failed to emit stalled job: {}", e);
+                }
+            }
+
+            let statuses = registry.statuses();
+            if statuses.is_empty() {
+                continue;
+            }
+            if let Err(e) = app.emit("job_heartbeat", statuses) {
+                eprintln!("Failed to emit heartbeat: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_jobs(registry: tauri::State<'_, JobRegistry>) -> Result<Vec<JobStatus>, Error> {
+    Ok(registry.statuses())
+}
+
+// Stops one delete job, or every running one when no id is given. Folders
+// already being removed are finished; the rest of the batch is skipped.
+// Returns how many delete jobs were cancelled.
+#[tauri::command]
+pub async fn cancel_delete(
+    registry: tauri::State<'_, JobRegistry>,
+    job_id: Option<u64>,
+) -> Result<usize, Error> {
+    let mut cancelled = 0;
+    for job in registry.jobs() {
+        let is_delete = job
+            .status()
+            .is_some_and(|status| status.kind == JobKind::Delete);
+        if is_delete && job_id.is_none_or(|id| id == job.id()) {
+            job.cancellation().cancel();
+            cancelled += 1;
+        }
+    }
+    Ok(cancelled)
+}
+
+#[tauri::command]
+pub async fn skip_stalled_directory(
+    registry: tauri::State<'_, JobRegistry>,
+    job_id: u64,
+) -> Result<String, Error> {
+    let job = registry
+        .find(job_id)
+        .ok_or_else(|| Error::NotFound("Job is no longer running".to_string()))?;
+    let status = job
+        .status()
+        .ok_or_else(|| "Failed to read job state".to_string())?;
+
+    if !status.kind.can_skip() {
+        return Err(Error::InvalidInput(
+            "Deletions can't be skipped while in progress".to_string(),
+        ));
+    }
+
+    job.skip();
+    Ok(status.current_path)
+}
@@ -0,0 +1,153 @@
+use std::{path::Path, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{error::Error, long_paths, settings, ScanResults};
+
+const MAX_RECENT_ROOTS: usize = 10;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LaunchScanSettings {
+    pub auto_scan_on_launch: bool,
+    // From the last scan started in the UI
+    pub last_roots: Vec<String>,
+    pub include_sizes: bool,
+    // Every root scanned in the UI, most recently used first
+    pub recent_roots: Vec<String>,
+}
+
+pub struct LaunchScan {
+    settings: Mutex<LaunchScanSettings>,
+    // Results of this launch's scan once it finishes, until the frontend takes them
+    results: Mutex<Option<ScanResults>>,
+}
+
+impl LaunchScan {
+    pub fn load(app: &AppHandle) -> Self {
+        let settings = settings::section(app, "launch_scan").unwrap_or_default();
+        LaunchScan {
+            settings: Mutex::new(settings),
+            results: Mutex::new(None),
+        }
+    }
+
+    pub fn current(&self) -> LaunchScanSettings {
+        self.settings
+            .lock()
+            .map(|settings| settings.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn update(&self, app: &AppHandle, settings: LaunchScanSettings) -> Result<(), String> {
+        let mut current = self
+            .settings
+            .lock()
+            .map_err(|e| format!("Failed to update launch scan settings: {}", e))?;
+        settings::save_section(app, "launch_scan", &settings)?;
+        *current = settings;
+        Ok(())
+    }
+}
+
+// Remembers what the user scanned, for the next launch
+pub fn remember_roots(app: &AppHandle, roots: &[String], include_sizes: bool) {
+    let Some(launch_scan) = app.try_state::<LaunchScan>() else {
+        return;
+    };
+    let current = launch_scan.current();
+    let mut recent_roots = roots.to_vec();
+    recent_roots.extend(
+        current
+            .recent_roots
+            .iter()
+            .filter(|root| !roots.contains(root))
+            .cloned(),
+    );
+    recent_roots.truncate(MAX_RECENT_ROOTS);
+    let settings = LaunchScanSettings {
+        last_roots: roots.to_vec(),
+        include_sizes,
+        recent_roots,
+        ..current
+    };
+    if let Err(e) = launch_scan.update(app, settings) {
+        eprintln!("{}", e);
+    }
+}
+
+// Rescans the last roots when enabled. Progress and found items stream
+// through the usual scan events; the results are also kept for
+// take_launch_scan_results in case the frontend wasn't listening yet.
+pub fn spawn(app: AppHandle) {
+    let Some(launch_scan) = app.try_state::<LaunchScan>() else {
+        return;
+    };
+    let settings = launch_scan.current();
+    if !settings.auto_scan_on_launch || settings.last_roots.is_empty() {
+        return;
+    }
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let window = window.as_ref().window();
+        match crate::run_launch_scan(&window, &settings.last_roots, settings.include_sizes).await {
+            Ok(results) => {
+                if let Some(launch_scan) = app.try_state::<LaunchScan>() {
+                    if let Ok(mut stored) = launch_scan.results.lock() {
+                        *stored = Some(results);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Launch scan failed: {}", e),
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_launch_scan_settings(
+    launch_scan: tauri::State<'_, LaunchScan>,
+) -> Result<LaunchScanSettings, Error> {
+    Ok(launch_scan.current())
+}
+
+#[tauri::command]
+pub async fn set_launch_scan_settings(
+    app: AppHandle,
+    launch_scan: tauri::State<'_, LaunchScan>,
+    settings: LaunchScanSettings,
+) -> Result<LaunchScanSettings, Error> {
+    launch_scan.update(&app, settings)?;
+    Ok(launch_scan.current())
+}
+
+// None while the launch scan is still running, or if there was none
+#[tauri::command]
+pub async fn take_launch_scan_results(
+    launch_scan: tauri::State<'_, LaunchScan>,
+) -> Result<Option<ScanResults>, Error> {
+    let mut results = launch_scan
+        .results
+        .lock()
+        .map_err(|e| format!("Failed to read launch scan results: {}", e))?;
+    Ok(results.take())
+}
+
+// Roots scanned before, most recent first, leaving out any that are gone
+#[tauri::command]
+pub async fn get_recent_roots(
+    launch_scan: tauri::State<'_, LaunchScan>,
+) -> Result<Vec<String>, Error> {
+    let recent_roots = launch_scan.current().recent_roots;
+    tokio::task::spawn_blocking(move || {
+        recent_roots
+            .into_iter()
+            .filter(|root| long_paths::extended(Path::new(root)).is_dir())
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to read recent roots: {}", e).into())
+}
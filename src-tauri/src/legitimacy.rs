@@ -0,0 +1,55 @@
+use std::sync::Mutex;
+
+use tauri::AppHandle;
+
+use crate::{error::Error, settings};
+
+pub use nodemodules_cleaner_core::legitimacy::{check_batch, network_share, SafetyCheckOptions};
+
+pub struct SafetyCheckSettings(Mutex<SafetyCheckOptions>);
+
+impl SafetyCheckSettings {
+    pub fn load(app: &AppHandle) -> Self {
+        let options = settings::section::<SafetyCheckOptions>(app, "safety_checks")
+            .filter(|options| options.validate().is_ok())
+            .unwrap_or_default();
+        SafetyCheckSettings(Mutex::new(options))
+    }
+
+    pub fn current(&self) -> SafetyCheckOptions {
+        self.0
+            .lock()
+            .map(|options| options.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn update(&self, app: &AppHandle, options: SafetyCheckOptions) -> Result<(), String> {
+        options.validate()?;
+
+        let mut current = self
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to update safety check settings: {}", e))?;
+        settings::save_section(app, "safety_checks", &options)?;
+
+        *current = options;
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn get_safety_check_settings(
+    settings: tauri::State<'_, SafetyCheckSettings>,
+) -> Result<SafetyCheckOptions, Error> {
+    Ok(settings.current())
+}
+
+#[tauri::command]
+pub async fn set_safety_check_settings(
+    app: AppHandle,
+    settings: tauri::State<'_, SafetyCheckSettings>,
+    options: SafetyCheckOptions,
+) -> Result<SafetyCheckOptions, Error> {
+    settings.update(&app, options)?;
+    Ok(settings.current())
+}
@@ -1,58 +1,325 @@
 use std::{
+    collections::HashMap,
     fs,
     path::{Path, PathBuf},
-    time::{Duration, Instant},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
 };
 
 use serde::{Deserialize, Serialize};
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 use tokio::task;
+use tokio_util::sync::CancellationToken;
+
+use nodemodules_cleaner_core::{
+    activity, artifacts,
+    deletion::{check_deletable, DeleteResult, DeleteStatus},
+    elevation, error, long_paths, nested_installs,
+    scan::{self, PreviousScan, ScanConfig, ScanProgress},
+    scan_patterns, scan_summary, sizing, snapshots,
+};
+
+#[cfg(target_os = "macos")]
+use nodemodules_cleaner_core::deletion::is_macos_backup_or_trash;
+
+use crate::{error::Error, scan_host::AppScanHost};
+
+mod annotations;
+mod audit;
+mod autostart;
+mod background_mode;
+mod clipboard;
+mod delete_backend;
+mod delete_history;
+mod delete_retry;
+mod delete_tokens;
+mod drive_visibility;
+mod drive_watch;
+mod export;
+mod folder_dialog;
+mod git_status;
+mod install_watch;
+mod jobs;
+mod launch_scan;
+mod legitimacy;
+mod metrics;
+mod notifications;
+mod planner;
+mod project_metadata;
+mod projection;
+mod protected_projects;
+mod quarantine_manifest;
+mod reinstall;
+mod report;
+mod risk;
+mod root_health;
+mod root_watch;
+mod scan_cache;
+mod scan_estimate;
+mod scan_file;
+mod scan_history;
+mod scan_host;
+mod scan_settings;
+mod scheduler;
+mod session_exclusions;
+mod settings;
+mod shell_integration;
+mod size_history;
+mod staleness;
+mod system_overview;
+mod undo;
+mod volumes;
+mod workspaces;
+#[cfg(windows)]
+mod wsl;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ScanItem {
     pub project_path: String,
+    // For other kinds of artifact, the artifact's folder
     pub node_modules_path: String,
+    #[serde(default)]
+    pub artifact_type: artifacts::ArtifactKind,
     pub size: Option<u64>,
+    pub reclaimable_size: Option<u64>,
+    // Files and links in the folder, counted along with the size
+    #[serde(default)]
+    pub file_count: Option<u64>,
+    pub size_tier: Option<sizing::SizeTier>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+    // From the sibling package.json
+    pub project_name: Option<String>,
+    pub project_version: Option<String>,
+    pub package_manager: Option<project_metadata::PackageManager>,
+    // Latest change to the project's own files, node_modules excluded
+    pub last_modified: Option<String>,
+    // Uncommitted changes or unpushed commits; None outside a git repository
+    pub git_dirty: Option<bool>,
+    // Monorepo root this project is a member of, or the project itself if it's the root
+    pub workspace_root: Option<String>,
+    // How likely deleting it gets in someone's way, as of the scan
+    #[serde(default)]
+    pub risk: Option<risk::Risk>,
+    // Installs below this one's packages, when the scan looked for them
+    #[serde(default)]
+    pub nested: Vec<nested_installs::NestedInstall>,
+    // Inside a OneDrive/Dropbox/Google Drive/iCloud folder; left unmeasured
+    // since reading online-only files downloads them
+    #[serde(default)]
+    pub is_cloud_synced: bool,
+    // On the protect list, so it can't be deleted
+    #[serde(default)]
+    pub is_protected: bool,
+    // A pnpm layout: `size` counts every linked file, `reclaimable_size`
+    // only what isn't shared with the store
+    #[serde(default)]
+    pub is_pnpm: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanResults {
+    pub items: Vec<ScanItem>,
+    pub summary: scan_summary::ScanSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteBatch {
+    pub results: Vec<DeleteResult>,
+    pub total_bytes_freed: u64,
+    pub total_files_removed: u64,
+    // Picked folders inside other node_modules, which weren't deleted on
+    // their own
+    #[serde(default)]
+    pub nested: Vec<nested_installs::NestedSelection>,
+}
+
+impl DeleteBatch {
+    fn new(results: Vec<DeleteResult>) -> Self {
+        let deleted = results.iter().filter(|result| result.success);
+        DeleteBatch {
+            total_bytes_freed: deleted
+                .clone()
+                .filter_map(|result| result.bytes_freed)
+                .sum(),
+            total_files_removed: deleted.filter_map(|result| result.files_removed).sum(),
+            results,
+            nested: Vec::new(),
+        }
+    }
+}
+
+// Streamed by execute_delete as each folder finishes, in completion order
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeleteItemCompleted {
+    pub result: DeleteResult,
+    pub completed: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanItemFound {
+    pub session_id: String,
+    pub item: ScanItem,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanStarted {
+    pub session_id: String,
+    pub roots: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanRootWarnings {
+    pub session_id: String,
+    pub roots: Vec<root_health::RootHealth>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanState {
+    Running,
+    Paused,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ScanProgress {
-    pub current_folder: String,
+pub struct ScanInfo {
+    pub session_id: String,
+    pub roots: Vec<String>,
+    pub started_at: String,
+    pub state: ScanState,
     pub folders_scanned: usize,
-    pub total_folders_estimated: usize,
     pub node_modules_found: usize,
-    pub directories_skipped: usize,
-    pub is_complete: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct DeleteResult {
+pub struct DeletePreviewItem {
     pub path: String,
-    pub success: bool,
-    pub error: Option<String>,
+    pub is_legitimate: bool,
+    // What deleting it would free, when known
+    pub size: Option<u64>,
+    // As scored by the scan, or now for folders it didn't cover; high ones
+    // need confirm_high_risk to be deleted
+    pub risk: Option<risk::RiskLevel>,
 }
 
+// The first half of a delete: what would go, and the token execute_delete
+// needs to go ahead with exactly that
 #[derive(Debug, Serialize, Deserialize)]
+pub struct DeletePlan {
+    pub token: String,
+    pub expires_in_secs: u64,
+    pub items: Vec<DeletePreviewItem>,
+}
+
+// What execute_delete would do with one folder
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DryRunItem {
+    pub path: String,
+    // Deleted, Failed (a check fails) or NeedsOverride (only legitimacy fails)
+    pub status: DeleteStatus,
+    pub reason: Option<Error>,
+    // None if the folder won't be deleted or couldn't be measured in time
+    pub bytes: Option<u64>,
+    pub files: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DryRunReport {
+    pub items: Vec<DryRunItem>,
+    // Over the folders that would be deleted without an override
+    pub total_bytes: u64,
+    pub total_files: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct DriveInfo {
     pub path: String,
     pub name: String,
+    // Network drives are slow to scan
+    pub drive_type: volumes::DriveType,
+    pub label: Option<String>,
+    pub total_bytes: Option<u64>,
+    pub free_bytes: Option<u64>,
+}
+
+impl DriveInfo {
+    fn new(path: String, name: String) -> Self {
+        let details = volumes::details(Path::new(&path));
+        DriveInfo {
+            path,
+            name,
+            drive_type: details.drive_type,
+            label: details.label,
+            total_bytes: details.total_bytes,
+            free_bytes: details.free_bytes,
+        }
+    }
 }
 
+// Removable, optical and network drives are left out unless the saved
+// visibility settings or `include_all` say otherwise
 #[tauri::command]
-async fn list_drives() -> Result<Vec<DriveInfo>, String> {
+async fn list_drives(
+    app: tauri::AppHandle,
+    include_all: Option<bool>,
+) -> Result<Vec<DriveInfo>, Error> {
+    let drives = task::spawn_blocking(current_drives)
+        .await
+        .map_err(|e| format!("Failed to list drives: {}", e))?;
+    if include_all.unwrap_or(false) {
+        return Ok(drives);
+    }
+    Ok(drive_visibility::visible_drives(&app, drives))
+}
+
+// Whether a directory listing entry is a folder, going by the listing
+// itself: stat on a hung network mount point blocks. Links are followed.
+#[cfg(not(target_os = "windows"))]
+fn is_dir_entry(entry: &fs::DirEntry) -> bool {
+    match entry.file_type() {
+        Ok(file_type) if file_type.is_symlink() => entry
+            .path()
+            .metadata()
+            .is_ok_and(|metadata| metadata.is_dir()),
+        Ok(file_type) => file_type.is_dir(),
+        Err(_) => false,
+    }
+}
+
+fn current_drives() -> Vec<DriveInfo> {
     #[cfg(target_os = "windows")]
     {
+        use windows_sys::Win32::Storage::FileSystem::GetLogicalDrives;
+
+        // SAFETY: no arguments; returns a bitmask of assigned drive letters
+        let assigned = unsafe { GetLogicalDrives() };
         let mut drives = Vec::new();
-        for letter in b'A'..=b'Z' {
+        for (index, letter) in (b'A'..=b'Z').enumerate() {
+            if assigned & (1 << index) == 0 {
+                continue;
+            }
             let drive_path = format!("{}:\\", letter as char);
-            if Path::new(&drive_path).exists() {
-                drives.push(DriveInfo {
-                    path: drive_path.clone(),
-                    name: format!("Drive {}", letter as char),
-                });
+            let Some(drive_type) = volumes::drive_type(&drive_path) else {
+                continue;
+            };
+
+            // A disconnected mapped drive can take seconds to stat, so only
+            // local drives are probed (card readers without a card fail here)
+            let is_network = drive_type == volumes::DriveType::Network;
+            if !is_network && !Path::new(&drive_path).exists() {
+                continue;
             }
+            let name = if is_network {
+                format!("Network Drive {}", letter as char)
+            } else {
+                format!("Drive {}", letter as char)
+            };
+            drives.push(DriveInfo::new(drive_path, name));
         }
-        Ok(drives)
+        drives.extend(wsl::home_roots());
+        drives
     }
 
     #[cfg(not(target_os = "windows"))]
@@ -60,25 +327,30 @@ async fn list_drives() -> Result<Vec<DriveInfo>, String> {
         let mut drives = Vec::new();
 
         // Add root directory
-        drives.push(DriveInfo {
-            path: "/".to_string(),
-            name: "Root Directory".to_string(),
-        });
+        drives.push(DriveInfo::new(
+            "/".to_string(),
+            "Root Directory".to_string(),
+        ));
 
         // On macOS, also check /Volumes for mounted volumes
         #[cfg(target_os = "macos")]
         {
             if let Ok(entries) = fs::read_dir("/Volumes") {
                 for entry in entries.flatten() {
-                    if let Ok(metadata) = entry.metadata() {
-                        if metadata.is_dir() {
-                            let path = entry.path();
-                            if let Some(name) = path.file_name() {
-                                drives.push(DriveInfo {
-                                    path: path.to_string_lossy().to_string(),
-                                    name: format!("Volume {}", name.to_string_lossy()),
-                                });
-                            }
+                    // Time Machine and hidden system mounts aren't user volumes
+                    if is_macos_backup_or_trash(&entry.path())
+                        || entry.file_name().to_string_lossy().starts_with('.')
+                    {
+                        continue;
+                    }
+
+                    if is_dir_entry(&entry) {
+                        let path = entry.path();
+                        if let Some(name) = path.file_name() {
+                            drives.push(DriveInfo::new(
+                                path.to_string_lossy().to_string(),
+                                format!("Volume {}", name.to_string_lossy()),
+                            ));
                         }
                     }
                 }
@@ -91,118 +363,603 @@ async fn list_drives() -> Result<Vec<DriveInfo>, String> {
             for mount_point in &["/media", "/mnt"] {
                 if let Ok(entries) = fs::read_dir(mount_point) {
                     for entry in entries.flatten() {
-                        if let Ok(metadata) = entry.metadata() {
-                            if metadata.is_dir() {
-                                let path = entry.path();
-                                if let Some(name) = path.file_name() {
-                                    drives.push(DriveInfo {
-                                        path: path.to_string_lossy().to_string(),
-                                        name: format!("Mount {}", name.to_string_lossy()),
-                                    });
-                                }
+                        if is_dir_entry(&entry) {
+                            let path = entry.path();
+                            if let Some(name) = path.file_name() {
+                                drives.push(DriveInfo::new(
+                                    path.to_string_lossy().to_string(),
+                                    format!("Mount {}", name.to_string_lossy()),
+                                ));
                             }
                         }
                     }
                 }
             }
+
+            // Desktop automounts land one level deeper, e.g. /media/<user>/<label>
+            // or /run/media/<user>/<label>
+            for mount_point in snapshots::mount_points() {
+                let is_automount = ["/media/", "/run/media/"]
+                    .iter()
+                    .any(|prefix| mount_point.starts_with(prefix));
+                if is_automount && !drives.iter().any(|drive| drive.path == mount_point) {
+                    if let Some(name) = Path::new(&mount_point).file_name() {
+                        drives.push(DriveInfo::new(
+                            mount_point.clone(),
+                            format!("Mount {}", name.to_string_lossy()),
+                        ));
+                    }
+                }
+            }
         }
 
-        Ok(drives)
+        drives
+    }
+}
+
+// Options passed with the request win over the saved scan settings
+fn scan_options(
+    options: Option<scan_settings::ScanOptions>,
+    settings: &scan_settings::ScanSettings,
+) -> Result<
+    (
+        Arc<scan_settings::ScanOptions>,
+        Arc<scan_patterns::ScanPatterns>,
+    ),
+    String,
+> {
+    let options = options.unwrap_or_else(|| settings.current());
+    options.validate()?;
+    let patterns =
+        scan_patterns::ScanPatterns::compile(&options.include_patterns, &options.exclude_patterns)?;
+    Ok((Arc::new(options), Arc::new(patterns)))
+}
+
+fn size_budget(seconds: Option<u64>) -> Result<Duration, String> {
+    match seconds {
+        Some(0) => Err("The size calculation time budget must be at least 1 second".to_string()),
+        Some(seconds) => Ok(Duration::from_secs(seconds)),
+        None => Ok(sizing::DEFAULT_SIZE_BUDGET),
     }
 }
 
+// Lets the UI flag bad patterns before a scan starts
+#[tauri::command]
+async fn validate_scan_patterns(
+    include: Vec<String>,
+    exclude: Vec<String>,
+) -> Result<Vec<scan_patterns::PatternError>, Error> {
+    Ok(scan_patterns::check(&include, &exclude))
+}
+
+#[tauri::command]
+async fn check_snapshot_status(
+    roots: Vec<String>,
+) -> Result<Vec<snapshots::SnapshotWarning>, Error> {
+    task::spawn_blocking(move || {
+        roots
+            .iter()
+            .filter_map(|root| snapshots::snapshot_warning(root))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Snapshot check failed: {}", e).into())
+}
+
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
-async fn start_scan(roots: Vec<String>, include_sizes: bool) -> Result<Vec<ScanItem>, String> {
-    // Start the scan with progress tracking
-    let scan_result = scan_directory_with_progressive_progress(&roots, include_sizes, None).await;
+async fn start_scan(
+    app: tauri::AppHandle,
+    roots: Vec<String>,
+    include_sizes: bool,
+    size_tiers: Option<sizing::SizeTierThresholds>,
+    size_budget_secs: Option<u64>,
+    options: Option<scan_settings::ScanOptions>,
+    min_age_days: Option<u64>,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    settings: tauri::State<'_, scan_settings::ScanSettings>,
+    suspended: tauri::State<'_, SuspendedScans>,
+) -> Result<ScanResults, Error> {
+    let size_tiers = size_tiers.unwrap_or_default();
+    size_tiers.validate()?;
+    let size_budget = size_budget(size_budget_secs)?;
+    let (options, patterns) = scan_options(options, &settings)?;
+    let config = ScanConfig {
+        include_sizes,
+        size_tiers,
+        max_results: None,
+        size_budget,
+        options,
+        patterns,
+        min_age_days,
+    };
+
+    forget_hidden_items(&app);
+    scan_without_window(&app, &roots, config, &jobs, &metrics, &suspended)
+        .await
+        .map_err(Error::from)
+}
+
+// Config for scans the app starts by itself, from the saved scan settings
+fn saved_scan_config(app: &tauri::AppHandle, include_sizes: bool) -> Result<ScanConfig, String> {
+    let (options, patterns) = scan_options(None, &app.state::<scan_settings::ScanSettings>())?;
+    Ok(ScanConfig {
+        include_sizes,
+        size_tiers: sizing::SizeTierThresholds::default(),
+        max_results: None,
+        size_budget: sizing::DEFAULT_SIZE_BUDGET,
+        options,
+        patterns,
+        min_age_days: None,
+    })
+}
+
+async fn run_scheduled_scan(
+    app: &tauri::AppHandle,
+    roots: &[String],
+    include_sizes: bool,
+) -> Result<ScanResults, String> {
+    scan_without_window(
+        app,
+        roots,
+        saved_scan_config(app, include_sizes)?,
+        &app.state::<jobs::JobRegistry>(),
+        &app.state::<metrics::Metrics>(),
+        &app.state::<SuspendedScans>(),
+    )
+    .await
+}
+
+// Streams progress to the window like a scan started from the UI
+async fn run_launch_scan(
+    window: &tauri::Window,
+    roots: &[String],
+    include_sizes: bool,
+) -> Result<ScanResults, String> {
+    let app = window.app_handle();
+    let session = Arc::new(new_scan_session(app, new_session_id(), roots));
+    scan_with_progress(
+        session,
+        roots,
+        saved_scan_config(app, include_sizes)?,
+        window,
+        &app.state::<jobs::JobRegistry>(),
+        &app.state::<metrics::Metrics>(),
+        &app.state::<SuspendedScans>(),
+    )
+    .await
+}
+
+async fn scan_without_window(
+    app: &tauri::AppHandle,
+    roots: &[String],
+    config: ScanConfig,
+    jobs: &jobs::JobRegistry,
+    metrics: &metrics::Metrics,
+    suspended: &SuspendedScans,
+) -> Result<ScanResults, String> {
+    let job = jobs.start(jobs::JobKind::Scan);
+    let started_at = SystemTime::now();
+    let start_time = Instant::now();
+    let session = Arc::new(new_scan_session(app, new_session_id(), roots));
+    let _active = register_scan(app, &session);
+
+    let scan_result = scan::start(session, roots, &config, &AppScanHost::new(None), &job).await;
 
     match scan_result {
-        Ok(items) => Ok(items),
+        Ok(outcome) => {
+            record_scan_metrics(metrics, started_at, start_time.elapsed(), &outcome);
+            cache_scan(app, roots, &outcome);
+            record_scan_total(app, &outcome);
+            if outcome.paused && !outcome.cancelled {
+                if let Ok(mut suspended) = suspended.0.lock() {
+                    suspended.insert(
+                        outcome.session.id.clone(),
+                        SuspendedScan {
+                            config,
+                            session: outcome.session.clone(),
+                        },
+                    );
+                }
+            }
+            let finished = !outcome.paused && !outcome.truncated;
+            let mut items = outcome.items;
+            finish_items(app, &mut items);
+            // Nobody is watching these, so this is often the only sign one ran
+            if finished {
+                notifications::scan_finished(app, &items, outcome.cancelled);
+            }
+            let summary = outcome.session.summary(reclaimable_bytes(&items));
+            Ok(ScanResults { items, summary })
+        }
         Err(e) => Err(format!("Scan failed: {}", e)),
     }
 }
 
+// Tauri injects state and the window as arguments, so commands grow long
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
 async fn start_scan_with_progress(
     roots: Vec<String>,
     include_sizes: bool,
+    size_tiers: Option<sizing::SizeTierThresholds>,
+    max_results: Option<usize>,
+    size_budget_secs: Option<u64>,
+    options: Option<scan_settings::ScanOptions>,
+    min_age_days: Option<u64>,
+    session_id: Option<String>,
     window: tauri::Window,
-) -> Result<Vec<ScanItem>, String> {
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    active: tauri::State<'_, ActiveScans>,
+    suspended: tauri::State<'_, SuspendedScans>,
+    settings: tauri::State<'_, scan_settings::ScanSettings>,
+) -> Result<ScanResults, Error> {
+    let size_tiers = size_tiers.unwrap_or_default();
+    size_tiers.validate()?;
+    let size_budget = size_budget(size_budget_secs)?;
+    if max_results == Some(0) {
+        return Err(Error::InvalidInput(
+            "The result limit must be at least 1".to_string(),
+        ));
+    }
+
+    let (options, patterns) = scan_options(options, &settings)?;
+    let config = ScanConfig {
+        include_sizes,
+        size_tiers,
+        max_results,
+        size_budget,
+        options,
+        patterns,
+        min_age_days,
+    };
+    let session_id = claim_session_id(session_id, &active, &suspended)?;
+    let session = Arc::new(new_scan_session(window.app_handle(), session_id, &roots));
+    launch_scan::remember_roots(window.app_handle(), &roots, include_sizes);
+
+    scan_with_progress(
+        session, &roots, config, &window, &jobs, &metrics, &suspended,
+    )
+    .await
+    .map_err(Error::from)
+}
+
+// Like start_scan_with_progress, but folders unchanged since the cached scan
+// aren't listed again and unchanged node_modules keep their cached size
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn rescan_incremental(
+    roots: Vec<String>,
+    include_sizes: bool,
+    size_tiers: Option<sizing::SizeTierThresholds>,
+    size_budget_secs: Option<u64>,
+    options: Option<scan_settings::ScanOptions>,
+    min_age_days: Option<u64>,
+    session_id: Option<String>,
+    window: tauri::Window,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    active: tauri::State<'_, ActiveScans>,
+    suspended: tauri::State<'_, SuspendedScans>,
+    cache: tauri::State<'_, scan_cache::ScanCache>,
+    settings: tauri::State<'_, scan_settings::ScanSettings>,
+) -> Result<ScanResults, Error> {
+    let size_tiers = size_tiers.unwrap_or_default();
+    size_tiers.validate()?;
+    let (options, patterns) = scan_options(options, &settings)?;
+    let config = ScanConfig {
+        include_sizes,
+        size_tiers,
+        max_results: None,
+        size_budget: size_budget(size_budget_secs)?,
+        options,
+        patterns,
+        min_age_days,
+    };
+    let session_id = claim_session_id(session_id, &active, &suspended)?;
+    let session = Arc::new(
+        new_scan_session(window.app_handle(), session_id, &roots).with_previous(cache.snapshot()),
+    );
+
+    scan_with_progress(
+        session, &roots, config, &window, &jobs, &metrics, &suspended,
+    )
+    .await
+    .map_err(Error::from)
+}
+
+async fn scan_with_progress(
+    session: Arc<ScanSession>,
+    roots: &[String],
+    config: ScanConfig,
+    window: &tauri::Window,
+    jobs: &jobs::JobRegistry,
+    metrics: &metrics::Metrics,
+    suspended: &SuspendedScans,
+) -> Result<ScanResults, String> {
+    let job = jobs.start(jobs::JobKind::Scan);
+    let started_at = SystemTime::now();
+    let start_time = Instant::now();
+    let _active = register_scan(window.app_handle(), &session);
+    forget_hidden_items(window.app_handle());
+
+    let started = ScanStarted {
+        session_id: session.id.clone(),
+        roots: roots.to_vec(),
+    };
+    if let Err(e) = window.emit("scan_started", started) {
+        eprintln!("Failed to emit scan start: {}", e);
+    }
+
     // Emit initial progress update
     let initial_progress = ScanProgress {
+        session_id: session.id.clone(),
         current_folder: "Starting scan...".to_string(),
         folders_scanned: 0,
         total_folders_estimated: 0,
         node_modules_found: 0,
         directories_skipped: 0,
         is_complete: false,
+        truncated: false,
+        cancelled: false,
+        paused: false,
+        eta_seconds: None,
+        summary: None,
     };
 
     if let Err(e) = window.emit("scan_progress", initial_progress) {
         eprintln!("Failed to emit initial progress: {}", e);
     }
 
+    // Tell the user up front why a root will be slow or come back empty
+    let unhealthy: Vec<root_health::RootHealth> = root_health::check_roots(roots)
+        .await
+        .into_iter()
+        .filter(|health| !health.warnings.is_empty())
+        .collect();
+    if !unhealthy.is_empty() {
+        let warnings = ScanRootWarnings {
+            session_id: session.id.clone(),
+            roots: unhealthy,
+        };
+        if let Err(e) = window.emit("scan_root_warnings", warnings) {
+            eprintln!("Failed to emit root warnings: {}", e);
+        }
+    }
+
     // Start the scan with progressive estimation
-    let scan_result =
-        scan_directory_with_progressive_progress(&roots, include_sizes, Some(&window)).await;
+    let scan_result = scan::start(
+        session,
+        roots,
+        &config,
+        &AppScanHost::new(Some(window)),
+        &job,
+    )
+    .await;
 
     match scan_result {
-        Ok(items) => {
+        Ok(outcome) => {
+            record_scan_metrics(metrics, started_at, start_time.elapsed(), &outcome);
+            cache_scan(window.app_handle(), roots, &outcome);
+            record_scan_total(window.app_handle(), &outcome);
+
             // Send final progress update
-            let final_progress = ScanProgress {
-                current_folder: "Scan completed".to_string(),
-                folders_scanned: items.len(), // Use actual scanned count
-                total_folders_estimated: items.len(), // Use actual count
-                node_modules_found: items.len(),
-                directories_skipped: 0, // Will be updated in the scan
-                is_complete: true,
-            };
+            Ok(complete_scan(window, suspended, config, outcome))
+        }
+        Err(e) => Err(format!("Scan failed: {}", e)),
+    }
+}
 
-            if let Err(e) = window.emit("scan_progress", final_progress) {
-                eprintln!("Failed to emit final progress: {}", e);
-            }
+#[tauri::command]
+async fn continue_scan(
+    session_id: String,
+    window: tauri::Window,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    suspended: tauri::State<'_, SuspendedScans>,
+) -> Result<ScanResults, Error> {
+    resume_suspended(&session_id, &window, &jobs, &metrics, &suspended)
+        .await
+        .map_err(Error::from)
+}
 
-            Ok(items)
+// Stops a running scan at the next directory boundary and keeps its pending
+// directories, freeing up disk I/O until resume_scan picks it up again. The
+// scan's own command returns what it found so far.
+#[tauri::command]
+async fn pause_scan(
+    session_id: String,
+    active: tauri::State<'_, ActiveScans>,
+) -> Result<(), Error> {
+    let active = active
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read running scans: {}", e))?;
+    let session = active
+        .get(&session_id)
+        .ok_or_else(|| "No running scan with this id".to_string())?;
+
+    session.pause();
+    Ok(())
+}
+
+#[tauri::command]
+async fn resume_scan(
+    session_id: String,
+    window: tauri::Window,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    suspended: tauri::State<'_, SuspendedScans>,
+) -> Result<ScanResults, Error> {
+    resume_suspended(&session_id, &window, &jobs, &metrics, &suspended)
+        .await
+        .map_err(Error::from)
+}
+
+async fn resume_suspended(
+    session_id: &str,
+    window: &tauri::Window,
+    jobs: &jobs::JobRegistry,
+    metrics: &metrics::Metrics,
+    suspended: &SuspendedScans,
+) -> Result<ScanResults, String> {
+    let SuspendedScan { config, session } = suspended
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read paused scans: {}", e))?
+        .remove(session_id)
+        .ok_or_else(|| "No paused scan with this id".to_string())?;
+
+    let job = jobs.start(jobs::JobKind::Scan);
+    let started_at = SystemTime::now();
+    let start_time = Instant::now();
+    let _active = register_scan(window.app_handle(), &session);
+
+    match scan::run(session, &config, &AppScanHost::new(Some(window)), &job).await {
+        Ok(outcome) => {
+            record_scan_metrics(metrics, started_at, start_time.elapsed(), &outcome);
+            // Its roots were only partly walked before the pause, so nothing
+            // cached for them is dropped
+            cache_scan(window.app_handle(), &[], &outcome);
+            record_scan_total(window.app_handle(), &outcome);
+            Ok(complete_scan(window, suspended, config, outcome))
         }
         Err(e) => Err(format!("Scan failed: {}", e)),
     }
 }
 
+// Cancels one scan, or every running scan when no id is given. A paused
+// scan is discarded. Returns how many scans were stopped.
 #[tauri::command]
-async fn open_folder_dialog(app: tauri::AppHandle) -> Result<Option<String>, String> {
-    use std::sync::{Arc, Mutex};
-    use std::time::Duration;
-    use tauri_plugin_dialog::DialogExt;
-    use tokio::time::sleep;
-
-    let result = Arc::new(Mutex::new(None::<String>));
-    let result_clone = result.clone();
-
-    app.dialog().file().pick_folder(move |path| {
-        if let Some(path) = path {
-            let path_str = path.to_string();
-            if let Ok(mut result) = result_clone.lock() {
-                *result = Some(path_str);
-            }
-        }
-    });
-
-    // Wait for the dialog result with polling
-    for _ in 0..300 {
-        // 30 seconds timeout (300 * 100ms)
-        if let Ok(result_guard) = result.lock() {
-            if result_guard.is_some() {
-                return Ok(result_guard.clone());
-            }
+async fn cancel_scan(
+    session_id: Option<String>,
+    active: tauri::State<'_, ActiveScans>,
+    suspended: tauri::State<'_, SuspendedScans>,
+) -> Result<usize, Error> {
+    let mut cancelled = 0;
+
+    let active = active
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read running scans: {}", e))?;
+    for (id, session) in active.iter() {
+        if session_id
+            .as_ref()
+            .is_none_or(|session_id| session_id == id)
+        {
+            session.cancel.cancel();
+            cancelled += 1;
         }
-        sleep(Duration::from_millis(100)).await;
     }
 
-    Ok(None) // Timeout or cancelled
+    let mut suspended = suspended
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read paused scans: {}", e))?;
+    match &session_id {
+        Some(id) => cancelled += suspended.remove(id).is_some() as usize,
+        None => cancelled += suspended.drain().count(),
+    }
+
+    Ok(cancelled)
+}
+
+fn new_session_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+// Lets the UI pick the id up front so it can tell concurrent scans' events
+// apart before the command returns
+fn claim_session_id(
+    requested: Option<String>,
+    active: &ActiveScans,
+    suspended: &SuspendedScans,
+) -> Result<String, String> {
+    let Some(id) = requested else {
+        return Ok(new_session_id());
+    };
+    let id = uuid::Uuid::parse_str(&id)
+        .map_err(|e| format!("Invalid scan id: {}", e))?
+        .to_string();
+
+    let running = active
+        .0
+        .lock()
+        .map(|active| active.contains_key(&id))
+        .unwrap_or(false);
+    let parked = suspended
+        .0
+        .lock()
+        .map(|suspended| suspended.contains_key(&id))
+        .unwrap_or(false);
+    if running || parked {
+        return Err("A scan with this id already exists".to_string());
+    }
+    Ok(id)
+}
+
+fn scan_info(session: &ScanSession, state: ScanState) -> ScanInfo {
+    ScanInfo {
+        session_id: session.id.clone(),
+        roots: session.roots.clone(),
+        started_at: chrono::DateTime::<chrono::Utc>::from(session.started_at).to_rfc3339(),
+        state,
+        folders_scanned: session.folders_scanned(),
+        node_modules_found: session.node_modules_found(),
+    }
+}
+
+// Running and paused scans, oldest first
+#[tauri::command]
+async fn list_scans(
+    active: tauri::State<'_, ActiveScans>,
+    suspended: tauri::State<'_, SuspendedScans>,
+) -> Result<Vec<ScanInfo>, Error> {
+    let mut scans: Vec<(SystemTime, ScanInfo)> = Vec::new();
+
+    let active = active
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read running scans: {}", e))?;
+    for session in active.values() {
+        scans.push((session.started_at, scan_info(session, ScanState::Running)));
+    }
+
+    let suspended = suspended
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read paused scans: {}", e))?;
+    for scan in suspended.values() {
+        scans.push((
+            scan.session.started_at,
+            scan_info(&scan.session, ScanState::Paused),
+        ));
+    }
+
+    scans.sort_by_key(|(started_at, _)| *started_at);
+    Ok(scans.into_iter().map(|(_, info)| info).collect())
 }
 
+// Stops every scan, size calculation, safety check and queued deletion at
+// once. Paused scans are discarded too. Returns how many jobs were running.
 #[tauri::command]
-async fn open_folder_in_explorer(path: String) -> Result<(), String> {
+async fn stop_all_work(
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    suspended: tauri::State<'_, SuspendedScans>,
+) -> Result<usize, Error> {
+    let stopped = jobs.stop_all();
+    suspended
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read paused scans: {}", e))?
+        .clear();
+    Ok(stopped)
+}
+
+#[tauri::command]
+async fn open_folder_in_explorer(path: String) -> Result<(), Error> {
     use std::process::Command;
 
     #[cfg(target_os = "windows")]
@@ -244,552 +1001,1049 @@ async fn open_folder_in_explorer(path: String) -> Result<(), String> {
         }
 
         if !opened {
-            return Err("No suitable file manager found".to_string());
+            return Err(Error::Unsupported(
+                "No suitable file manager found".to_string(),
+            ));
         }
     }
 
     Ok(())
 }
 
+// Deletes what a prepare_delete call listed; `token` is the one it returned
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
-async fn delete_node_modules(paths: Vec<String>) -> Result<Vec<DeleteResult>, String> {
+async fn execute_delete(
+    app: tauri::AppHandle,
+    token: String,
+    delete_mode: Option<delete_backend::DeleteMode>,
+    confirm_permanent: Option<bool>,
+    // Opt-in: remove permanently what the trash refuses
+    permanent_fallback: Option<bool>,
+    // Opt-in: clear read-only, hidden and system attributes first (Windows)
+    clear_read_only: Option<bool>,
+    // Opt-in: purge permanent deletes with robocopy (Windows)
+    fast_remove: Option<bool>,
+    // Also delete folders the scan rated high risk
+    confirm_high_risk: Option<bool>,
+    // Opt-in: deletes running at once on each drive, instead of one per
+    // spinning disk and a few per SSD; falls back to the saved setting
+    parallelism: Option<usize>,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    backend: tauri::State<'_, delete_backend::DeleteBackendSettings>,
+    safety_checks: tauri::State<'_, legitimacy::SafetyCheckSettings>,
+    pending: tauri::State<'_, delete_tokens::PendingDeletes>,
+) -> Result<DeleteBatch, Error> {
+    let requested = pending.redeem(&token)?;
+    let nested = nested_installs::check_selection(&requested);
+    let paths: Vec<String> = requested
+        .iter()
+        .filter(|path| !nested.iter().any(|selection| selection.path == **path))
+        .cloned()
+        .collect();
+    let backend = delete_mode
+        .unwrap_or_default()
+        .backend(backend.selected(), confirm_permanent.unwrap_or(false))?;
+    let options = delete_backend::RemoveOptions {
+        permanent_fallback: permanent_fallback.unwrap_or(false),
+        clear_read_only: clear_read_only.unwrap_or(false),
+        fast_remove: fast_remove.unwrap_or(false),
+        ..Default::default()
+    };
+    let parallelism = parallelism
+        .map(|parallelism| parallelism.clamp(1, MAX_DELETE_PARALLELISM))
+        .or_else(|| settings::delete_parallelism(&app));
+    let confirm_high_risk = confirm_high_risk.unwrap_or(false);
+    let job = jobs.start(jobs::JobKind::Delete);
+    let run = DeleteRun::start();
     let mut results: Vec<DeleteResult> = Vec::new();
 
-    for path in paths {
-        let result = delete_single_node_modules(&path).await;
-        results.push(result);
+    // Verify the whole selection up front instead of one item at a time
+    let verdicts =
+        legitimacy::check_batch(&paths, &safety_checks.current(), job.cancellation()).await;
+
+    // A spinning disk only seeks back and forth between parallel deletes, so
+    // each gets them one at a time; an SSD overlaps a few well. Drives don't
+    // share a limit, so separate disks always work in parallel.
+    let lookup = paths.clone();
+    let drives = task::spawn_blocking(move || {
+        lookup
+            .iter()
+            .map(|path| {
+                let drive = planner::physical_drive_of(Path::new(path));
+                let limit = parallelism.unwrap_or(if planner::is_solid_state(&drive) {
+                    SSD_DELETE_PARALLELISM
+                } else {
+                    1
+                });
+                (drive, limit)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Failed to group deletions by drive: {}", e))?;
+
+    let risks = risks_of(&app, &paths).await;
+
+    let mut per_drive: HashMap<String, Arc<tokio::sync::Semaphore>> = HashMap::new();
+    let mut deletes = task::JoinSet::new();
+    for (index, (((path, is_legitimate), (drive, limit)), risk)) in paths
+        .iter()
+        .zip(verdicts)
+        .zip(drives)
+        .zip(risks)
+        .enumerate()
+    {
+        let limit = per_drive
+            .entry(drive)
+            .or_insert_with(|| Arc::new(tokio::sync::Semaphore::new(limit)))
+            .clone();
+        let app = app.clone();
+        let job = (*job).clone();
+        let path = path.clone();
+        let high_risk =
+            risk.filter(|risk| risk.level == risk::RiskLevel::High && !confirm_high_risk);
+        deletes.spawn(async move {
+            if let Some(risk) = high_risk {
+                let reasons: Vec<&str> = risk
+                    .signals
+                    .iter()
+                    .filter(|signal| signal.weight > 0)
+                    .map(|signal| signal.detail.as_str())
+                    .collect();
+                let reason = format!(
+                    "Rated high risk ({}); confirm to delete it anyway",
+                    reasons.join("; ")
+                );
+                return (index, DeleteResult::needs_override(&path, reason));
+            }
+            let Ok(_permit) = limit.acquire_owned().await else {
+                return (index, DeleteResult::skipped(&path));
+            };
+            // Deletes already under way are finished; queued ones are not started
+            if job.cancellation().is_cancelled() {
+                return (index, DeleteResult::skipped(&path));
+            }
+            job.progress(Path::new(&path));
+            let result =
+                delete_single_node_modules(&app, backend, options, &path, is_legitimate).await;
+            (index, result)
+        });
     }
 
-    Ok(results)
-}
-
-#[tauri::command]
-async fn test_trash_functionality(path: String) -> Result<String, String> {
-    let path_buf = PathBuf::from(&path);
-
-    if !path_buf.exists() {
-        return Err("Path does not exist".to_string());
+    let total = paths.len();
+    let mut ordered: Vec<Option<DeleteResult>> = paths.iter().map(|_| None).collect();
+    let mut completed = 0;
+    while let Some(done) = deletes.join_next().await {
+        let (index, result) = done.map_err(|e| format!("Failed to delete: {}", e))?;
+        completed += 1;
+        let event = DeleteItemCompleted {
+            result: result.clone(),
+            completed,
+            total,
+        };
+        if let Err(e) = app.emit("delete_item_completed", event) {
+            eprintln!("Failed to emit delete progress: {}", e);
+        }
+        ordered[index] = Some(result);
     }
+    results.extend(ordered.into_iter().flatten());
+    run.finish(&app, &metrics, &pending, &results);
 
-    match trash::delete(&path_buf) {
-        Ok(_) => Ok("Successfully moved to trash".to_string()),
-        Err(e) => Err(format!("Failed to move to trash: {}", e)),
+    if nested.is_empty() {
+        return Ok(DeleteBatch::new(results));
     }
+    let results = with_nested_results(&requested, &nested, results);
+    Ok(DeleteBatch {
+        nested,
+        ..DeleteBatch::new(results)
+    })
 }
 
-async fn calculate_directory_size(path: &Path) -> Option<u64> {
-    let path = path.to_path_buf();
-
-    // Run size calculation in a blocking thread pool to avoid blocking async runtime
-    task::spawn_blocking(move || {
-        let start_time = Instant::now();
-        let max_duration = Duration::from_secs(30); // Cap time for size calculation
-        let max_depth = 10; // Cap depth for size calculation
-
-        let mut total_size = 0u64;
-        let mut stack = vec![(path, 0)]; // (path, depth)
-        let mut processed_paths = 0;
-
-        while let Some((current_path, depth)) = stack.pop() {
-            // Check time limit
-            if start_time.elapsed() > max_duration {
-                eprintln!("Size calculation timed out for: {}", current_path.display());
-                return None;
-            }
-
-            // Check depth limit
-            if depth > max_depth {
-                continue;
-            }
-
-            // Reject symlinks/junctions
-            if let Ok(metadata) = fs::symlink_metadata(&current_path) {
-                if metadata.file_type().is_symlink() {
-                    continue;
-                }
-            }
+// When a delete command started, for what it records once it's done
+struct DeleteRun {
+    started_at: SystemTime,
+    start_time: Instant,
+    // Trash entries from before this are not part of the delete
+    trash_cutoff: i64,
+}
 
-            if let Ok(entries) = fs::read_dir(&current_path) {
-                for entry in entries.flatten() {
-                    let entry_path = entry.path();
+impl DeleteRun {
+    fn start() -> Self {
+        DeleteRun {
+            started_at: SystemTime::now(),
+            start_time: Instant::now(),
+            trash_cutoff: chrono::Utc::now().timestamp(),
+        }
+    }
 
-                    // Reject symlinks/junctions
-                    if let Ok(metadata) = fs::symlink_metadata(&entry_path) {
-                        if metadata.file_type().is_symlink() {
-                            continue;
-                        }
-                    }
+    // What every delete command does with its results: metrics, the undo
+    // record, permission failures' elevated retry and the notification
+    fn finish(
+        &self,
+        app: &tauri::AppHandle,
+        metrics: &metrics::Metrics,
+        pending: &delete_tokens::PendingDeletes,
+        results: &[DeleteResult],
+    ) {
+        let failures = results.iter().filter(|result| !result.success).count() as u64;
+        metrics.record(
+            jobs::JobKind::Delete,
+            self.started_at,
+            self.start_time.elapsed(),
+            0,
+            results.len() as u64 - failures,
+            failures,
+        );
+        undo::record(app, results, self.trash_cutoff);
+        pending.allow_elevation(results);
+        notifications::delete_finished(app, results);
+    }
+}
 
-                    if let Ok(metadata) = entry.metadata() {
-                        if metadata.is_file() {
-                            total_size += metadata.len();
-                        } else if metadata.is_dir() {
-                            stack.push((entry_path, depth + 1));
-                        }
-                    }
-                }
+// Results in the order the paths were requested, with one for each nested
+// pick: the parent's outcome when the parent was deleted too, a refusal
+// otherwise. Sizes stay with the parent so nothing is counted twice.
+fn with_nested_results(
+    requested: &[String],
+    nested: &[nested_installs::NestedSelection],
+    results: Vec<DeleteResult>,
+) -> Vec<DeleteResult> {
+    let mut by_path: HashMap<String, DeleteResult> = results
+        .into_iter()
+        .map(|result| (result.path.clone(), result))
+        .collect();
+    let parents: HashMap<&str, DeleteResult> = nested
+        .iter()
+        .filter(|selection| selection.parent_selected)
+        .filter_map(|selection| {
+            Some((
+                selection.parent.as_str(),
+                by_path.get(&selection.parent)?.clone(),
+            ))
+        })
+        .collect();
+
+    requested
+        .iter()
+        .filter_map(|path| {
+            if let Some(result) = by_path.remove(path) {
+                return Some(result);
             }
+            let selection = nested.iter().find(|selection| selection.path == *path)?;
+            Some(match parents.get(selection.parent.as_str()) {
+                Some(parent) => DeleteResult {
+                    path: path.clone(),
+                    bytes_freed: None,
+                    files_removed: None,
+                    ..parent.clone()
+                },
+                None => DeleteResult::failed(
+                    path,
+                    format!(
+                        "Nested inside {}; select that node_modules to delete both",
+                        selection.parent
+                    ),
+                ),
+            })
+        })
+        .collect()
+}
 
-            processed_paths += 1;
-
-            // Yield control periodically to keep UI responsive
-            if processed_paths % 1000 == 0 {
-                std::thread::sleep(Duration::from_millis(1));
-            }
+// Windows won't move a folder while any file in it is open, which is also
+// what makes deletes fail halfway. Moving each folder aside and straight
+// back finds those up front.
+#[cfg(windows)]
+fn find_locked(paths: &[String]) -> Option<String> {
+    paths.iter().find_map(|path| {
+        let path = long_paths::extended(Path::new(path));
+        let mut probe = path.clone().into_os_string();
+        probe.push(".nmcleaner-probe");
+        match fs::rename(&path, &probe) {
+            Ok(()) => fs::rename(&probe, &path)
+                .err()
+                .map(|e| format!("Failed to move {} back: {}", path.display(), e)),
+            Err(e) => Some(format!("{} is in use: {}", path.display(), e)),
         }
-
-        Some(total_size)
     })
-    .await
-    .ok()
-    .flatten()
 }
 
-async fn delete_single_node_modules(path: &str) -> DeleteResult {
-    let path_buf = PathBuf::from(path);
+// Deletes every node_modules of one workspace or none of them: nothing is
+// removed until all folders have passed their checks. `token` is from a
+// prepare_delete call for these same paths.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn delete_workspace_group(
+    app: tauri::AppHandle,
+    workspace_root: String,
+    paths: Vec<String>,
+    token: String,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    backend: tauri::State<'_, delete_backend::DeleteBackendSettings>,
+    safety_checks: tauri::State<'_, legitimacy::SafetyCheckSettings>,
+    pending: tauri::State<'_, delete_tokens::PendingDeletes>,
+) -> Result<DeleteBatch, Error> {
+    pending.redeem_for(&token, &paths)?;
+    let root = PathBuf::from(&workspace_root);
+    let lookup = paths.clone();
+    let roots = task::spawn_blocking(move || {
+        lookup
+            .iter()
+            .map(|path| {
+                Path::new(path)
+                    .parent()
+                    .and_then(workspaces::workspace_root)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Failed to resolve workspaces: {}", e))?;
+    if let Some((path, _)) = paths
+        .iter()
+        .zip(&roots)
+        .find(|(_, found)| found.as_deref() != Some(root.as_path()))
+    {
+        return Err(Error::InvalidInput(format!(
+            "{} is not part of the workspace at {}",
+            path, workspace_root
+        )));
+    }
 
-    // Enhanced safety checks
-    if !path_buf.exists() {
-        return DeleteResult {
-            path: path.to_string(),
-            success: false,
-            error: Some("Path does not exist".to_string()),
-        };
+    let backend = backend.selected();
+    let job = jobs.start(jobs::JobKind::Delete);
+    let run = DeleteRun::start();
+
+    let verdicts =
+        legitimacy::check_batch(&paths, &safety_checks.current(), job.cancellation()).await;
+    let protected = protected_projects::rules(&app);
+    let mut blocked: HashMap<String, DeleteResult> = paths
+        .iter()
+        .zip(&verdicts)
+        .filter_map(|(path, is_legitimate)| {
+            check_deletable(path, *is_legitimate, &protected).map(|failed| (path.clone(), failed))
+        })
+        .collect();
+
+    #[cfg(windows)]
+    if blocked.is_empty() {
+        let probe = paths.clone();
+        let locked = task::spawn_blocking(move || find_locked(&probe))
+            .await
+            .map_err(|e| format!("Failed to check for open files: {}", e))?;
+        if let Some(locked) = locked {
+            return Err(Error::Locked(locked));
+        }
     }
 
-    if !path_buf.is_dir() {
-        return DeleteResult {
-            path: path.to_string(),
-            success: false,
-            error: Some("Path is not a directory".to_string()),
-        };
+    if !blocked.is_empty() {
+        return Ok(DeleteBatch::new(
+            paths
+                .iter()
+                .map(|path| {
+                    blocked.remove(path).unwrap_or_else(|| {
+                        DeleteResult::failed(
+                            path,
+                            "Not deleted: other folders in this workspace failed their checks",
+                        )
+                    })
+                })
+                .collect(),
+        ));
     }
 
-    // Reject symlinks/junctions
-    if let Ok(metadata) = fs::symlink_metadata(&path_buf) {
-        if metadata.file_type().is_symlink() {
-            return DeleteResult {
-                path: path.to_string(),
-                success: false,
-                error: Some("Cannot delete symlinks/junctions".to_string()),
-            };
+    let mut results = Vec::with_capacity(paths.len());
+    for path in &paths {
+        if job.cancellation().is_cancelled() {
+            results.push(DeleteResult::skipped(path));
+            continue;
         }
+        job.progress(Path::new(path));
+        results.push(
+            delete_single_node_modules(
+                &app,
+                backend,
+                delete_backend::RemoveOptions::default(),
+                path,
+                true,
+            )
+            .await,
+        );
     }
 
-    // CRITICAL SAFETY CHECK: Ensure it's actually a node_modules directory
-    if path_buf.file_name() != Some(std::ffi::OsStr::new("node_modules")) {
-        return DeleteResult {
-            path: path.to_string(),
-            success: false,
-            error: Some("Path does not end with 'node_modules'".to_string()),
-        };
+    run.finish(&app, &metrics, &pending, &results);
+
+    Ok(DeleteBatch::new(results))
+}
+
+// `token` is from a prepare_delete call for just this path
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn delete_with_override(
+    app: tauri::AppHandle,
+    path: String,
+    reason: String,
+    token: String,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+    backend: tauri::State<'_, delete_backend::DeleteBackendSettings>,
+    pending: tauri::State<'_, delete_tokens::PendingDeletes>,
+) -> Result<DeleteResult, Error> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(Error::InvalidInput(
+            "A reason is required to override the safety check".to_string(),
+        ));
     }
+    pending.redeem_for(&token, std::slice::from_ref(&path))?;
+    let job = jobs.start(jobs::JobKind::Delete);
+    let run = DeleteRun::start();
+    job.progress(Path::new(&path));
+
+    // Every other safety check still applies, only the legitimacy verdict is waived
+    let result = delete_single_node_modules(
+        &app,
+        backend.selected(),
+        delete_backend::RemoveOptions::default(),
+        &path,
+        true,
+    )
+    .await;
+    audit::record(
+        &app,
+        audit::AuditEntry::legitimacy_override(&path, reason, result.success),
+    );
+    run.finish(&app, &metrics, &pending, std::slice::from_ref(&result));
+
+    Ok(result)
+}
 
-    // Additional safety: Check if this is a legitimate node_modules directory
-    let is_legitimate = is_legitimate_node_modules(&path_buf).await;
-    if !is_legitimate {
-        println!("Legitimacy check failed for: {}", path);
-        return DeleteResult {
-            path: path.to_string(),
-            success: false,
-            error: Some("Safety check failed: This doesn't appear to be a legitimate node_modules directory".to_string()),
-        };
+// Retries one folder that failed on permissions with administrator rights.
+// Only a folder a confirmed delete just failed on that way is accepted. The
+// OS asks the user first; every safety check runs again before that.
+#[tauri::command]
+async fn delete_elevated(
+    app: tauri::AppHandle,
+    path: String,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    safety_checks: tauri::State<'_, legitimacy::SafetyCheckSettings>,
+    pending: tauri::State<'_, delete_tokens::PendingDeletes>,
+) -> Result<DeleteResult, Error> {
+    pending.redeem_elevation(&path)?;
+    let verdicts = legitimacy::check_batch(
+        std::slice::from_ref(&path),
+        &safety_checks.current(),
+        &jobs.child_token(),
+    )
+    .await;
+    let is_legitimate = verdicts.first().copied().unwrap_or(false);
+    let protected = protected_projects::rules(&app);
+    let check = {
+        let path = path.clone();
+        task::spawn_blocking(move || check_deletable(&path, is_legitimate, &protected))
+    }
+    .await
+    .map_err(|e| format!("Failed to check {}: {}", path, e))?;
+    if let Some(failed) = check {
+        return Ok(failed);
     }
 
-    // Use trash crate instead of custom implementation
-    match trash::delete(&path_buf) {
-        Ok(_) => {
-            println!("Successfully deleted: {}", path);
-            DeleteResult {
-                path: path.to_string(),
-                success: true,
-                error: None,
-            }
-        }
-        Err(e) => {
-            println!("Failed to delete {}: {}", path, e);
+    let path_buf = PathBuf::from(&path);
+    let size = size_before_delete(&app, &path_buf).await;
+    let removed = task::spawn_blocking(move || elevation::remove(&path_buf))
+        .await
+        .map_err(|e| format!("Failed to delete {}: {}", path, e))?;
+
+    let result = match removed {
+        Ok(()) => {
+            println!("Deleted with elevation: {}", path);
             DeleteResult {
-                path: path.to_string(),
-                success: false,
-                error: Some(format!("Failed to delete: {}", e)),
+                bytes_freed: size.map(|size| size.reclaimable),
+                files_removed: size.map(|size| size.files).filter(|&files| files > 0),
+                elevated: true,
+                ..DeleteResult::deleted(&path, delete_backend::DeleteBackend::Direct)
             }
         }
-    }
+        Err(e) => DeleteResult::failed(&path, e),
+    };
+    delete_history::record(&app, &result);
+    Ok(result)
 }
 
-async fn is_legitimate_node_modules(path: &Path) -> bool {
-    let path = path.to_path_buf();
+// Lists the folders with their sizes and issues the token execute_delete
+// takes to delete them
+#[tauri::command]
+async fn prepare_delete(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    safety_checks: tauri::State<'_, legitimacy::SafetyCheckSettings>,
+    pending: tauri::State<'_, delete_tokens::PendingDeletes>,
+) -> Result<DeletePlan, Error> {
+    let verdicts =
+        legitimacy::check_batch(&paths, &safety_checks.current(), &jobs.child_token()).await;
+
+    let risks = risks_of(&app, &paths).await;
+
+    let mut items = Vec::with_capacity(paths.len());
+    for ((path, is_legitimate), risk) in paths.iter().zip(verdicts).zip(risks) {
+        let size = size_before_delete(&app, Path::new(path)).await;
+        items.push(DeletePreviewItem {
+            path: path.clone(),
+            is_legitimate,
+            size: size.map(|size| size.reclaimable),
+            risk: risk.map(|risk| risk.level),
+        });
+    }
 
-    // Run legitimacy check in a blocking thread pool
-    task::spawn_blocking(move || {
-        // First, check if parent directory has package.json or lockfiles
-        if let Some(parent) = path.parent() {
-            let parent_indicators = [
-                "package.json",
-                "package-lock.json",
-                "yarn.lock",
-                "pnpm-lock.yaml",
-                "bun.lockb",
-            ];
-
-            let mut has_parent_indicators = false;
-            for indicator in &parent_indicators {
-                if parent.join(indicator).exists() {
-                    has_parent_indicators = true;
-                    break;
-                }
-            }
+    Ok(DeletePlan {
+        token: pending.issue(paths)?,
+        expires_in_secs: delete_tokens::CONFIRMATION_TIMEOUT.as_secs(),
+        items,
+    })
+}
 
-            if !has_parent_indicators {
-                println!("No parent indicators found for: {}", path.display());
-                // For debugging, let's be more lenient and continue with the check
-                // return false;
-            }
+// Runs every check execute_delete would and measures what would be
+// freed, without deleting anything
+#[tauri::command]
+async fn preview_delete(
+    paths: Vec<String>,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    safety_checks: tauri::State<'_, legitimacy::SafetyCheckSettings>,
+    protected: tauri::State<'_, protected_projects::ProtectedProjects>,
+) -> Result<DryRunReport, Error> {
+    let protected = protected.current();
+    let job = jobs.start(jobs::JobKind::Size);
+    let verdicts =
+        legitimacy::check_batch(&paths, &safety_checks.current(), job.cancellation()).await;
+
+    let mut items = Vec::with_capacity(paths.len());
+    for (path, is_legitimate) in paths.iter().zip(verdicts) {
+        let check = {
+            let path = path.clone();
+            let protected = protected.clone();
+            task::spawn_blocking(move || check_deletable(&path, is_legitimate, &protected))
         }
+        .await
+        .map_err(|e| format!("Failed to check {}: {}", path, e))?;
+        let (status, reason) = match check {
+            Some(failed) => (failed.status, failed.error),
+            None => (DeleteStatus::Deleted, None),
+        };
 
-        // Check if this directory contains typical node_modules contents
-        if let Ok(entries) = fs::read_dir(&path) {
-            let mut has_package_json = false;
-            let mut has_node_modules_structure = false;
-            let mut entry_count = 0;
+        // Folders that fail outright have nothing to measure
+        let measured = if status == DeleteStatus::Failed {
+            None
+        } else {
+            job.progress(Path::new(path));
+            sizing::measure_directory(
+                Path::new(path),
+                sizing::DEFAULT_SIZE_BUDGET,
+                job.cancellation().clone(),
+                |_| {},
+            )
+            .await
+        };
+        items.push(DryRunItem {
+            path: path.clone(),
+            status,
+            reason,
+            bytes: measured.map(|size| size.reclaimable),
+            files: measured.map(|size| size.files),
+        });
+    }
 
-            for entry in entries.flatten() {
-                entry_count += 1;
+    let deleted = items
+        .iter()
+        .filter(|item| item.status == DeleteStatus::Deleted);
+    Ok(DryRunReport {
+        total_bytes: deleted.clone().filter_map(|item| item.bytes).sum(),
+        total_files: deleted.filter_map(|item| item.files).sum(),
+        items,
+    })
+}
 
-                // Reject symlinks/junctions
-                if let Ok(metadata) = fs::symlink_metadata(entry.path()) {
-                    if metadata.file_type().is_symlink() {
-                        continue;
-                    }
-                }
+async fn calculate_directory_size(path: &Path, cancel: CancellationToken) -> Option<u64> {
+    sizing::measure_directory(path, sizing::DEFAULT_SIZE_BUDGET, cancel, |_| {})
+        .await
+        .map(|size| size.total)
+}
 
-                if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_dir() {
-                        // Check for common package directories
-                        let name = entry.file_name();
-                        let name_str = name.to_string_lossy();
-                        if name_str.contains('.') && name_str.len() > 3 {
-                            has_node_modules_structure = true;
-                        }
-                    } else if metadata.is_file() {
-                        // Check for package.json or similar files
-                        let name = entry.file_name();
-                        let name_str = name.to_string_lossy();
-                        if name_str == "package.json" || name_str == "package-lock.json" {
-                            has_package_json = true;
-                        }
-                    }
-                }
+async fn project_last_modified(path: &Path) -> Option<SystemTime> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || activity::last_modified(&path))
+        .await
+        .ok()
+        .flatten()
+}
 
-                // Limit check to first 50 entries for performance
-                if entry_count > 50 {
-                    break;
-                }
-            }
+// The risk score the last scan gave each of `paths`. Ones it didn't
+// describe, e.g. after the scan cache was cleared, are scored now, so an
+// uncached folder can't skip the high risk confirmation.
+async fn risks_of(app: &tauri::AppHandle, paths: &[String]) -> Vec<Option<risk::Risk>> {
+    let snapshot = app
+        .try_state::<scan_cache::ScanCache>()
+        .map(|cache| cache.snapshot());
+    let cached: Vec<Option<risk::Risk>> = paths
+        .iter()
+        .map(|path| {
+            snapshot
+                .as_ref()
+                .and_then(|snapshot| snapshot.items.get(path))
+                .and_then(|item| item.risk.clone())
+        })
+        .collect();
+    if cached.iter().all(Option::is_some) {
+        return cached;
+    }
 
-            // Must have either typical structure or package files
-            has_node_modules_structure || has_package_json
-        } else {
-            false
-        }
-    })
-    .await
-    .unwrap_or(false)
+    let working_dirs = Arc::new(
+        task::spawn_blocking(risk::process_working_dirs)
+            .await
+            .unwrap_or_default(),
+    );
+    let mut risks = Vec::with_capacity(paths.len());
+    for (path, cached) in paths.iter().zip(cached) {
+        risks.push(match cached {
+            Some(risk) => Some(risk),
+            None => risk::assess_path(path, working_dirs.clone()).await,
+        });
+    }
+    risks
 }
 
-async fn scan_directory_with_progressive_progress(
-    roots: &[String],
-    include_sizes: bool,
-    window: Option<&tauri::Window>,
-) -> Result<Vec<ScanItem>, String> {
-    let mut results = Vec::new();
-    let mut folders_scanned = 0;
-    let mut node_modules_found = 0;
-
-    for root in roots {
-        if let Err(e) = scan_directory_progressive_single(
-            root,
-            include_sizes,
-            &mut folders_scanned,
-            &mut node_modules_found,
-            &mut results,
-            window,
-        )
-        .await
-        {
-            eprintln!("Error scanning {}: {}", root, e);
-        }
+// The scan's size when the cache has one, otherwise measured now
+async fn size_before_delete(app: &tauri::AppHandle, path: &Path) -> Option<sizing::DirectorySize> {
+    let cached = app
+        .try_state::<scan_cache::ScanCache>()
+        .and_then(|cache| cache.snapshot().size(&path.to_string_lossy()));
+    if cached.is_some() {
+        return cached;
     }
 
-    Ok(results)
+    let cancel = app
+        .try_state::<jobs::JobRegistry>()
+        .map(|jobs| jobs.child_token())
+        .unwrap_or_default();
+    sizing::measure_directory(path, sizing::DEFAULT_SIZE_BUDGET, cancel, |_| {}).await
 }
 
-async fn scan_directory_progressive_single(
-    root: &str,
-    include_sizes: bool,
-    folders_scanned: &mut usize,
-    node_modules_found: &mut usize,
-    results: &mut Vec<ScanItem>,
-    window: Option<&tauri::Window>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let root_path = Path::new(root);
-    if !root_path.exists() || !root_path.is_dir() {
-        return Ok(());
+async fn delete_single_node_modules(
+    app: &tauri::AppHandle,
+    backend: delete_backend::DeleteBackend,
+    options: delete_backend::RemoveOptions,
+    path: &str,
+    is_legitimate: bool,
+) -> DeleteResult {
+    let result = match check_deletable(path, is_legitimate, &protected_projects::rules(app)) {
+        Some(failed) => failed,
+        None => remove_node_modules(app, backend, options, path).await,
+    };
+    delete_history::record(app, &result);
+    result
+}
+
+// With the saved retry policy and the scan's size when it has one
+async fn remove_node_modules(
+    app: &tauri::AppHandle,
+    backend: delete_backend::DeleteBackend,
+    options: delete_backend::RemoveOptions,
+    path: &str,
+) -> DeleteResult {
+    let size = size_before_delete(app, Path::new(path)).await;
+    let options = delete_backend::RemoveOptions {
+        retry: app
+            .try_state::<delete_retry::DeleteRetrySettings>()
+            .map(|settings| settings.current())
+            .unwrap_or_default(),
+        ..options
+    };
+    let result = delete_backend::remove_node_modules(
+        &delete_backend::host(app),
+        backend,
+        options,
+        path,
+        size,
+    )
+    .await;
+    match &result.error {
+        None => println!("Successfully deleted: {}", path),
+        Some(e) => println!("Failed to delete {}: {}", path, e),
     }
+    result
+}
 
-    let mut stack = vec![(root_path.to_path_buf(), 0)]; // (path, depth)
+// Per drive, for execute_delete; spinning disks get one at a time
+const SSD_DELETE_PARALLELISM: usize = 4;
+const MAX_DELETE_PARALLELISM: usize = 16;
+
+type ScanSession = scan::ScanSession<ScanItem>;
+type ScanOutcome = scan::ScanOutcome<ScanItem>;
+
+// A session that stops with everything else and knows the protect list and
+// how many folders the same roots took last time
+fn new_scan_session(app: &tauri::AppHandle, id: String, roots: &[String]) -> ScanSession {
+    let cancel = app
+        .try_state::<jobs::JobRegistry>()
+        .map(|jobs| jobs.child_token())
+        .unwrap_or_default();
+    let expected = app
+        .try_state::<scan_estimate::ScanTotals>()
+        .and_then(|totals| totals.expected(roots));
+    ScanSession::new(id, roots, cancel)
+        .with_expected_folders(expected)
+        .with_protected(protected_projects::rules(app))
+}
 
-    while let Some((current_path, depth)) = stack.pop() {
-        // Skip special directories on Unix systems
-        #[cfg(not(target_os = "windows"))]
-        {
-            if let Some(name) = current_path.file_name() {
-                let name_str = name.to_string_lossy();
-                if matches!(name_str.as_ref(), "proc" | "sys" | "dev") {
-                    continue;
-                }
-            }
-        }
+// What the results would free, for the summary
+fn reclaimable_bytes(items: &[ScanItem]) -> u64 {
+    items
+        .iter()
+        .filter_map(|item| item.reclaimable_size.or(item.size))
+        .sum()
+}
 
-        // Skip irrelevant directories that won't contain node_modules
-        if let Some(name) = current_path.file_name() {
-            let name_str = name.to_string_lossy();
-            if should_skip_directory(&name_str, depth) {
-                continue;
-            }
-        }
+struct SuspendedScan {
+    config: ScanConfig,
+    session: Arc<ScanSession>,
+}
 
-        if let Ok(entries) = fs::read_dir(&current_path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
+// Scans that stopped at their result cap or were paused, keyed by session id
+#[derive(Default)]
+struct SuspendedScans(Mutex<HashMap<String, SuspendedScan>>);
 
-                // Reject symlinks/junctions
-                if let Ok(metadata) = fs::symlink_metadata(&path) {
-                    if metadata.file_type().is_symlink() {
-                        continue;
-                    }
-                }
+// The scans currently running, keyed by session id
+#[derive(Default)]
+struct ActiveScans(Mutex<HashMap<String, Arc<ScanSession>>>);
 
-                if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_dir() {
-                        if let Some(name) = path.file_name() {
-                            if name == "node_modules" {
-                                // Found a node_modules directory
-                                let project_path = current_path.to_string_lossy().to_string();
-                                let node_modules_path = path.to_string_lossy().to_string();
-
-                                let size = if include_sizes {
-                                    calculate_directory_size(&path).await
-                                } else {
-                                    None
-                                };
-
-                                let item = ScanItem {
-                                    project_path,
-                                    node_modules_path,
-                                    size,
-                                };
-
-                                *node_modules_found += 1;
-                                results.push(item.clone());
-
-                                // Don't recurse into node_modules
-                                continue;
-                            }
-                        }
+// Unregisters a running scan once its command returns
+struct ActiveScanGuard {
+    app: tauri::AppHandle,
+    id: String,
+}
 
-                        // Only add subdirectory if it's worth scanning
-                        if depth < 6 && should_scan_subdirectory(&path, depth) {
-                            stack.push((path, depth + 1));
-                        }
-                    }
-                }
+impl Drop for ActiveScanGuard {
+    fn drop(&mut self) {
+        if let Some(active) = self.app.try_state::<ActiveScans>() {
+            if let Ok(mut active) = active.0.lock() {
+                active.remove(&self.id);
             }
         }
+    }
+}
 
-        *folders_scanned += 1;
-
-        // Emit progress update more frequently for better UX
-        if *folders_scanned % 5 == 0 || window.is_some() {
-            if let Some(w) = window {
-                let progress = ScanProgress {
-                    current_folder: current_path.to_string_lossy().to_string(),
-                    folders_scanned: *folders_scanned,
-                    total_folders_estimated: 0, // Mark as unknown for better UX
-                    node_modules_found: *node_modules_found,
-                    directories_skipped: 0, // Will be updated later
-                    is_complete: false,
-                };
-
-                if let Err(e) = w.emit("scan_progress", progress) {
-                    eprintln!("Failed to emit progress: {}", e);
-                }
-            }
+fn register_scan(app: &tauri::AppHandle, session: &Arc<ScanSession>) -> ActiveScanGuard {
+    if let Some(active) = app.try_state::<ActiveScans>() {
+        if let Ok(mut active) = active.0.lock() {
+            active.insert(session.id.clone(), session.clone());
         }
+    }
 
-        // Small delay to keep UI responsive
-        tokio::time::sleep(Duration::from_millis(1)).await;
+    ActiveScanGuard {
+        app: app.clone(),
+        id: session.id.clone(),
     }
+}
 
-    Ok(())
+// Items hidden for the session stay hidden only until the next fresh scan;
+// continuing a paused one keeps them hidden
+fn forget_hidden_items(app: &tauri::AppHandle) {
+    if let Some(exclusions) = app.try_state::<session_exclusions::SessionExclusions>() {
+        exclusions.clear();
+    }
 }
 
-fn should_skip_directory(name: &str, depth: usize) -> bool {
-    // Always skip these directories regardless of depth
-    let always_skip = [
-        ".pnpm-store",
-        ".npm",
-        ".yarn",
-        ".npmrc",
-        ".yarnrc",
-        ".yarn-cache",
-        ".npm-cache",
-        ".yarn-cache",
-        ".npm-cache",
-        ".yarn-cache",
-        ".git",
-        ".svn",
-        ".hg",
-        ".bzr", // Version control
-        ".vscode",
-        ".idea",
-        ".atom",
-        ".sublime",     // IDE
-        "node_modules", // Already found
-        "dist",
-        "build",
-        ".next",
-        "out",
-        "target", // Build outputs
-        ".cache",
-        ".temp",
-        "tmp",
-        "temp", // Cache/temp
-        "android",
-        "ios",
-        "macos",
-        "windows", // OS specific
-        "bin",
-        "obj",
-        "Debug",
-        "Release", // Binary/compiled
-        "vendor",
-        "composer",
-        "gradle",
-        "maven", // Other package managers
-    ];
-
-    if always_skip.iter().any(|&skip| name == skip) {
-        return true;
-    }
-
-    // Skip hidden directories at root level (depth 0)
-    if depth == 0 && name.starts_with('.') && name != ".config" {
-        return true;
-    }
-
-    // Skip system directories at root level
-    if depth == 0 {
-        let system_dirs = [
-            "System Volume Information",
-            "Recovery",
-            "Windows",
-            "Program Files",
-            "Program Files (x86)",
-        ];
-        if system_dirs.iter().any(|&sys| name == sys) {
-            return true;
-        }
+// Fills in user annotations, records sizes for trend tracking and keeps an
+// eye on the folders for reinstalls
+fn finish_items(app: &tauri::AppHandle, items: &mut Vec<ScanItem>) {
+    if let Some(exclusions) = app.try_state::<session_exclusions::SessionExclusions>() {
+        exclusions.remove_hidden(items);
+    }
+    if let Some(annotations) = app.try_state::<annotations::ProjectAnnotations>() {
+        annotations.apply(items);
+    }
+    if let Some(history) = app.try_state::<size_history::SizeHistory>() {
+        history.record(items);
     }
+    if let Some(tracked) = app.try_state::<install_watch::TrackedInstalls>() {
+        tracked.track(items);
+    }
+}
 
-    false
-}
-
-fn should_scan_subdirectory(path: &Path, depth: usize) -> bool {
-    // Don't go deeper than 6 levels
-    if depth >= 6 {
-        return false;
-    }
-
-    // Check if this directory contains development indicators
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    let name = entry.file_name();
-                    let name_str = name.to_string_lossy();
-                    // Look for development files
-                    if matches!(
-                        name_str.as_ref(),
-                        "package.json"
-                            | "yarn.lock"
-                            | "pnpm-lock.yaml"
-                            | "lerna.json"
-                            | "tsconfig.json"
-                            | "webpack.config.js"
-                            | "vite.config.ts"
-                            | "angular.json"
-                            | "vue.config.js"
-                            | "next.config.js"
-                            | "Cargo.toml"
-                            | "pom.xml"
-                            | "build.gradle"
-                            | "requirements.txt"
-                    ) {
-                        return true; // This directory is worth scanning
-                    }
-                }
-            }
+// Emits the final progress event with the scan's summary and parks the
+// session if it hit its cap
+fn complete_scan(
+    window: &tauri::Window,
+    suspended: &SuspendedScans,
+    config: ScanConfig,
+    outcome: ScanOutcome,
+) -> ScanResults {
+    // A cancelled scan is over for good, even if it also hit its cap
+    let truncated = outcome.truncated && !outcome.cancelled;
+    let paused = outcome.paused && !outcome.cancelled;
+    let mut items = outcome.items;
+    finish_items(window.app_handle(), &mut items);
+    let summary = outcome.session.summary(reclaimable_bytes(&items));
+    let final_progress = ScanProgress {
+        session_id: outcome.session.id.clone(),
+        current_folder: if outcome.cancelled {
+            "Scan cancelled".to_string()
+        } else if paused {
+            "Scan paused".to_string()
+        } else if truncated {
+            "Scan paused: result limit reached".to_string()
+        } else {
+            "Scan completed".to_string()
+        },
+        folders_scanned: outcome.folders_scanned,
+        total_folders_estimated: outcome.folders_scanned,
+        node_modules_found: outcome.session.node_modules_found(),
+        directories_skipped: outcome.session.directories_skipped(),
+        is_complete: true,
+        truncated,
+        cancelled: outcome.cancelled,
+        paused,
+        eta_seconds: None,
+        summary: Some(summary.clone()),
+    };
+
+    if truncated || paused {
+        if let Ok(mut suspended) = suspended.0.lock() {
+            suspended.insert(
+                outcome.session.id.clone(),
+                SuspendedScan {
+                    config,
+                    session: outcome.session.clone(),
+                },
+            );
         }
     }
 
-    // If no development indicators found, only scan if it's a common development folder
-    if let Some(name) = path.file_name() {
-        let name_str = name.to_string_lossy();
-        let dev_folders = [
-            "src",
-            "lib",
-            "app",
-            "frontend",
-            "backend",
-            "client",
-            "server",
-            "components",
-            "pages",
-            "routes",
-            "api",
-            "services",
-            "utils",
-            "public",
-            "assets",
-            "styles",
-            "scripts",
-            "tests",
-            "docs",
-        ];
-        if dev_folders.iter().any(|&folder| name_str == folder) {
-            return true;
-        }
+    if let Err(e) = window.emit("scan_progress", final_progress) {
+        eprintln!("Failed to emit final progress: {}", e);
+    }
+    if !paused && !truncated {
+        notifications::scan_finished(window.app_handle(), &items, outcome.cancelled);
+    }
+
+    ScanResults { items, summary }
+}
+
+// Remembers what the scan saw so the next incremental rescan can skip
+// folders that haven't changed
+fn cache_scan(app: &tauri::AppHandle, roots: &[String], outcome: &ScanOutcome) {
+    let Some(cache) = app.try_state::<scan_cache::ScanCache>() else {
+        return;
+    };
+
+    let listings = outcome.session.take_listings();
+    let complete_roots: Vec<String> = if outcome.truncated || outcome.cancelled || outcome.paused {
+        Vec::new()
+    } else {
+        roots
+            .iter()
+            .filter(|root| !outcome.session.is_root_lost(Path::new(root)))
+            .cloned()
+            .collect()
+    };
+
+    cache.update(&complete_roots, listings, &outcome.items);
+}
+
+// Remembers how many folders a complete walk took, for the next estimate
+fn record_scan_total(app: &tauri::AppHandle, outcome: &ScanOutcome) {
+    let session = &outcome.session;
+    let lost_root = session
+        .roots
+        .iter()
+        .any(|root| session.is_root_lost(Path::new(root)));
+    // A paused scan is logged once it's continued to the end
+    if !outcome.paused {
+        let partial = outcome.truncated || outcome.cancelled || lost_root;
+        scan_history::record(app, &session.roots, &outcome.items, partial);
+    }
+    if outcome.truncated || outcome.cancelled || outcome.paused || lost_root {
+        return;
+    }
+
+    if let Some(totals) = app.try_state::<scan_estimate::ScanTotals>() {
+        totals.record(&session.roots, outcome.folders_scanned);
     }
+}
 
-    // Default: scan if not too deep
-    depth < 4
+fn record_scan_metrics(
+    metrics: &metrics::Metrics,
+    started_at: SystemTime,
+    duration: Duration,
+    outcome: &ScanOutcome,
+) {
+    metrics.record(
+        jobs::JobKind::Scan,
+        started_at,
+        duration,
+        outcome.folders_scanned as u64,
+        outcome.items.len() as u64,
+        0,
+    );
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let job_registry = jobs::JobRegistry::default();
+
     tauri::Builder::default()
+        // Must be registered first so a second launch (e.g. from a file manager
+        // action) forwards its arguments instead of opening another window
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            shell_integration::handle_args(app, &argv, Path::new(&cwd));
+        }))
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(autostart::init())
+        .manage(shell_integration::PendingScanRequests::default())
+        .manage(job_registry.clone())
+        .manage(metrics::Metrics::default())
+        .manage(install_watch::TrackedInstalls::default())
+        .manage(root_watch::RootWatcher::default())
+        .manage(SuspendedScans::default())
+        .manage(ActiveScans::default())
+        .manage(background_mode::BackgroundMode::default())
+        .manage(session_exclusions::SessionExclusions::default())
+        .manage(undo::LastDelete::default())
+        .manage(delete_tokens::PendingDeletes::default())
+        .setup(|app| {
+            jobs::spawn_watchdog(app.handle().clone(), job_registry);
+            drive_watch::spawn(app.handle().clone());
+            install_watch::spawn(app.handle().clone());
+            app.manage(delete_backend::DeleteBackendSettings::load(app.handle()));
+            app.manage(delete_retry::DeleteRetrySettings::load(app.handle()));
+            app.manage(protected_projects::ProtectedProjects::load(app.handle()));
+            app.manage(annotations::ProjectAnnotations::load(app.handle()));
+            app.manage(size_history::SizeHistory::load(app.handle()));
+            app.manage(scan_cache::ScanCache::load(app.handle()));
+            app.manage(scan_estimate::ScanTotals::load(app.handle()));
+            app.manage(scan_settings::ScanSettings::load(app.handle()));
+            app.manage(legitimacy::SafetyCheckSettings::load(app.handle()));
+            app.manage(drive_visibility::DriveVisibilitySettings::load(
+                app.handle(),
+            ));
+            app.manage(scheduler::Scheduler::load(app.handle()));
+            scheduler::spawn(app.handle().clone());
+            app.manage(launch_scan::LaunchScan::load(app.handle()));
+            launch_scan::spawn(app.handle().clone());
+
+            let args: Vec<String> = std::env::args().collect();
+            let cwd = std::env::current_dir().unwrap_or_default();
+            autostart::apply_startup_args(app.handle(), &args);
+            shell_integration::handle_args(app.handle(), &args, &cwd);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             list_drives,
             start_scan,
             start_scan_with_progress,
-            delete_node_modules,
-            open_folder_dialog,
+            continue_scan,
+            cancel_scan,
+            execute_delete,
+            prepare_delete,
+            delete_with_override,
+            folder_dialog::open_folder_dialog,
             open_folder_in_explorer,
-            test_trash_functionality
+            shell_integration::take_pending_scan_requests,
+            shell_integration::install_finder_quick_action,
+            shell_integration::uninstall_finder_quick_action,
+            shell_integration::is_finder_quick_action_installed,
+            shell_integration::install_file_manager_action,
+            shell_integration::uninstall_file_manager_action,
+            shell_integration::is_file_manager_action_installed,
+            autostart::set_autostart,
+            autostart::get_autostart,
+            delete_backend::get_delete_backend,
+            delete_backend::set_delete_backend,
+            projection::project_reclaim,
+            report::generate_report,
+            check_snapshot_status,
+            jobs::list_jobs,
+            jobs::skip_stalled_directory,
+            metrics::get_metrics,
+            annotations::set_project_annotation,
+            annotations::get_project_annotations,
+            annotations::list_project_tags,
+            annotations::filter_by_tags,
+            planner::plan_cleanup,
+            size_history::get_size_history,
+            scan_file::export_scan,
+            scan_file::import_scan,
+            root_health::check_scan_roots,
+            rescan_incremental,
+            scan_cache::clear_scan_cache,
+            background_mode::set_background_mode,
+            background_mode::get_background_mode,
+            scan_settings::get_scan_settings,
+            scan_settings::set_scan_settings,
+            quarantine_manifest::list_quarantine,
+            quarantine_manifest::verify_quarantined,
+            quarantine_manifest::restore_quarantined,
+            system_overview::system_overview,
+            validate_scan_patterns,
+            report::copy_selection_table,
+            pause_scan,
+            resume_scan,
+            risk::score_risk,
+            legitimacy::get_safety_check_settings,
+            legitimacy::set_safety_check_settings,
+            list_scans,
+            session_exclusions::hide_for_session,
+            session_exclusions::unhide_for_session,
+            session_exclusions::list_session_exclusions,
+            stop_all_work,
+            staleness::filter_results,
+            workspaces::group_by_workspace,
+            delete_workspace_group,
+            root_watch::watch_roots,
+            scheduler::get_schedule,
+            scheduler::set_schedule,
+            launch_scan::get_launch_scan_settings,
+            launch_scan::set_launch_scan_settings,
+            launch_scan::take_launch_scan_results,
+            drive_visibility::get_drive_visibility,
+            drive_visibility::set_drive_visibility,
+            jobs::cancel_delete,
+            preview_delete,
+            undo::restore_last_delete,
+            delete_history::get_delete_history,
+            delete_history::clear_delete_history,
+            delete_retry::get_delete_retry_policy,
+            delete_retry::set_delete_retry_policy,
+            protected_projects::get_protected_projects,
+            protected_projects::protect_project,
+            protected_projects::unprotect_project,
+            delete_elevated,
+            reinstall::reinstall_dependencies,
+            export::export_results,
+            settings::get_settings,
+            settings::update_settings,
+            scan_history::get_statistics,
+            scan_history::clear_scan_history,
+            folder_dialog::open_folders_dialog,
+            launch_scan::get_recent_roots
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Don't leave blocking threads walking the disk after the window is gone
+            if let tauri::RunEvent::Exit = event {
+                if let Some(jobs) = app_handle.try_state::<jobs::JobRegistry>() {
+                    jobs.stop_all();
+                }
+            }
+
+            // Folders sent from the Finder Quick Action arrive as opened URLs
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = event {
+                shell_integration::handle_opened_urls(app_handle, urls);
+            }
+        });
 }
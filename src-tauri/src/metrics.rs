@@ -0,0 +1,133 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, jobs::JobKind};
+
+const MAX_RECENT_OPERATIONS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OperationMetric {
+    pub kind: JobKind,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub directories: u64,
+    pub items: u64,
+    pub failures: u64,
+    pub directories_per_second: f64,
+    pub items_per_second: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OperationTotals {
+    pub operations: u64,
+    pub total_duration_ms: u64,
+    pub directories: u64,
+    pub items: u64,
+    pub failures: u64,
+    pub directories_per_second: f64,
+    pub items_per_second: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetricsReport {
+    pub scans: OperationTotals,
+    pub deletes: OperationTotals,
+    pub recent: Vec<OperationMetric>,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    scans: OperationTotals,
+    deletes: OperationTotals,
+    recent: VecDeque<OperationMetric>,
+}
+
+#[derive(Default)]
+pub struct Metrics(Mutex<MetricsState>);
+
+fn per_second(count: u64, duration: Duration) -> f64 {
+    let seconds = duration.as_secs_f64();
+    if seconds > 0.0 {
+        count as f64 / seconds
+    } else {
+        0.0
+    }
+}
+
+impl OperationTotals {
+    fn add(&mut self, metric: &OperationMetric) {
+        self.operations += 1;
+        self.total_duration_ms += metric.duration_ms;
+        self.directories += metric.directories;
+        self.items += metric.items;
+        self.failures += metric.failures;
+
+        let total = Duration::from_millis(self.total_duration_ms);
+        self.directories_per_second = per_second(self.directories, total);
+        self.items_per_second = per_second(self.items, total);
+    }
+}
+
+impl Metrics {
+    pub fn record(
+        &self,
+        kind: JobKind,
+        started_at: SystemTime,
+        duration: Duration,
+        directories: u64,
+        items: u64,
+        failures: u64,
+    ) {
+        let metric = OperationMetric {
+            kind,
+            started_at: DateTime::<Utc>::from(started_at).to_rfc3339(),
+            duration_ms: duration.as_millis() as u64,
+            directories,
+            items,
+            failures,
+            directories_per_second: per_second(directories, duration),
+            items_per_second: per_second(items, duration),
+        };
+
+        let Ok(mut state) = self.0.lock() else {
+            return;
+        };
+
+        match kind {
+            JobKind::Scan => state.scans.add(&metric),
+            JobKind::Delete => state.deletes.add(&metric),
+            JobKind::Size => {}
+        }
+
+        if state.recent.len() == MAX_RECENT_OPERATIONS {
+            state.recent.pop_front();
+        }
+        state.recent.push_back(metric);
+    }
+
+    // Observed delete throughput, once at least one delete has been timed
+    pub fn delete_items_per_second(&self) -> Option<f64> {
+        let state = self.0.lock().ok()?;
+        (state.deletes.items_per_second > 0.0).then_some(state.deletes.items_per_second)
+    }
+}
+
+#[tauri::command]
+pub async fn get_metrics(metrics: tauri::State<'_, Metrics>) -> Result<MetricsReport, Error> {
+    let state = metrics
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read metrics: {}", e))?;
+
+    Ok(MetricsReport {
+        scans: state.scans.clone(),
+        deletes: state.deletes.clone(),
+        recent: state.recent.iter().cloned().collect(),
+    })
+}
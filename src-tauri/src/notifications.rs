@@ -0,0 +1,73 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use nodemodules_cleaner_core::sizing::format_bytes;
+
+use crate::{artifacts::ArtifactKind, settings, DeleteResult, ScanItem};
+
+// Whether to notify at all; on unless turned off in the settings
+pub fn enabled(app: &AppHandle) -> bool {
+    settings::section(app, "notifications").unwrap_or(true)
+}
+
+// Someone looking at the window already sees the result
+fn window_in_view(app: &AppHandle) -> bool {
+    app.get_webview_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false)
+            && window.is_focused().unwrap_or(false)
+            && !window.is_minimized().unwrap_or(false)
+    })
+}
+
+fn show(app: &AppHandle, title: &str, body: String) {
+    if !enabled(app) || window_in_view(app) {
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
+
+// "Found 37 node_modules, 22.4 GB reclaimable"
+pub fn scan_finished(app: &AppHandle, items: &[ScanItem], cancelled: bool) {
+    if cancelled {
+        return;
+    }
+    let kind = if items
+        .iter()
+        .all(|item| item.artifact_type == ArtifactKind::NodeModules)
+    {
+        "node_modules"
+    } else {
+        "folders"
+    };
+    let mut body = format!("Found {} {}", items.len(), kind);
+    if items.iter().any(|item| item.size.is_some()) {
+        let reclaimable: u64 = items
+            .iter()
+            .filter_map(|item| item.reclaimable_size.or(item.size))
+            .sum();
+        body.push_str(&format!(", {} reclaimable", format_bytes(reclaimable)));
+    }
+    show(app, "Scan finished", body);
+}
+
+// "Deleted 12 of 14 folders, 8.1 GB freed; 2 failed"
+pub fn delete_finished(app: &AppHandle, results: &[DeleteResult]) {
+    let deleted = results.iter().filter(|result| result.success);
+    let freed: u64 = deleted
+        .clone()
+        .filter_map(|result| result.bytes_freed)
+        .sum();
+    let mut body = format!(
+        "Deleted {} of {} folders, {} freed",
+        deleted.count(),
+        results.len(),
+        format_bytes(freed)
+    );
+    let failed = results.iter().filter(|result| !result.success).count();
+    if failed > 0 {
+        body.push_str(&format!("; {} failed", failed));
+    }
+    show(app, "Delete finished", body);
+}
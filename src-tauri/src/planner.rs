@@ -0,0 +1,297 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, jobs, metrics, workspaces, ScanItem};
+
+// Used until a delete has been timed on this machine
+const FALLBACK_SECONDS_PER_ITEM: f64 = 2.0;
+const FALLBACK_SECONDS_PER_GB: f64 = 1.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanOrder {
+    #[default]
+    LargestFirst,
+    PerDrive,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    // One selected node_modules lives inside another selected one
+    Nested,
+    // Several selected projects belong to the same workspace
+    SameMonorepo,
+    // A running job is working inside a selected path
+    RunningJob,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlanConflict {
+    pub kind: ConflictKind,
+    pub paths: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlanStep {
+    pub order: usize,
+    pub project_path: String,
+    pub node_modules_path: String,
+    pub drive: String,
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CleanupPlan {
+    pub steps: Vec<PlanStep>,
+    pub conflicts: Vec<PlanConflict>,
+    pub total_bytes: u64,
+    pub estimated_seconds: u64,
+}
+
+// Mount point (Unix) or drive prefix (Windows) holding `path`
+fn drive_of(path: &Path) -> String {
+    #[cfg(windows)]
+    {
+        match path.components().next() {
+            Some(std::path::Component::Prefix(prefix)) => {
+                prefix.as_os_str().to_string_lossy().to_uppercase()
+            }
+            _ => String::new(),
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let Ok(device) = std::fs::metadata(path).map(|metadata| metadata.dev()) else {
+            return "/".to_string();
+        };
+
+        // Walk up until the device changes; the last directory on it is the mount point
+        let mut mount = path.to_path_buf();
+        for ancestor in path.ancestors().skip(1) {
+            match std::fs::metadata(ancestor) {
+                Ok(metadata) if metadata.dev() == device => mount = ancestor.to_path_buf(),
+                _ => break,
+            }
+        }
+        mount.to_string_lossy().to_string()
+    }
+}
+
+// The disk behind `path`, so partitions of one disk share an identity.
+// Falls back to the mount point where the disk can't be resolved.
+pub fn physical_drive_of(path: &Path) -> String {
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(disk) = linux_block_device(path) {
+            return disk;
+        }
+    }
+
+    drive_of(path)
+}
+
+// Whether `drive`, as named by physical_drive_of, is known not to spin.
+// Only Linux says so cheaply; elsewhere the answer is a cautious no.
+pub fn is_solid_state(drive: &str) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        if let Some(disk) = drive.strip_prefix("/dev/") {
+            return std::fs::read_to_string(format!("/sys/block/{}/queue/rotational", disk))
+                .is_ok_and(|rotational| rotational.trim() == "0");
+        }
+    }
+
+    let _ = drive;
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn linux_block_device(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = std::fs::metadata(path).ok()?.dev();
+    let major = ((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0fff);
+    let minor = ((dev >> 12) & 0xffff_ff00) | (dev & 0x00ff);
+    // Anonymous devices (tmpfs, btrfs subvolumes, FUSE) have no disk in sysfs
+    if major == 0 {
+        return None;
+    }
+
+    let device = std::fs::canonicalize(format!("/sys/dev/block/{}:{}", major, minor)).ok()?;
+    let disk = if device.join("partition").is_file() {
+        device.parent()?
+    } else {
+        device.as_path()
+    };
+    Some(format!("/dev/{}", disk.file_name()?.to_string_lossy()))
+}
+
+fn find_conflicts(items: &[ScanItem], running: &[jobs::JobStatus]) -> Vec<PlanConflict> {
+    let mut conflicts = Vec::new();
+
+    for outer in items {
+        let outer_path = Path::new(&outer.node_modules_path);
+        for inner in items {
+            let inner_path = Path::new(&inner.node_modules_path);
+            if inner_path != outer_path && inner_path.starts_with(outer_path) {
+                conflicts.push(PlanConflict {
+                    kind: ConflictKind::Nested,
+                    paths: vec![
+                        outer.node_modules_path.clone(),
+                        inner.node_modules_path.clone(),
+                    ],
+                    message: format!(
+                        "{} is removed together with {}",
+                        inner.node_modules_path, outer.node_modules_path
+                    ),
+                });
+            }
+        }
+    }
+
+    let mut workspaces: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for item in items {
+        if let Some(root) = workspaces::workspace_root(Path::new(&item.project_path)) {
+            workspaces
+                .entry(root)
+                .or_default()
+                .push(item.node_modules_path.clone());
+        }
+    }
+    for (root, paths) in workspaces {
+        if paths.len() > 1 {
+            conflicts.push(PlanConflict {
+                kind: ConflictKind::SameMonorepo,
+                message: format!(
+                    "{} selected folders belong to the workspace at {}; packages may rely on hoisted dependencies",
+                    paths.len(),
+                    root.display()
+                ),
+                paths,
+            });
+        }
+    }
+
+    for job in running {
+        let current = Path::new(&job.current_path);
+        let touched: Vec<String> = items
+            .iter()
+            .filter(|item| {
+                job.kind == jobs::JobKind::Delete
+                    || current.starts_with(&item.node_modules_path)
+                    || current.starts_with(&item.project_path)
+            })
+            .map(|item| item.node_modules_path.clone())
+            .collect();
+
+        if !touched.is_empty() {
+            conflicts.push(PlanConflict {
+                kind: ConflictKind::RunningJob,
+                paths: touched,
+                message: match job.kind {
+                    jobs::JobKind::Delete => {
+                        format!("Delete job {} is still running", job.id)
+                    }
+                    _ => format!("Job {} is working in {}", job.id, job.current_path),
+                },
+            });
+        }
+    }
+
+    conflicts
+}
+
+fn estimate_seconds(items: &[PlanStep], items_per_second: Option<f64>) -> u64 {
+    let seconds = match items_per_second {
+        Some(rate) => items.len() as f64 / rate,
+        None => {
+            let gigabytes = items.iter().filter_map(|step| step.size).sum::<u64>() as f64
+                / (1024.0 * 1024.0 * 1024.0);
+            items.len() as f64 * FALLBACK_SECONDS_PER_ITEM + gigabytes * FALLBACK_SECONDS_PER_GB
+        }
+    };
+    seconds.ceil() as u64
+}
+
+fn build_plan(
+    items: Vec<ScanItem>,
+    order: PlanOrder,
+    running: Vec<jobs::JobStatus>,
+    items_per_second: Option<f64>,
+) -> CleanupPlan {
+    let conflicts = find_conflicts(&items, &running);
+
+    // Nested folders go away with their parent, so they get no step of their own
+    let mut seen = HashSet::new();
+    let mut steps: Vec<PlanStep> = items
+        .iter()
+        .filter(|item| {
+            !items.iter().any(|other| {
+                other.node_modules_path != item.node_modules_path
+                    && Path::new(&item.node_modules_path).starts_with(&other.node_modules_path)
+            })
+        })
+        .filter(|item| seen.insert(item.node_modules_path.clone()))
+        .cloned()
+        .map(|item| PlanStep {
+            order: 0,
+            drive: drive_of(Path::new(&item.node_modules_path)),
+            project_path: item.project_path,
+            node_modules_path: item.node_modules_path,
+            size: item.size,
+        })
+        .collect();
+
+    steps.sort_by_key(|step| std::cmp::Reverse(step.size));
+    if order == PlanOrder::PerDrive {
+        // Keep each drive's deletions together, biggest drive total first
+        let mut drive_totals: HashMap<String, u64> = HashMap::new();
+        for step in &steps {
+            *drive_totals.entry(step.drive.clone()).or_default() += step.size.unwrap_or(0);
+        }
+        steps.sort_by_key(|step| {
+            (
+                std::cmp::Reverse(drive_totals.get(&step.drive).copied().unwrap_or(0)),
+                step.drive.clone(),
+                std::cmp::Reverse(step.size),
+            )
+        });
+    }
+
+    for (index, step) in steps.iter_mut().enumerate() {
+        step.order = index + 1;
+    }
+
+    CleanupPlan {
+        total_bytes: steps.iter().filter_map(|step| step.size).sum(),
+        estimated_seconds: estimate_seconds(&steps, items_per_second),
+        steps,
+        conflicts,
+    }
+}
+
+#[tauri::command]
+pub async fn plan_cleanup(
+    selection: Vec<ScanItem>,
+    order: Option<PlanOrder>,
+    jobs: tauri::State<'_, jobs::JobRegistry>,
+    metrics: tauri::State<'_, metrics::Metrics>,
+) -> Result<CleanupPlan, Error> {
+    let running = jobs.statuses();
+    let items_per_second = metrics.delete_items_per_second();
+    let order = order.unwrap_or_default();
+
+    // Workspace detection and drive lookup both touch the filesystem
+    tokio::task::spawn_blocking(move || build_plan(selection, order, running, items_per_second))
+        .await
+        .map_err(|e| format!("Failed to plan cleanup: {}", e).into())
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::workspaces;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageManager {
+    Npm,
+    Yarn,
+    Pnpm,
+    Bun,
+}
+
+// Checked in order; bun and pnpm projects sometimes keep a stale
+// package-lock.json around, so npm's lockfile comes last
+const LOCKFILES: [(&str, PackageManager); 6] = [
+    ("bun.lockb", PackageManager::Bun),
+    ("bun.lock", PackageManager::Bun),
+    ("pnpm-lock.yaml", PackageManager::Pnpm),
+    ("yarn.lock", PackageManager::Yarn),
+    ("package-lock.json", PackageManager::Npm),
+    ("npm-shrinkwrap.json", PackageManager::Npm),
+];
+
+#[derive(Debug, Default)]
+pub struct ProjectMetadata {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub package_manager: Option<PackageManager>,
+}
+
+// The "packageManager" field Corepack reads, e.g. "pnpm@9.1.0"
+fn declared_package_manager(declared: &str) -> Option<PackageManager> {
+    match declared.split('@').next()? {
+        "npm" => Some(PackageManager::Npm),
+        "yarn" => Some(PackageManager::Yarn),
+        "pnpm" => Some(PackageManager::Pnpm),
+        "bun" => Some(PackageManager::Bun),
+        _ => None,
+    }
+}
+
+fn lockfile_package_manager(dir: &Path) -> Option<PackageManager> {
+    LOCKFILES
+        .iter()
+        .find(|(lockfile, _)| dir.join(lockfile).is_file())
+        .map(|(_, package_manager)| *package_manager)
+}
+
+pub fn read(project: &Path) -> ProjectMetadata {
+    let manifest: Option<serde_json::Value> = std::fs::read_to_string(project.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok());
+    let field = |key: &str| {
+        manifest
+            .as_ref()
+            .and_then(|manifest| manifest.get(key))
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+    };
+
+    let package_manager = field("packageManager")
+        .and_then(|declared| declared_package_manager(&declared))
+        .or_else(|| lockfile_package_manager(project))
+        // Workspace packages share the lockfile at the workspace root
+        .or_else(|| {
+            workspaces::workspace_root(project)
+                .filter(|root| root != project)
+                .and_then(|root| lockfile_package_manager(&root))
+        });
+
+    ProjectMetadata {
+        name: field("name"),
+        version: field("version"),
+        package_manager,
+    }
+}
@@ -0,0 +1,113 @@
+use std::{
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calculate_directory_size,
+    jobs::{JobKind, JobRegistry},
+    project_last_modified, ScanItem,
+};
+
+use crate::error::Error;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const MONTH_DAYS: u64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReclaimPolicy {
+    pub min_age_days: u64,
+    pub min_size_bytes: Option<u64>,
+    pub months: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MonthlyReclaim {
+    pub month: usize,
+    pub items: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReclaimProjection {
+    pub reclaimable_now_items: usize,
+    pub reclaimable_now_bytes: u64,
+    pub monthly: Vec<MonthlyReclaim>,
+    pub below_size_threshold: usize,
+    pub unknown_activity: usize,
+}
+
+#[tauri::command]
+pub async fn project_reclaim(
+    items: Vec<ScanItem>,
+    policy: ReclaimPolicy,
+    jobs: tauri::State<'_, JobRegistry>,
+) -> Result<ReclaimProjection, Error> {
+    let job = jobs.start(JobKind::Size);
+    let months = policy.months.unwrap_or(12);
+    let now = SystemTime::now();
+
+    let mut projection = ReclaimProjection {
+        reclaimable_now_items: 0,
+        reclaimable_now_bytes: 0,
+        monthly: (1..=months)
+            .map(|month| MonthlyReclaim {
+                month,
+                items: 0,
+                bytes: 0,
+            })
+            .collect(),
+        below_size_threshold: 0,
+        unknown_activity: 0,
+    };
+
+    for item in items {
+        job.progress(Path::new(&item.node_modules_path));
+
+        let size = match item.size {
+            Some(size) => size,
+            None => calculate_directory_size(
+                Path::new(&item.node_modules_path),
+                job.cancellation().clone(),
+            )
+            .await
+            .unwrap_or(0),
+        };
+
+        if policy.min_size_bytes.is_some_and(|min| size < min) {
+            projection.below_size_threshold += 1;
+            continue;
+        }
+
+        let Some(last_modified) = project_last_modified(Path::new(&item.project_path)).await else {
+            projection.unknown_activity += 1;
+            continue;
+        };
+
+        let age_days = now
+            .duration_since(last_modified)
+            .unwrap_or_default()
+            .as_secs()
+            / DAY.as_secs();
+
+        if age_days >= policy.min_age_days {
+            projection.reclaimable_now_items += 1;
+            projection.reclaimable_now_bytes += size;
+            continue;
+        }
+
+        // Assuming the project stays untouched, it crosses the age threshold
+        // this many days from now
+        let days_until_eligible = policy.min_age_days - age_days;
+        let month = days_until_eligible.div_ceil(MONTH_DAYS) as usize;
+
+        if let Some(bucket) = projection.monthly.get_mut(month.saturating_sub(1)) {
+            bucket.items += 1;
+            bucket.bytes += size;
+        }
+    }
+
+    Ok(projection)
+}
@@ -0,0 +1,83 @@
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Manager};
+
+use crate::{error::Error, settings};
+
+pub use nodemodules_cleaner_core::protect_rules::ProtectRules;
+
+pub struct ProtectedProjects(Mutex<Arc<ProtectRules>>);
+
+impl ProtectedProjects {
+    pub fn load(app: &AppHandle) -> Self {
+        let rules = settings::section::<Vec<String>>(app, "protected_projects")
+            .and_then(|entries| ProtectRules::compile(entries).ok())
+            .unwrap_or_default();
+        ProtectedProjects(Mutex::new(Arc::new(rules)))
+    }
+
+    pub fn current(&self) -> Arc<ProtectRules> {
+        self.0.lock().map(|rules| rules.clone()).unwrap_or_default()
+    }
+
+    pub fn update(
+        &self,
+        app: &AppHandle,
+        edit: impl FnOnce(&mut Vec<String>),
+    ) -> Result<Vec<String>, String> {
+        let mut current = self
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to update protected projects: {}", e))?;
+        let mut entries = current.entries().to_vec();
+        edit(&mut entries);
+        let rules = ProtectRules::compile(entries)?;
+
+        settings::save_section(app, "protected_projects", rules.entries())?;
+
+        *current = Arc::new(rules);
+        Ok(current.entries().to_vec())
+    }
+}
+
+// The rules in effect, for code that has no State to hand
+pub fn rules(app: &AppHandle) -> Arc<ProtectRules> {
+    app.try_state::<ProtectedProjects>()
+        .map(|protected| protected.current())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub async fn get_protected_projects(
+    protected: tauri::State<'_, ProtectedProjects>,
+) -> Result<Vec<String>, Error> {
+    Ok(protected.current().entries().to_vec())
+}
+
+#[tauri::command]
+pub async fn protect_project(
+    app: AppHandle,
+    entry: String,
+    protected: tauri::State<'_, ProtectedProjects>,
+) -> Result<Vec<String>, Error> {
+    let entry = entry.trim().to_string();
+    if entry.is_empty() {
+        return Err(Error::InvalidInput("Path is empty".to_string()));
+    }
+    Ok(protected.update(&app, |entries| {
+        if !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    })?)
+}
+
+#[tauri::command]
+pub async fn unprotect_project(
+    app: AppHandle,
+    entry: String,
+    protected: tauri::State<'_, ProtectedProjects>,
+) -> Result<Vec<String>, Error> {
+    Ok(protected.update(&app, |entries| {
+        entries.retain(|existing| *existing != entry)
+    })?)
+}
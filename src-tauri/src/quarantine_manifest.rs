@@ -0,0 +1,281 @@
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use crate::{delete_backend, error::Error, long_paths};
+
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ManifestEntry {
+    File {
+        path: String,
+        size: u64,
+        sha256: String,
+    },
+    // node_modules/.bin is mostly links; their target is what matters
+    Symlink {
+        path: String,
+        target: String,
+    },
+    Directory {
+        path: String,
+    },
+}
+
+impl ManifestEntry {
+    fn path(&self) -> &str {
+        match self {
+            ManifestEntry::File { path, .. }
+            | ManifestEntry::Symlink { path, .. }
+            | ManifestEntry::Directory { path } => path,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuarantineManifest {
+    pub version: u32,
+    pub original_path: String,
+    pub quarantined_at: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuarantinedItem {
+    pub name: String,
+    pub original_path: Option<String>,
+    pub quarantined_at: Option<String>,
+    pub has_manifest: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ManifestVerification {
+    pub name: String,
+    pub verified: bool,
+    pub missing: Vec<String>,
+    pub changed: Vec<String>,
+    pub unexpected: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestoreResult {
+    pub restored_path: String,
+    pub verification: ManifestVerification,
+}
+
+fn manifest_path(quarantined: &Path) -> PathBuf {
+    let mut name = quarantined.as_os_str().to_owned();
+    name.push(MANIFEST_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 256 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Every entry below `root`, sorted by relative path ('/'-separated)
+fn collect_entries(root: &Path) -> Result<Vec<ManifestEntry>, String> {
+    let mut entries = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+
+    while let Some(relative) = stack.pop() {
+        let dir = root.join(&relative);
+        let listing =
+            fs::read_dir(&dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+        for entry in listing.flatten() {
+            let relative = relative.join(entry.file_name());
+            let key = relative.to_string_lossy().replace('\\', "/");
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            if file_type.is_symlink() {
+                let target = fs::read_link(entry.path())
+                    .map(|target| target.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                entries.push(ManifestEntry::Symlink { path: key, target });
+            } else if file_type.is_dir() {
+                entries.push(ManifestEntry::Directory { path: key });
+                stack.push(relative);
+            } else {
+                let sha256 = sha256_file(&entry.path())
+                    .map_err(|e| format!("Failed to hash {}: {}", key, e))?;
+                let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+                entries.push(ManifestEntry::File {
+                    path: key,
+                    size,
+                    sha256,
+                });
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(entries)
+}
+
+// Records what a freshly quarantined tree contains, next to it
+pub fn write(quarantined: &Path, original_path: &Path) -> Result<(), String> {
+    let manifest = QuarantineManifest {
+        version: MANIFEST_VERSION,
+        original_path: original_path.to_string_lossy().to_string(),
+        quarantined_at: chrono::Utc::now().to_rfc3339(),
+        entries: collect_entries(quarantined)?,
+    };
+
+    let contents = serde_json::to_string(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    fs::write(manifest_path(quarantined), contents)
+        .map_err(|e| format!("Failed to save manifest: {}", e))
+}
+
+fn read(quarantined: &Path) -> Option<QuarantineManifest> {
+    let contents = fs::read_to_string(manifest_path(quarantined)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn verify(name: &str, quarantined: &Path) -> Result<ManifestVerification, String> {
+    let manifest =
+        read(quarantined).ok_or_else(|| "No manifest was recorded for this item".to_string())?;
+    let actual = collect_entries(quarantined)?;
+
+    let mut missing = Vec::new();
+    let mut changed = Vec::new();
+    for expected in &manifest.entries {
+        match actual.binary_search_by(|entry| entry.path().cmp(expected.path())) {
+            Ok(index) if actual[index] == *expected => {}
+            Ok(_) => changed.push(expected.path().to_string()),
+            Err(_) => missing.push(expected.path().to_string()),
+        }
+    }
+    let unexpected: Vec<String> = actual
+        .iter()
+        .filter(|entry| {
+            manifest
+                .entries
+                .binary_search_by(|expected| expected.path().cmp(entry.path()))
+                .is_err()
+        })
+        .map(|entry| entry.path().to_string())
+        .collect();
+
+    Ok(ManifestVerification {
+        name: name.to_string(),
+        verified: missing.is_empty() && changed.is_empty() && unexpected.is_empty(),
+        missing,
+        changed,
+        unexpected,
+    })
+}
+
+// Resolves an item name from list_quarantine, refusing anything outside the folder
+fn quarantined_item(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+        return Err("Invalid quarantine item".to_string());
+    }
+    let path = delete_backend::quarantine_dir(app)?.join(name);
+    if !path.is_dir() {
+        return Err("No quarantined item with this name".to_string());
+    }
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn list_quarantine(app: AppHandle) -> Result<Vec<QuarantinedItem>, Error> {
+    let dir = delete_backend::quarantine_dir(&app)?;
+
+    tokio::task::spawn_blocking(move || {
+        let entries =
+            fs::read_dir(&dir).map_err(|e| format!("Failed to read quarantine folder: {}", e))?;
+
+        let mut items: Vec<QuarantinedItem> = entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .map(|entry| {
+                let manifest = read(&entry.path());
+                QuarantinedItem {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    has_manifest: manifest.is_some(),
+                    original_path: manifest.as_ref().map(|m| m.original_path.clone()),
+                    quarantined_at: manifest.map(|m| m.quarantined_at),
+                }
+            })
+            .collect();
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(items)
+    })
+    .await
+    .map_err(|e| format!("Failed to list quarantine: {}", e))?
+}
+
+#[tauri::command]
+pub async fn verify_quarantined(
+    app: AppHandle,
+    name: String,
+) -> Result<ManifestVerification, Error> {
+    let path = quarantined_item(&app, &name)?;
+
+    tokio::task::spawn_blocking(move || verify(&name, &long_paths::extended(&path)))
+        .await
+        .map_err(|e| format!("Failed to verify: {}", e))?
+        .map_err(Error::from)
+}
+
+// Moves a quarantined tree back to where it came from, but only if it still
+// matches its manifest byte for byte
+#[tauri::command]
+pub async fn restore_quarantined(app: AppHandle, name: String) -> Result<RestoreResult, Error> {
+    let path = long_paths::extended(&quarantined_item(&app, &name)?);
+
+    tokio::task::spawn_blocking(move || {
+        let verification = verify(&name, &path)?;
+        if !verification.verified {
+            return Err(format!(
+                "Quarantined files no longer match their manifest ({} missing, {} changed, {} unexpected)",
+                verification.missing.len(),
+                verification.changed.len(),
+                verification.unexpected.len()
+            ));
+        }
+
+        let manifest = read(&path).ok_or_else(|| "Failed to read manifest".to_string())?;
+        let original = PathBuf::from(&manifest.original_path);
+        if original.exists() {
+            return Err(format!("{} already exists", manifest.original_path));
+        }
+
+        fs::rename(&path, long_paths::extended(&original))
+            .map_err(|e| format!("Failed to restore: {}", e))?;
+        if let Err(e) = fs::remove_file(manifest_path(&path)) {
+            eprintln!("Failed to remove manifest: {}", e);
+        }
+
+        Ok(RestoreResult {
+            restored_path: manifest.original_path,
+            verification,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to restore: {}", e))?
+    .map_err(Error::from)
+}
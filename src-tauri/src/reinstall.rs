@@ -0,0 +1,157 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::task;
+
+use crate::{error::Error, long_paths, project_metadata, project_metadata::PackageManager};
+
+// One line the install printed, emitted as `reinstall_output`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReinstallOutput {
+    pub project_path: String,
+    pub line: String,
+    // Printed to stderr; npm and pnpm put warnings and progress there too
+    pub is_stderr: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReinstallResult {
+    pub project_path: String,
+    pub package_manager: PackageManager,
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+}
+
+// The install that reproduces the lockfile. `npm ci` refuses to run
+// without one, so plain npm projects get `npm install`.
+fn install_args(
+    package_manager: PackageManager,
+    project: &Path,
+) -> (&'static str, &'static [&'static str]) {
+    match package_manager {
+        PackageManager::Npm => {
+            let locked = ["package-lock.json", "npm-shrinkwrap.json"]
+                .iter()
+                .any(|lockfile| long_paths::extended(&project.join(lockfile)).is_file());
+            if locked {
+                ("npm", &["ci"])
+            } else {
+                ("npm", &["install"])
+            }
+        }
+        PackageManager::Yarn => ("yarn", &["install"]),
+        PackageManager::Pnpm => ("pnpm", &["install"]),
+        PackageManager::Bun => ("bun", &["install"]),
+    }
+}
+
+// npm, yarn and pnpm are .cmd shims on Windows, which only cmd can start
+#[cfg(windows)]
+fn install_command(program: &str, args: &[&str]) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let mut command = Command::new("cmd.exe");
+    command
+        .arg("/C")
+        .arg(program)
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+#[cfg(not(windows))]
+fn install_command(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new(program);
+    command.args(args);
+    command
+}
+
+fn emit_lines(app: &AppHandle, project_path: &str, stream: impl Read, is_stderr: bool) {
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        let output = ReinstallOutput {
+            project_path: project_path.to_string(),
+            line,
+            is_stderr,
+        };
+        if let Err(e) = app.emit("reinstall_output", output) {
+            eprintln!("Failed to emit reinstall output: {}", e);
+        }
+    }
+}
+
+fn run_install(
+    app: &AppHandle,
+    project: &Path,
+    package_manager: PackageManager,
+) -> Result<ReinstallResult, String> {
+    let (program, args) = install_args(package_manager, project);
+    let project_path = project.to_string_lossy().to_string();
+
+    let mut child = install_command(program, args)
+        .current_dir(project)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+
+    // Both pipes have to be drained, or the install blocks once one fills up
+    let stderr = child.stderr.take().map(|stderr| {
+        let app = app.clone();
+        let project_path = project_path.clone();
+        std::thread::spawn(move || emit_lines(&app, &project_path, stderr, true))
+    });
+    if let Some(stdout) = child.stdout.take() {
+        emit_lines(app, &project_path, stdout, false);
+    }
+    if let Some(stderr) = stderr {
+        let _ = stderr.join();
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for {}: {}", program, e))?;
+    Ok(ReinstallResult {
+        project_path,
+        package_manager,
+        command: format!("{} {}", program, args.join(" ")),
+        success: status.success(),
+        exit_code: status.code(),
+    })
+}
+
+// Installs a project's dependencies again with the package manager its
+// lockfile (or packageManager field) names, streaming the output as
+// `reinstall_output` events
+#[tauri::command]
+pub async fn reinstall_dependencies(
+    app: AppHandle,
+    project_path: String,
+) -> Result<ReinstallResult, Error> {
+    let project = PathBuf::from(&project_path);
+    task::spawn_blocking(move || {
+        if !long_paths::extended(&project.join("package.json")).is_file() {
+            return Err(format!("{} has no package.json", project.display()));
+        }
+        let package_manager = project_metadata::read(&long_paths::extended(&project))
+            .package_manager
+            .ok_or_else(|| {
+                format!(
+                    "Couldn't tell which package manager {} uses; it has no lockfile",
+                    project.display()
+                )
+            })?;
+        run_install(&app, &project, package_manager)
+    })
+    .await
+    .map_err(|e| format!("Reinstall task failed: {}", e))?
+    .map_err(Error::from)
+}
@@ -0,0 +1,353 @@
+use std::{fs, path::PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use nodemodules_cleaner_core::sizing::format_bytes;
+
+use crate::{
+    annotations::ProjectAnnotations,
+    clipboard,
+    delete_history::{self, DeleteHistoryEntry},
+    error::Error,
+    ScanItem,
+};
+
+// Deletes listed under Cleanups; the totals count every one on record
+const REPORT_CLEANUPS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    Plain,
+    Markdown,
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(crate) fn escape_markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+fn annotation_text(item: &ScanItem) -> String {
+    let mut parts = Vec::new();
+    if !item.tags.is_empty() {
+        parts.push(item.tags.join(", "));
+    }
+    if let Some(note) = &item.note {
+        parts.push(note.clone());
+    }
+    parts.join(" - ")
+}
+
+fn cleanup_date(entry: &DeleteHistoryEntry) -> String {
+    DateTime::parse_from_rfc3339(&entry.timestamp)
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|_| entry.timestamp.clone())
+}
+
+fn cleanup_outcome(entry: &DeleteHistoryEntry) -> String {
+    match (&entry.error, entry.success) {
+        (_, true) => "Deleted".to_string(),
+        (Some(error), false) => format!("Failed: {}", error),
+        (None, false) => "Failed".to_string(),
+    }
+}
+
+fn cleanup_bytes(entry: &DeleteHistoryEntry) -> String {
+    entry
+        .bytes
+        .filter(|_| entry.success)
+        .map(format_bytes)
+        .unwrap_or_else(|| "-".to_string())
+}
+
+// `cleanups` is the delete history, oldest first
+pub(crate) fn render_report(
+    items: &[ScanItem],
+    cleanups: &[DeleteHistoryEntry],
+    format: ReportFormat,
+) -> String {
+    let generated = Local::now().format("%Y-%m-%d %H:%M").to_string();
+    // What deleting would free, as the scan summary and notifications count it
+    let total_bytes: u64 = items.iter().filter_map(|item| item.reclaimable_size).sum();
+    let unsized_items = items
+        .iter()
+        .filter(|item| item.reclaimable_size.is_none())
+        .count();
+
+    let mut sorted: Vec<&ScanItem> = items.iter().collect();
+    sorted.sort_by_key(|item| std::cmp::Reverse(item.reclaimable_size));
+
+    let deleted = cleanups.iter().filter(|entry| entry.success);
+    let deleted_count = deleted.clone().count();
+    let freed_bytes: u64 = deleted.filter_map(|entry| entry.bytes).sum();
+    let failed_count = cleanups.len() - deleted_count;
+    let recent: Vec<&DeleteHistoryEntry> = cleanups.iter().rev().take(REPORT_CLEANUPS).collect();
+
+    match format {
+        ReportFormat::Markdown => {
+            let mut out = format!(
+                "# node_modules report\n\n\
+                 Generated: {}\n\n\
+                 - node_modules found: {}\n\
+                 - Reclaimable space: {}\n",
+                generated,
+                items.len(),
+                format_bytes(total_bytes)
+            );
+            if unsized_items > 0 {
+                out.push_str(&format!("- Items without size: {}\n", unsized_items));
+            }
+
+            out.push_str("\n| Project | Size | Tags / note |\n|---|---:|---|\n");
+            for item in sorted {
+                out.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    escape_markdown_cell(&item.project_path),
+                    item.reclaimable_size
+                        .map(format_bytes)
+                        .unwrap_or_else(|| "-".to_string()),
+                    escape_markdown_cell(&annotation_text(item))
+                ));
+            }
+
+            out.push_str(&format!(
+                "\n## Cleanups\n\n\
+                 - Folders deleted: {}\n\
+                 - Space freed: {}\n\
+                 - Failed deletes: {}\n",
+                deleted_count,
+                format_bytes(freed_bytes),
+                failed_count
+            ));
+            if !recent.is_empty() {
+                out.push_str(&format!(
+                    "\nLatest {}:\n\n| Date | Folder | Freed | Result |\n|---|---|---:|---|\n",
+                    recent.len()
+                ));
+                for entry in recent {
+                    out.push_str(&format!(
+                        "| {} | {} | {} | {} |\n",
+                        cleanup_date(entry),
+                        escape_markdown_cell(&entry.path),
+                        cleanup_bytes(entry),
+                        escape_markdown_cell(&cleanup_outcome(entry))
+                    ));
+                }
+            }
+            out
+        }
+        ReportFormat::Html => {
+            let mut rows = String::new();
+            for item in sorted {
+                rows.push_str(&format!(
+                    "<tr><td>{}</td><td style=\"text-align:right\">{}</td><td>{}</td></tr>\n",
+                    escape_html(&item.project_path),
+                    item.reclaimable_size
+                        .map(format_bytes)
+                        .unwrap_or_else(|| "-".to_string()),
+                    escape_html(&annotation_text(item))
+                ));
+            }
+
+            let mut cleanup_rows = String::new();
+            for entry in &recent {
+                cleanup_rows.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td style=\"text-align:right\">{}</td><td>{}</td></tr>\n",
+                    escape_html(&cleanup_date(entry)),
+                    escape_html(&entry.path),
+                    cleanup_bytes(entry),
+                    escape_html(&cleanup_outcome(entry))
+                ));
+            }
+            let cleanup_table = if recent.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "<p>Latest {}:</p>\n\
+                     <table>\n<tr><th>Date</th><th>Folder</th><th>Freed</th><th>Result</th></tr>\n{}</table>\n",
+                    recent.len(),
+                    cleanup_rows
+                )
+            };
+
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+                 <title>node_modules report {generated}</title>\n</head>\n<body>\n\
+                 <h1>node_modules report</h1>\n\
+                 <p>Generated: {generated}</p>\n\
+                 <ul>\n<li>node_modules found: {count}</li>\n<li>Reclaimable space: {total}</li>\n\
+                 <li>Items without size: {unsized_items}</li>\n</ul>\n\
+                 <table>\n<tr><th>Project</th><th>Size</th><th>Tags / note</th></tr>\n{rows}</table>\n\
+                 <h2>Cleanups</h2>\n\
+                 <ul>\n<li>Folders deleted: {deleted_count}</li>\n<li>Space freed: {freed}</li>\n\
+                 <li>Failed deletes: {failed_count}</li>\n</ul>\n\
+                 {cleanup_table}\
+                 </body>\n</html>\n",
+                generated = escape_html(&generated),
+                count = items.len(),
+                total = format_bytes(total_bytes),
+                unsized_items = unsized_items,
+                rows = rows,
+                deleted_count = deleted_count,
+                freed = format_bytes(freed_bytes),
+                failed_count = failed_count,
+                cleanup_table = cleanup_table
+            )
+        }
+    }
+}
+
+// Writes the report on `items` and the delete history to `folder`
+pub(crate) fn write_report(
+    app: &AppHandle,
+    items: &[ScanItem],
+    folder: &str,
+    format: ReportFormat,
+) -> Result<PathBuf, String> {
+    let folder = PathBuf::from(folder);
+    fs::create_dir_all(&folder).map_err(|e| format!("Failed to create report folder: {}", e))?;
+
+    let extension = match format {
+        ReportFormat::Markdown => "md",
+        ReportFormat::Html => "html",
+    };
+    let file_name = format!(
+        "nodemodules-report-{}.{}",
+        Local::now().format("%Y-%m-%d"),
+        extension
+    );
+    let path = folder.join(file_name);
+
+    let cleanups = delete_history::read_entries(delete_history::history_path(app)?);
+    fs::write(&path, render_report(items, &cleanups, format))
+        .map_err(|e| format!("Failed to write report: {}", e))?;
+
+    Ok(path)
+}
+
+// A compact table of the selection, meant for pasting into a chat
+pub(crate) fn render_table(items: &[ScanItem], format: TableFormat) -> String {
+    let mut sorted: Vec<&ScanItem> = items.iter().collect();
+    sorted.sort_by_key(|item| std::cmp::Reverse(item.size));
+
+    let header = ["Project", "Size", "Tags / note"];
+    let rows: Vec<[String; 3]> = sorted
+        .iter()
+        .map(|item| {
+            [
+                item.project_path.clone(),
+                item.size
+                    .map(format_bytes)
+                    .unwrap_or_else(|| "?".to_string()),
+                annotation_text(item),
+            ]
+        })
+        .collect();
+    let total_bytes: u64 = items.iter().filter_map(|item| item.size).sum();
+    let summary = format!(
+        "{} node_modules, {} total",
+        items.len(),
+        format_bytes(total_bytes)
+    );
+
+    match format {
+        TableFormat::Markdown => {
+            let mut out = format!("| {} |\n|---|---:|---|\n", header.join(" | "));
+            for row in &rows {
+                let cells: Vec<String> =
+                    row.iter().map(|cell| escape_markdown_cell(cell)).collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+            out.push_str(&format!("\n{}\n", summary));
+            out
+        }
+        TableFormat::Plain => {
+            let mut widths = header.map(|title| title.chars().count());
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+
+            // Sizes are right-aligned so their units line up
+            let line = |cells: [&str; 3]| {
+                format!(
+                    "{:<w0$}  {:>w1$}  {}",
+                    cells[0],
+                    cells[1],
+                    cells[2],
+                    w0 = widths[0],
+                    w1 = widths[1]
+                )
+                .trim_end()
+                .to_string()
+            };
+
+            let mut out = line(header) + "\n";
+            out.push_str(&line([
+                &"-".repeat(widths[0]),
+                &"-".repeat(widths[1]),
+                &"-".repeat(widths[2]),
+            ]));
+            out.push('\n');
+            for row in &rows {
+                out.push_str(&line([&row[0], &row[1], &row[2]]));
+                out.push('\n');
+            }
+            out.push_str(&format!("\n{}\n", summary));
+            out
+        }
+    }
+}
+
+// Returns the copied text so the UI can show it as well
+#[tauri::command]
+pub async fn copy_selection_table(
+    annotations: tauri::State<'_, ProjectAnnotations>,
+    mut items: Vec<ScanItem>,
+    format: TableFormat,
+) -> Result<String, Error> {
+    annotations.apply(&mut items);
+    let table = render_table(&items, format);
+
+    let text = table.clone();
+    tokio::task::spawn_blocking(move || clipboard::write_text(&text))
+        .await
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))??;
+    Ok(table)
+}
+
+#[tauri::command]
+pub async fn generate_report(
+    app: AppHandle,
+    annotations: tauri::State<'_, ProjectAnnotations>,
+    mut items: Vec<ScanItem>,
+    folder: String,
+    format: ReportFormat,
+) -> Result<String, Error> {
+    // Use the latest tags and notes rather than whatever the UI held on to
+    annotations.apply(&mut items);
+    write_report(&app, &items, &folder, format)
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(Error::from)
+}
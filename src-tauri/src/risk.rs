@@ -0,0 +1,262 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use nodemodules_cleaner_core::artifacts::{self, ArtifactKind};
+
+use crate::{error::Error, git_status, project_last_modified, workspaces, ScanItem};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskSignalKind {
+    RecentActivity,
+    GitDirty,
+    RunningProcess,
+    Monorepo,
+    // A JavaScript install with no package.json next to it any more;
+    // nothing would reinstall it
+    Orphaned,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RiskSignal {
+    pub kind: RiskSignalKind,
+    // Points added to the score; negative signals lower it
+    pub weight: i32,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Risk {
+    // 0 (safe to delete) to 100
+    pub score: u8,
+    pub level: RiskLevel,
+    pub signals: Vec<RiskSignal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ItemRisk {
+    pub node_modules_path: String,
+    #[serde(flatten)]
+    pub risk: Risk,
+}
+
+fn level(score: u8) -> RiskLevel {
+    match score {
+        0..25 => RiskLevel::Low,
+        25..50 => RiskLevel::Medium,
+        _ => RiskLevel::High,
+    }
+}
+
+// Working directories of running processes, where the platform exposes them
+pub fn process_working_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        let own_pid = std::process::id().to_string();
+        std::fs::read_dir("/proc")
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|entry| {
+                        let name = entry.file_name().to_string_lossy().to_string();
+                        name.chars().all(|c| c.is_ascii_digit()) && name != own_pid
+                    })
+                    .filter_map(|entry| std::fs::read_link(entry.path().join("cwd")).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        // -Fn prints one "n<path>" line per cwd
+        std::process::Command::new("lsof")
+            .args(["-a", "-d", "cwd", "-Fn"])
+            .output()
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .filter_map(|line| line.strip_prefix('n'))
+                    .map(PathBuf::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Reading another process's cwd on Windows means poking at its PEB;
+    // not worth it for a hint
+    #[cfg(target_os = "windows")]
+    {
+        Vec::new()
+    }
+}
+
+fn activity_signal(last_modified: Option<SystemTime>) -> Option<RiskSignal> {
+    let age = SystemTime::now()
+        .duration_since(last_modified?)
+        .unwrap_or_default();
+    let days = age.as_secs() / DAY.as_secs();
+    let weight = match days {
+        0..7 => 40,
+        7..30 => 20,
+        _ => return None,
+    };
+    Some(RiskSignal {
+        kind: RiskSignalKind::RecentActivity,
+        weight,
+        detail: format!("Files changed {} day(s) ago", days),
+    })
+}
+
+// Scores a project from what the caller already looked up about it; only
+// the package.json check touches the disk
+pub fn assess(
+    project: &Path,
+    artifact_type: ArtifactKind,
+    last_modified: Option<SystemTime>,
+    git: Option<git_status::GitStatus>,
+    workspace_root: Option<&Path>,
+    working_dirs: &[PathBuf],
+) -> Risk {
+    let mut signals: Vec<RiskSignal> = activity_signal(last_modified).into_iter().collect();
+
+    if let Some(status) = git.filter(|status| status.is_dirty()) {
+        signals.push(RiskSignal {
+            kind: RiskSignalKind::GitDirty,
+            weight: 25,
+            detail: format!(
+                "{} uncommitted change(s), {} unpushed commit(s)",
+                status.changes, status.unpushed
+            ),
+        });
+    }
+
+    let processes = working_dirs
+        .iter()
+        .filter(|dir| dir.starts_with(project))
+        .count();
+    if processes > 0 {
+        signals.push(RiskSignal {
+            kind: RiskSignalKind::RunningProcess,
+            weight: 40,
+            detail: format!("{} running process(es) work in this project", processes),
+        });
+    }
+
+    if let Some(root) = workspace_root {
+        let detail = if root == project {
+            "Workspace root; its packages share this node_modules".to_string()
+        } else {
+            format!("Part of the workspace at {}", root.display())
+        };
+        signals.push(RiskSignal {
+            kind: RiskSignalKind::Monorepo,
+            weight: 15,
+            detail,
+        });
+    }
+
+    // Other toolchains' folders say nothing about package.json
+    let installed_from_package_json = matches!(
+        artifact_type,
+        ArtifactKind::NodeModules | ArtifactKind::YarnCache | ArtifactKind::YarnUnplugged
+    );
+    if installed_from_package_json && !project.join("package.json").is_file() {
+        signals.push(RiskSignal {
+            kind: RiskSignalKind::Orphaned,
+            weight: -20,
+            detail: "No package.json; the project no longer uses it".to_string(),
+        });
+    }
+
+    let score = signals
+        .iter()
+        .map(|signal| signal.weight)
+        .sum::<i32>()
+        .clamp(0, 100) as u8;
+    Risk {
+        score,
+        level: level(score),
+        signals,
+    }
+}
+
+async fn assess_project(
+    project: PathBuf,
+    artifact_type: ArtifactKind,
+    working_dirs: Arc<Vec<PathBuf>>,
+) -> Risk {
+    let last_modified = project_last_modified(&project).await;
+    tokio::task::spawn_blocking(move || {
+        let git = git_status::status(&project);
+        let workspace_root = workspaces::workspace_root(&project);
+        assess(
+            &project,
+            artifact_type,
+            last_modified,
+            git,
+            workspace_root.as_deref(),
+            &working_dirs,
+        )
+    })
+    .await
+    .unwrap_or_default()
+}
+
+async fn assess_item(item: ScanItem, working_dirs: Arc<Vec<PathBuf>>) -> ItemRisk {
+    let project = PathBuf::from(&item.project_path);
+    ItemRisk {
+        risk: assess_project(project, item.artifact_type, working_dirs).await,
+        node_modules_path: item.node_modules_path,
+    }
+}
+
+// Scores an artifact folder no scan described, e.g. one picked after the
+// scan cache was cleared
+pub async fn assess_path(path: &str, working_dirs: Arc<Vec<PathBuf>>) -> Option<Risk> {
+    let path = Path::new(path);
+    let project = artifacts::project_of(path)?.to_path_buf();
+    let artifact_type = artifacts::kind_of(path).unwrap_or_default();
+    Some(assess_project(project, artifact_type, working_dirs).await)
+}
+
+// Scores items again, e.g. ones loaded from a results file or whose
+// projects changed since the scan. Scans score every item as they find it.
+#[tauri::command]
+pub async fn score_risk(items: Vec<ScanItem>) -> Result<Vec<ItemRisk>, Error> {
+    let working_dirs = tokio::task::spawn_blocking(process_working_dirs)
+        .await
+        .map(Arc::new)
+        .map_err(|e| format!("Failed to list running processes: {}", e))?;
+
+    let mut risks: Vec<Option<ItemRisk>> = items.iter().map(|_| None).collect();
+    let mut checks = tokio::task::JoinSet::new();
+    for (index, item) in items.into_iter().enumerate() {
+        let working_dirs = working_dirs.clone();
+        checks.spawn(async move { (index, assess_item(item, working_dirs).await) });
+    }
+
+    while let Some(result) = checks.join_next().await {
+        if let Ok((index, risk)) = result {
+            risks[index] = Some(risk);
+        }
+    }
+
+    Ok(risks.into_iter().flatten().collect())
+}
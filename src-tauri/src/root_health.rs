@@ -0,0 +1,218 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, legitimacy, long_paths};
+
+// A healthy local disk answers a directory listing in milliseconds
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(3);
+const SLOW_AFTER: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RootWarningKind {
+    Missing,
+    NotADirectory,
+    Unreadable,
+    Empty,
+    CloudPlaceholder,
+    NetworkShare,
+    Slow,
+    Unresponsive,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RootWarning {
+    pub kind: RootWarningKind,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RootHealth {
+    pub root: String,
+    // False when the scan won't find anything under this root at all
+    pub scannable: bool,
+    pub warnings: Vec<RootWarning>,
+}
+
+fn warning(kind: RootWarningKind, message: impl Into<String>) -> RootWarning {
+    RootWarning {
+        kind,
+        message: message.into(),
+    }
+}
+
+// Name of the sync client when `path` is a cloud-backed folder whose files
+// may only be downloaded on access
+fn cloud_provider(path: &Path, metadata: &fs::Metadata) -> Option<String> {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+
+        // OFFLINE, RECALL_ON_OPEN, RECALL_ON_DATA_ACCESS (OneDrive, Dropbox, ...)
+        const PLACEHOLDER_ATTRIBUTES: u32 = 0x0000_1000 | 0x0004_0000 | 0x0040_0000;
+        if metadata.file_attributes() & PLACEHOLDER_ATTRIBUTES != 0 {
+            return Some("cloud sync".to_string());
+        }
+        let _ = path;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = metadata;
+        let path = path.to_string_lossy();
+        if path.contains("/Library/Mobile Documents") {
+            return Some("iCloud Drive".to_string());
+        }
+        if path.contains("/Library/CloudStorage/") {
+            return Some("File Provider".to_string());
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = metadata;
+        let fs_type = crate::snapshots::filesystem_type(path)?;
+        if matches!(
+            fs_type.as_str(),
+            "fuse.rclone"
+                | "fuse.gvfsd-fuse"
+                | "fuse.onedriver"
+                | "fuse.google-drive-ocamlfuse"
+                | "fuse.s3fs"
+        ) {
+            return Some(fs_type.trim_start_matches("fuse.").to_string());
+        }
+    }
+
+    None
+}
+
+fn probe_root(path: &Path) -> Vec<RootWarning> {
+    let fs_path = long_paths::extended(path);
+
+    let metadata = match fs::metadata(&fs_path) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            return vec![warning(
+                RootWarningKind::Missing,
+                format!("Folder is not accessible: {}", e),
+            )]
+        }
+    };
+    if !metadata.is_dir() {
+        return vec![warning(
+            RootWarningKind::NotADirectory,
+            "Path is not a folder",
+        )];
+    }
+
+    let mut warnings = Vec::new();
+    match fs::read_dir(&fs_path) {
+        Ok(mut entries) => {
+            if entries.next().is_none() {
+                warnings.push(warning(RootWarningKind::Empty, "Folder is empty"));
+            }
+        }
+        Err(e) => warnings.push(warning(
+            RootWarningKind::Unreadable,
+            format!("Folder can't be read: {}", e),
+        )),
+    }
+
+    if let Some(provider) = cloud_provider(path, &metadata) {
+        warnings.push(warning(
+            RootWarningKind::CloudPlaceholder,
+            format!(
+                "Folder is synced by {}; files that are only in the cloud may be downloaded or skipped",
+                provider
+            ),
+        ));
+    }
+
+    if let Some(share) = legitimacy::network_share(path) {
+        warnings.push(warning(
+            RootWarningKind::NetworkShare,
+            format!(
+                "Folder is on the network share {}; scanning it is much slower than a local disk",
+                share
+            ),
+        ));
+    }
+
+    warnings
+}
+
+pub async fn check_root(root: String) -> RootHealth {
+    let path = PathBuf::from(&root);
+    let started = Instant::now();
+
+    let probe = tokio::task::spawn_blocking(move || probe_root(&path));
+    let mut warnings = match tokio::time::timeout(RESPONSE_TIMEOUT, probe).await {
+        Ok(Ok(warnings)) => warnings,
+        Ok(Err(e)) => vec![warning(
+            RootWarningKind::Unreadable,
+            format!("Health check failed: {}", e),
+        )],
+        Err(_) => vec![warning(
+            RootWarningKind::Unresponsive,
+            format!(
+                "Device did not respond within {} seconds; the scan may hang here",
+                RESPONSE_TIMEOUT.as_secs()
+            ),
+        )],
+    };
+
+    let elapsed = started.elapsed();
+    if elapsed > SLOW_AFTER && elapsed < RESPONSE_TIMEOUT {
+        warnings.push(warning(
+            RootWarningKind::Slow,
+            format!(
+                "Device took {} ms to list this folder; expect a slow scan",
+                elapsed.as_millis()
+            ),
+        ));
+    }
+
+    let scannable = !warnings.iter().any(|warning| {
+        matches!(
+            warning.kind,
+            RootWarningKind::Missing
+                | RootWarningKind::NotADirectory
+                | RootWarningKind::Unreadable
+                | RootWarningKind::Unresponsive
+        )
+    });
+
+    RootHealth {
+        root,
+        scannable,
+        warnings,
+    }
+}
+
+pub async fn check_roots(roots: &[String]) -> Vec<RootHealth> {
+    let mut checks = tokio::task::JoinSet::new();
+    for (index, root) in roots.iter().enumerate() {
+        let root = root.clone();
+        checks.spawn(async move { (index, check_root(root).await) });
+    }
+
+    let mut results = Vec::with_capacity(roots.len());
+    while let Some(result) = checks.join_next().await {
+        if let Ok(health) = result {
+            results.push(health);
+        }
+    }
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, health)| health).collect()
+}
+
+#[tauri::command]
+pub async fn check_scan_roots(roots: Vec<String>) -> Result<Vec<RootHealth>, Error> {
+    Ok(check_roots(&roots).await)
+}
@@ -0,0 +1,228 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use notify::{
+    event::{ModifyKind, RenameMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::{error::Error, long_paths};
+
+// Linux spends an inotify watch on every folder below a root, and the default
+// per-user limit can be as low as 8192
+const MAX_WATCHED_DIRECTORIES: usize = 8_000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NodeModulesChange {
+    pub project_path: String,
+    pub node_modules_path: String,
+}
+
+// Watcher for the roots of the last watch_roots call; dropping it stops watching
+#[derive(Default)]
+pub struct RootWatcher(Mutex<Option<RecommendedWatcher>>);
+
+fn is_node_modules(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "node_modules")
+}
+
+// Changes inside an install are the package manager at work, not a new install
+fn is_inside_node_modules(path: &Path) -> bool {
+    path.parent().is_some_and(|parent| {
+        parent
+            .components()
+            .any(|component| component.as_os_str() == "node_modules")
+    })
+}
+
+fn emit_change(app: &AppHandle, event: &str, node_modules: &Path) {
+    let change = NodeModulesChange {
+        project_path: node_modules
+            .parent()
+            .map(|project| project.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        node_modules_path: node_modules.to_string_lossy().to_string(),
+    };
+    if let Err(e) = app.emit(event, change) {
+        eprintln!("Failed to emit {}: {}", event, e);
+    }
+}
+
+fn appeared(app: &AppHandle, path: &Path) {
+    if is_inside_node_modules(path) {
+        return;
+    }
+    if is_node_modules(path) {
+        if long_paths::extended(path).is_dir() {
+            emit_change(app, "node_modules_added", path);
+        }
+        return;
+    }
+
+    // A project moved or copied in shows up as a single folder
+    let node_modules = path.join("node_modules");
+    if long_paths::extended(&node_modules).is_dir() {
+        emit_change(app, "node_modules_added", &node_modules);
+    }
+}
+
+fn disappeared(app: &AppHandle, path: &Path) {
+    if is_node_modules(path) && !is_inside_node_modules(path) {
+        emit_change(app, "node_modules_removed", path);
+    }
+}
+
+fn handle_event(app: &AppHandle, event: Event) {
+    match event.kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            for path in &event.paths {
+                appeared(app, path);
+            }
+        }
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            for path in &event.paths {
+                disappeared(app, path);
+            }
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+            if let [from, to] = event.paths.as_slice() {
+                disappeared(app, from);
+                appeared(app, to);
+            }
+        }
+        // FSEvents doesn't say which side of a rename a path is on
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            for path in &event.paths {
+                if path.exists() {
+                    appeared(app, path);
+                } else {
+                    disappeared(app, path);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// A whole drive or filesystem is far too much to watch recursively
+fn is_filesystem_root(root: &Path) -> bool {
+    let Ok(resolved) = fs::canonicalize(long_paths::extended(root)) else {
+        return false;
+    };
+    let resolved = long_paths::plain(&resolved);
+    if resolved.parent().is_none() {
+        return true;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if nodemodules_cleaner_core::snapshots::mount_points()
+            .iter()
+            .any(|mount_point| Path::new(mount_point) == resolved)
+        {
+            return true;
+        }
+    }
+
+    // Other drives are mounted under /Volumes
+    #[cfg(target_os = "macos")]
+    {
+        if resolved.parent() == Some(Path::new("/Volumes")) {
+            return true;
+        }
+    }
+
+    false
+}
+
+// Folders a recursive watch on the roots would cover, installs included;
+// stops counting once past `limit`
+fn count_directories(roots: &[String], limit: usize) -> usize {
+    let mut pending: Vec<PathBuf> = roots.iter().map(PathBuf::from).collect();
+    let mut count = 0;
+    while let Some(dir) = pending.pop() {
+        count += 1;
+        if count > limit {
+            break;
+        }
+        let Ok(entries) = fs::read_dir(long_paths::extended(&dir)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // Links aren't followed by the watcher either
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                pending.push(entry.path());
+            }
+        }
+    }
+    count
+}
+
+// Watches the roots for node_modules folders appearing or disappearing and
+// emits `node_modules_added` / `node_modules_removed`. Replaces the roots
+// of any earlier call; an empty list stops watching.
+#[tauri::command]
+pub async fn watch_roots(
+    roots: Vec<String>,
+    app: AppHandle,
+    watcher: tauri::State<'_, RootWatcher>,
+) -> Result<(), Error> {
+    // Stop the previous watcher first so its roots don't keep reporting
+    let previous = watcher
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to update watched roots: {}", e))?
+        .take();
+    drop(previous);
+    if roots.is_empty() {
+        return Ok(());
+    }
+
+    if let Some(root) = roots
+        .iter()
+        .find(|root| is_filesystem_root(Path::new(root)))
+    {
+        return Err(Error::InvalidInput(format!(
+            "{} is a whole drive; pick a folder on it to watch instead",
+            root
+        )));
+    }
+
+    // Recursive watches walk the whole tree up front on some platforms
+    let started = tokio::task::spawn_blocking(move || {
+        if count_directories(&roots, MAX_WATCHED_DIRECTORIES) > MAX_WATCHED_DIRECTORIES {
+            return Err(Error::InvalidInput(format!(
+                "The selected folders hold more than {} folders, too many to watch; pick smaller ones",
+                MAX_WATCHED_DIRECTORIES
+            )));
+        }
+
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+                Ok(event) => handle_event(&app, event),
+                Err(e) => eprintln!("File watcher error: {}", e),
+            })
+            .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+
+        for root in &roots {
+            watcher
+                .watch(Path::new(root), RecursiveMode::Recursive)
+                .map_err(|e| format!("Failed to watch {}: {}", root, e))?;
+        }
+        Ok::<_, Error>(watcher)
+    })
+    .await
+    .map_err(|e| format!("Failed to start file watcher: {}", e))??;
+
+    let mut current = watcher
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to update watched roots: {}", e))?;
+    *current = Some(started);
+    Ok(())
+}
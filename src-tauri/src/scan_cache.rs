@@ -0,0 +1,139 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use nodemodules_cleaner_core::{scan::PreviousScan, sizing::DirectorySize};
+
+use crate::{error::Error, ScanItem};
+
+pub use nodemodules_cleaner_core::traversal::CachedDirectory;
+
+const CACHE_FILE: &str = "scan_cache.json";
+const CACHE_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CacheData {
+    pub version: u32,
+    pub directories: HashMap<String, CachedDirectory>,
+    // Keyed by node_modules path
+    pub items: HashMap<String, ScanItem>,
+}
+
+impl PreviousScan for CacheData {
+    fn directory(&self, path: &str) -> Option<CachedDirectory> {
+        self.directories.get(path).cloned()
+    }
+
+    // Results saved before file counts were kept are measured again
+    fn size(&self, node_modules: &str) -> Option<DirectorySize> {
+        let item = self.items.get(node_modules)?;
+        Some(DirectorySize {
+            total: item.size?,
+            reclaimable: item.reclaimable_size.or(item.size)?,
+            files: item.file_count?,
+        })
+    }
+}
+
+pub struct ScanCache {
+    path: Option<PathBuf>,
+    data: Mutex<Arc<CacheData>>,
+}
+
+fn is_under(path: &str, roots: &[String]) -> bool {
+    roots
+        .iter()
+        .any(|root| Path::new(path).starts_with(Path::new(root)))
+}
+
+impl ScanCache {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = app
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|dir| dir.join(CACHE_FILE));
+        let data = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str::<CacheData>(&contents).ok())
+            // An older layout is simply rebuilt by the next full scan
+            .filter(|data| data.version == CACHE_VERSION)
+            .unwrap_or_default();
+
+        ScanCache {
+            path,
+            data: Mutex::new(Arc::new(data)),
+        }
+    }
+
+    pub fn snapshot(&self) -> Arc<CacheData> {
+        self.data
+            .lock()
+            .map(|data| data.clone())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, data: &CacheData) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| "Failed to resolve app data directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        }
+
+        let contents = serde_json::to_string(data)
+            .map_err(|e| format!("Failed to serialize scan cache: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to save scan cache: {}", e))
+    }
+
+    // Merges what a scan saw. `complete_roots` were walked to the end, so
+    // anything cached below them that the scan didn't see again is gone.
+    pub fn update(
+        &self,
+        complete_roots: &[String],
+        directories: HashMap<String, CachedDirectory>,
+        items: &[ScanItem],
+    ) {
+        let Ok(mut data) = self.data.lock() else {
+            return;
+        };
+
+        let mut updated = CacheData::clone(&data);
+        updated.version = CACHE_VERSION;
+        updated
+            .directories
+            .retain(|path, _| !is_under(path, complete_roots));
+        updated
+            .items
+            .retain(|path, _| !is_under(path, complete_roots));
+        updated.directories.extend(directories);
+        for item in items {
+            updated
+                .items
+                .insert(item.node_modules_path.clone(), item.clone());
+        }
+
+        if let Err(e) = self.save(&updated) {
+            eprintln!("{}", e);
+        }
+        *data = Arc::new(updated);
+    }
+}
+
+#[tauri::command]
+pub async fn clear_scan_cache(cache: tauri::State<'_, ScanCache>) -> Result<(), Error> {
+    let mut data = cache
+        .data
+        .lock()
+        .map_err(|e| format!("Failed to read scan cache: {}", e))?;
+    *data = Arc::new(CacheData::default());
+    Ok(cache.save(&data)?)
+}
@@ -0,0 +1,99 @@
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+const TOTALS_FILE: &str = "scan_totals.json";
+// Distinct root selections remembered; the oldest is dropped beyond this
+const MAX_ROOT_SETS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct RecordedTotal {
+    folders: usize,
+    recorded_at: String,
+}
+
+// Folder counts of past complete scans, keyed by the set of roots scanned
+pub struct ScanTotals {
+    path: Option<PathBuf>,
+    totals: Mutex<HashMap<String, RecordedTotal>>,
+}
+
+fn key(roots: &[String]) -> String {
+    let mut roots = roots.to_vec();
+    roots.sort();
+    roots.dedup();
+    roots.join("\n")
+}
+
+impl ScanTotals {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = app
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|dir| dir.join(TOTALS_FILE));
+        let totals = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        ScanTotals {
+            path,
+            totals: Mutex::new(totals),
+        }
+    }
+
+    fn save(&self, totals: &HashMap<String, RecordedTotal>) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| "Failed to resolve app data directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        }
+
+        let contents = serde_json::to_string(totals)
+            .map_err(|e| format!("Failed to serialize scan totals: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to save scan totals: {}", e))
+    }
+
+    pub fn expected(&self, roots: &[String]) -> Option<usize> {
+        self.totals
+            .lock()
+            .ok()?
+            .get(&key(roots))
+            .map(|total| total.folders)
+    }
+
+    // Only complete scans are recorded; a partial count would undershoot
+    pub fn record(&self, roots: &[String], folders: usize) {
+        let Ok(mut totals) = self.totals.lock() else {
+            return;
+        };
+
+        totals.insert(
+            key(roots),
+            RecordedTotal {
+                folders,
+                recorded_at: Utc::now().to_rfc3339(),
+            },
+        );
+        while totals.len() > MAX_ROOT_SETS {
+            let oldest = totals
+                .iter()
+                .min_by(|a, b| a.1.recorded_at.cmp(&b.1.recorded_at))
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                totals.remove(&oldest);
+            }
+        }
+
+        if let Err(e) = self.save(&totals) {
+            eprintln!("{}", e);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, ScanItem};
+
+const FORMAT_NAME: &str = "nmscan";
+const EXTENSION: &str = "nmscan";
+// Bump when a change would make older builds misread the file
+const FORMAT_VERSION: u32 = 1;
+const COMPRESSION_LEVEL: i32 = 9;
+// Imported files come from other machines; don't inflate arbitrarily large payloads
+const MAX_DECOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanSnapshot {
+    pub roots: Vec<String>,
+    pub items: Vec<ScanItem>,
+    pub folders_scanned: Option<usize>,
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+// On-disk layout of a .nmscan file: zstd-compressed JSON of this struct
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanFile {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub platform: String,
+    pub machine: Option<String>,
+    #[serde(flatten)]
+    pub snapshot: ScanSnapshot,
+}
+
+fn machine_name() -> Option<String> {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .filter(|name| !name.is_empty())
+}
+
+fn with_extension(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    if path
+        .extension()
+        .is_some_and(|extension| extension == EXTENSION)
+    {
+        path
+    } else {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".");
+        name.push(EXTENSION);
+        PathBuf::from(name)
+    }
+}
+
+fn write_scan_file(path: &Path, snapshot: ScanSnapshot) -> Result<(), String> {
+    let file = ScanFile {
+        format: FORMAT_NAME.to_string(),
+        version: FORMAT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        platform: std::env::consts::OS.to_string(),
+        machine: machine_name(),
+        snapshot,
+    };
+
+    let json = serde_json::to_vec(&file).map_err(|e| format!("Failed to serialize scan: {}", e))?;
+    let compressed = zstd::encode_all(json.as_slice(), COMPRESSION_LEVEL)
+        .map_err(|e| format!("Failed to compress scan: {}", e))?;
+    std::fs::write(path, compressed).map_err(|e| format!("Failed to write scan file: {}", e))
+}
+
+fn read_scan_file(path: &Path) -> Result<ScanFile, String> {
+    let compressed =
+        std::fs::File::open(path).map_err(|e| format!("Failed to open scan file: {}", e))?;
+    let decoder = zstd::stream::read::Decoder::new(compressed)
+        .map_err(|e| format!("Failed to read scan file: {}", e))?;
+
+    let mut json = Vec::new();
+    decoder
+        .take(MAX_DECOMPRESSED_BYTES + 1)
+        .read_to_end(&mut json)
+        .map_err(|e| format!("Not a valid .nmscan file: {}", e))?;
+    if json.len() as u64 > MAX_DECOMPRESSED_BYTES {
+        return Err("Scan file is too large to import".to_string());
+    }
+
+    // Check the header first so a newer file gets a clear message instead of
+    // a field-level parse error
+    let header: serde_json::Value =
+        serde_json::from_slice(&json).map_err(|e| format!("Not a valid .nmscan file: {}", e))?;
+    if header.get("format").and_then(|format| format.as_str()) != Some(FORMAT_NAME) {
+        return Err("Not a valid .nmscan file".to_string());
+    }
+    let version = header
+        .get("version")
+        .and_then(|version| version.as_u64())
+        .unwrap_or(0);
+    if version > FORMAT_VERSION as u64 {
+        return Err(format!(
+            "This scan was saved by a newer version of the app (format {}); please update to open it",
+            version
+        ));
+    }
+
+    serde_json::from_value(header).map_err(|e| format!("Failed to read scan file: {}", e))
+}
+
+#[tauri::command]
+pub async fn export_scan(path: String, snapshot: ScanSnapshot) -> Result<String, Error> {
+    let path = with_extension(&path);
+
+    tokio::task::spawn_blocking(move || {
+        write_scan_file(&path, snapshot)?;
+        Ok(path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Export failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn import_scan(path: String) -> Result<ScanFile, Error> {
+    tokio::task::spawn_blocking(move || read_scan_file(Path::new(&path)))
+        .await
+        .map_err(|e| format!("Import failed: {}", e))?
+        .map_err(Error::from)
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{delete_history, error::Error, ScanItem};
+
+const HISTORY_FILE: &str = "scan_history.jsonl";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanHistoryEntry {
+    pub timestamp: String,
+    pub roots: Vec<String>,
+    pub items_found: usize,
+    // Over the items that were sized
+    pub total_size: u64,
+    pub reclaimable_size: u64,
+    // Stopped early by a cancel, the result cap or a lost drive
+    pub partial: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MonthStatistics {
+    // "2024-05"
+    pub month: String,
+    pub scans_run: usize,
+    // Largest reclaimable total a single scan found that month
+    pub peak_reclaimable: u64,
+    pub folders_deleted: usize,
+    pub bytes_reclaimed: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Statistics {
+    pub scans_run: usize,
+    pub folders_deleted: usize,
+    pub bytes_reclaimed: u64,
+    pub first_scan: Option<String>,
+    pub last_scan: Option<ScanHistoryEntry>,
+    // Oldest first, only months with any activity
+    pub months: Vec<MonthStatistics>,
+}
+
+fn history_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(dir.join(HISTORY_FILE))
+}
+
+fn append(app: &AppHandle, entry: &ScanHistoryEntry) -> Result<(), String> {
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize scan history entry: {}", e))?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history_path(app)?)
+        .map_err(|e| format!("Failed to open scan history: {}", e))?;
+
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write scan history: {}", e))
+}
+
+// Logs a finished scan; failing to do so never fails the scan
+pub fn record(app: &AppHandle, roots: &[String], items: &[ScanItem], partial: bool) {
+    let entry = ScanHistoryEntry {
+        timestamp: Utc::now().to_rfc3339(),
+        roots: roots.to_vec(),
+        items_found: items.len(),
+        total_size: items.iter().filter_map(|item| item.size).sum(),
+        reclaimable_size: items
+            .iter()
+            .filter_map(|item| item.reclaimable_size.or(item.size))
+            .sum(),
+        partial,
+    };
+    if let Err(e) = append(app, &entry) {
+        eprintln!("{}", e);
+    }
+}
+
+fn read_entries(path: PathBuf) -> Vec<ScanHistoryEntry> {
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    // A line cut short by a crash is skipped rather than failing the lot
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+// "2024-05-17T..." -> "2024-05"
+fn month_of(timestamp: &str) -> String {
+    timestamp.chars().take(7).collect()
+}
+
+#[tauri::command]
+pub async fn get_statistics(app: AppHandle) -> Result<Statistics, Error> {
+    let scans_path = history_path(&app)?;
+    let deletes_path = delete_history::history_path(&app)?;
+    let (scans, deletes) = tokio::task::spawn_blocking(move || {
+        (
+            read_entries(scans_path),
+            delete_history::read_entries(deletes_path),
+        )
+    })
+    .await
+    .map_err(|e| format!("Failed to read statistics: {}", e))?;
+
+    let mut months: BTreeMap<String, MonthStatistics> = BTreeMap::new();
+    for scan in &scans {
+        let month = months.entry(month_of(&scan.timestamp)).or_default();
+        month.scans_run += 1;
+        month.peak_reclaimable = month.peak_reclaimable.max(scan.reclaimable_size);
+    }
+    let deleted = deletes.iter().filter(|entry| entry.success);
+    for entry in deleted.clone() {
+        let month = months.entry(month_of(&entry.timestamp)).or_default();
+        month.folders_deleted += 1;
+        month.bytes_reclaimed += entry.bytes.unwrap_or(0);
+    }
+
+    Ok(Statistics {
+        scans_run: scans.len(),
+        folders_deleted: deleted.clone().count(),
+        bytes_reclaimed: deleted.filter_map(|entry| entry.bytes).sum(),
+        first_scan: scans.first().map(|scan| scan.timestamp.clone()),
+        last_scan: scans.last().cloned(),
+        months: months
+            .into_iter()
+            .map(|(month, stats)| MonthStatistics { month, ..stats })
+            .collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn clear_scan_history(app: AppHandle) -> Result<(), Error> {
+    match std::fs::remove_file(history_path(&app)?) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to clear scan history: {}", e).into()),
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+};
+
+use serde::Serialize;
+use tauri::{Emitter, Manager};
+
+use nodemodules_cleaner_core::scan::{Found, ScanHost, ScanProgress, ScanRootLost, SizeProgress};
+
+use crate::{
+    annotations, git_status, long_paths, project_metadata, risk, session_exclusions, workspaces,
+    ScanItem, ScanItemFound,
+};
+
+// Fills in what the app knows about each project and, for scans started
+// from the window, streams the scan's events to it
+pub struct AppScanHost {
+    window: Option<tauri::Window>,
+    // Where running processes work, for risk scores; listed on the first find
+    working_dirs: OnceLock<Vec<PathBuf>>,
+}
+
+impl AppScanHost {
+    pub fn new(window: Option<&tauri::Window>) -> Arc<Self> {
+        Arc::new(AppScanHost {
+            window: window.cloned(),
+            working_dirs: OnceLock::new(),
+        })
+    }
+
+    fn emit(&self, event: &str, payload: impl Serialize + Clone) {
+        if let Some(window) = &self.window {
+            if let Err(e) = window.emit(event, payload) {
+                eprintln!("Failed to emit {}: {}", event, e);
+            }
+        }
+    }
+}
+
+impl ScanHost for AppScanHost {
+    type Item = ScanItem;
+
+    fn describe(&self, found: Found) -> ScanItem {
+        // Manifest, workspace and git lookups all touch the disk
+        let git_status = git_status::status(&found.project);
+        let workspace_root = workspaces::workspace_root(&found.project);
+        let risk = risk::assess(
+            &found.project,
+            found.artifact_type,
+            found.last_modified,
+            git_status,
+            workspace_root.as_deref(),
+            self.working_dirs.get_or_init(risk::process_working_dirs),
+        );
+        let metadata = project_metadata::read(&long_paths::extended(&found.project));
+
+        ScanItem {
+            project_path: found.project.to_string_lossy().to_string(),
+            node_modules_path: found.path.to_string_lossy().to_string(),
+            artifact_type: found.artifact_type,
+            size: found.size.map(|size| size.total),
+            reclaimable_size: found.size.map(|size| size.reclaimable),
+            file_count: found.size.map(|size| size.files),
+            size_tier: found.size_tier,
+            tags: Vec::new(),
+            note: None,
+            project_name: metadata.name,
+            project_version: metadata.version,
+            package_manager: metadata.package_manager,
+            last_modified: found
+                .last_modified
+                .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339()),
+            git_dirty: git_status.map(|status| status.is_dirty()),
+            workspace_root: workspace_root.map(|root| root.to_string_lossy().to_string()),
+            risk: Some(risk),
+            nested: found.nested,
+            is_cloud_synced: found.is_cloud_synced,
+            is_protected: found.is_protected,
+            is_pnpm: found.is_pnpm,
+        }
+    }
+
+    // Shows results as they come in instead of only at the end
+    fn found(&self, session_id: &str, item: &ScanItem) {
+        let Some(window) = &self.window else {
+            return;
+        };
+        let hidden = window
+            .try_state::<session_exclusions::SessionExclusions>()
+            .is_some_and(|exclusions| exclusions.is_hidden(item));
+        if hidden {
+            return;
+        }
+
+        let mut found = [item.clone()];
+        if let Some(annotations) = window.try_state::<annotations::ProjectAnnotations>() {
+            annotations.apply(&mut found);
+        }
+        let [item] = found;
+        self.emit(
+            "scan_item_found",
+            ScanItemFound {
+                session_id: session_id.to_string(),
+                item,
+            },
+        );
+    }
+
+    fn progress(&self, progress: ScanProgress) {
+        self.emit("scan_progress", progress);
+    }
+
+    fn size_progress(&self, progress: SizeProgress) {
+        self.emit("size_progress", progress);
+    }
+
+    fn root_lost(&self, lost: ScanRootLost) {
+        self.emit("scan_root_lost", lost);
+    }
+}
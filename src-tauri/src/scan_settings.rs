@@ -0,0 +1,55 @@
+use std::sync::Mutex;
+
+use tauri::AppHandle;
+
+use crate::{error::Error, settings};
+
+pub use nodemodules_cleaner_core::scan_options::ScanOptions;
+
+pub struct ScanSettings(Mutex<ScanOptions>);
+
+impl ScanSettings {
+    pub fn load(app: &AppHandle) -> Self {
+        let options = settings::section::<ScanOptions>(app, "scan")
+            .filter(|options| options.validate().is_ok())
+            .unwrap_or_default();
+        ScanSettings(Mutex::new(options))
+    }
+
+    pub fn current(&self) -> ScanOptions {
+        self.0
+            .lock()
+            .map(|options| options.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn update(&self, app: &AppHandle, options: ScanOptions) -> Result<(), String> {
+        options.validate()?;
+
+        let mut current = self
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to update scan settings: {}", e))?;
+        settings::save_section(app, "scan", &options)?;
+
+        *current = options;
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub async fn get_scan_settings(
+    settings: tauri::State<'_, ScanSettings>,
+) -> Result<ScanOptions, Error> {
+    Ok(settings.current())
+}
+
+#[tauri::command]
+pub async fn set_scan_settings(
+    app: AppHandle,
+    settings: tauri::State<'_, ScanSettings>,
+    options: ScanOptions,
+) -> Result<ScanOptions, Error> {
+    settings.update(&app, options)?;
+    Ok(settings.current())
+}
@@ -0,0 +1,236 @@
+use std::{sync::Mutex, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{error::Error, report, settings, ActiveScans};
+
+// How often the scheduler checks whether a run is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const MAX_INTERVAL_HOURS: u64 = 24 * 90;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Schedule {
+    pub enabled: bool,
+    // Hours between runs; 168 is weekly
+    pub interval_hours: u64,
+    pub roots: Vec<String>,
+    // Reclaimable space is only known when sizes are measured
+    pub include_sizes: bool,
+    // Where each run writes a report, if anywhere
+    pub report_folder: Option<String>,
+    pub report_format: Option<report::ReportFormat>,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Schedule {
+            enabled: false,
+            interval_hours: 24 * 7,
+            roots: Vec::new(),
+            include_sizes: true,
+            report_folder: None,
+            report_format: None,
+        }
+    }
+}
+
+impl Schedule {
+    fn validate(&self) -> Result<(), String> {
+        if self.interval_hours == 0 || self.interval_hours > MAX_INTERVAL_HOURS {
+            return Err(format!(
+                "The schedule interval must be between 1 and {} hours",
+                MAX_INTERVAL_HOURS
+            ));
+        }
+        if self.enabled && self.roots.is_empty() {
+            return Err("A scheduled scan needs at least one folder".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+struct ScheduleState {
+    schedule: Schedule,
+    last_run: Option<String>,
+}
+
+impl ScheduleState {
+    // Never-run schedules are due right away
+    fn next_run(&self) -> Option<DateTime<Utc>> {
+        if !self.schedule.enabled {
+            return None;
+        }
+        let last_run = self
+            .last_run
+            .as_deref()
+            .and_then(|last_run| DateTime::parse_from_rfc3339(last_run).ok());
+        Some(match last_run {
+            Some(last_run) => {
+                last_run.with_timezone(&Utc)
+                    + chrono::Duration::hours(self.schedule.interval_hours as i64)
+            }
+            None => Utc::now(),
+        })
+    }
+
+    pub fn status(&self) -> ScheduleStatus {
+        ScheduleStatus {
+            schedule: self.schedule.clone(),
+            last_run: self.last_run.clone(),
+            next_run: self.next_run().map(|next_run| next_run.to_rfc3339()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleStatus {
+    pub schedule: Schedule,
+    pub last_run: Option<String>,
+    pub next_run: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledScanCompleted {
+    pub roots: Vec<String>,
+    pub node_modules_found: usize,
+    pub reclaimable_bytes: u64,
+    pub report_path: Option<String>,
+}
+
+pub struct Scheduler(Mutex<ScheduleState>);
+
+impl Scheduler {
+    pub fn load(app: &AppHandle) -> Self {
+        let state = settings::section::<ScheduleState>(app, "schedule")
+            .filter(|state| state.schedule.validate().is_ok())
+            .unwrap_or_default();
+        Scheduler(Mutex::new(state))
+    }
+
+    pub fn status(&self) -> ScheduleStatus {
+        self.0
+            .lock()
+            .map(|state| state.status())
+            .unwrap_or_else(|_| ScheduleState::default().status())
+    }
+
+    pub fn update(&self, app: &AppHandle, schedule: Schedule) -> Result<(), String> {
+        schedule.validate()?;
+
+        let mut state = self
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to update schedule: {}", e))?;
+        let updated = ScheduleState {
+            schedule,
+            last_run: state.last_run.clone(),
+        };
+        settings::save_section(app, "schedule", &updated)?;
+        *state = updated;
+        Ok(())
+    }
+
+    // The schedule to run now, if a run is due
+    fn due(&self) -> Option<Schedule> {
+        let state = self.0.lock().ok()?;
+        let next_run = state.next_run()?;
+        (next_run <= Utc::now()).then(|| state.schedule.clone())
+    }
+
+    fn record_run(&self, app: &AppHandle) {
+        let Ok(mut state) = self.0.lock() else {
+            return;
+        };
+        state.last_run = Some(Utc::now().to_rfc3339());
+        if let Err(e) = settings::save_section(app, "schedule", &*state) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+fn scan_running(app: &AppHandle) -> bool {
+    app.try_state::<ActiveScans>()
+        .and_then(|active| active.0.lock().ok().map(|active| !active.is_empty()))
+        .unwrap_or(false)
+}
+
+async fn run(app: &AppHandle, schedule: Schedule) {
+    let results =
+        match crate::run_scheduled_scan(app, &schedule.roots, schedule.include_sizes).await {
+            Ok(results) => results,
+            Err(e) => {
+                eprintln!("Scheduled scan failed: {}", e);
+                return;
+            }
+        };
+
+    let report_path = schedule.report_folder.as_deref().and_then(|folder| {
+        let format = schedule
+            .report_format
+            .unwrap_or(report::ReportFormat::Markdown);
+        match report::write_report(app, &results.items, folder, format) {
+            Ok(path) => Some(path.to_string_lossy().to_string()),
+            Err(e) => {
+                eprintln!("{}", e);
+                None
+            }
+        }
+    });
+
+    let completed = ScheduledScanCompleted {
+        roots: schedule.roots,
+        node_modules_found: results.items.len(),
+        reclaimable_bytes: results.summary.reclaimable_bytes,
+        report_path,
+    };
+    if let Err(e) = app.emit("scheduled_scan_completed", completed) {
+        eprintln!("Failed to emit scheduled scan result: {}", e);
+    }
+}
+
+// Runs the saved schedule's scans in the background and emits
+// `scheduled_scan_completed` with what each one found
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let Some(scheduler) = app.try_state::<Scheduler>() else {
+                continue;
+            };
+            let Some(schedule) = scheduler.due() else {
+                continue;
+            };
+            // Don't compete with a scan the user started; try again next check
+            if scan_running(&app) {
+                continue;
+            }
+
+            // Recorded up front so a failing scan isn't retried every minute
+            scheduler.record_run(&app);
+            run(&app, schedule).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn get_schedule(scheduler: tauri::State<'_, Scheduler>) -> Result<ScheduleStatus, Error> {
+    Ok(scheduler.status())
+}
+
+#[tauri::command]
+pub async fn set_schedule(
+    app: AppHandle,
+    scheduler: tauri::State<'_, Scheduler>,
+    schedule: Schedule,
+) -> Result<ScheduleStatus, Error> {
+    scheduler.update(&app, schedule)?;
+    Ok(scheduler.status())
+}
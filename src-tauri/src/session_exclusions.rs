@@ -0,0 +1,99 @@
+use std::{path::Path, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, ScanItem};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionExclusion {
+    pub path: String,
+    // Also hides every item below `path`, not just the item at it
+    pub subtree: bool,
+}
+
+impl SessionExclusion {
+    fn hides(&self, item: &ScanItem) -> bool {
+        if item.node_modules_path == self.path || item.project_path == self.path {
+            return true;
+        }
+        self.subtree && Path::new(&item.node_modules_path).starts_with(&self.path)
+    }
+}
+
+// Items hidden until the next scan starts. Kept in memory only; ignore rules
+// and settings are for exclusions that should stick.
+#[derive(Default)]
+pub struct SessionExclusions(Mutex<Vec<SessionExclusion>>);
+
+impl SessionExclusions {
+    pub fn is_hidden(&self, item: &ScanItem) -> bool {
+        self.0
+            .lock()
+            .map(|exclusions| exclusions.iter().any(|exclusion| exclusion.hides(item)))
+            .unwrap_or(false)
+    }
+
+    pub fn remove_hidden(&self, items: &mut Vec<ScanItem>) {
+        let Ok(exclusions) = self.0.lock() else {
+            return;
+        };
+        items.retain(|item| !exclusions.iter().any(|exclusion| exclusion.hides(item)));
+    }
+
+    // A new scan shows everything again
+    pub fn clear(&self) {
+        if let Ok(mut exclusions) = self.0.lock() {
+            exclusions.clear();
+        }
+    }
+
+    fn list(&self) -> Result<Vec<SessionExclusion>, String> {
+        self.0
+            .lock()
+            .map(|exclusions| exclusions.clone())
+            .map_err(|e| format!("Failed to read hidden items: {}", e))
+    }
+}
+
+#[tauri::command]
+pub async fn hide_for_session(
+    path: String,
+    subtree: bool,
+    exclusions: tauri::State<'_, SessionExclusions>,
+) -> Result<Vec<SessionExclusion>, Error> {
+    if path.trim().is_empty() {
+        return Err(Error::InvalidInput("Path is empty".to_string()));
+    }
+
+    {
+        let mut current = exclusions
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to hide item: {}", e))?;
+        current.retain(|exclusion| exclusion.path != path);
+        current.push(SessionExclusion { path, subtree });
+    }
+    Ok(exclusions.list()?)
+}
+
+#[tauri::command]
+pub async fn unhide_for_session(
+    path: String,
+    exclusions: tauri::State<'_, SessionExclusions>,
+) -> Result<Vec<SessionExclusion>, Error> {
+    {
+        let mut current = exclusions
+            .0
+            .lock()
+            .map_err(|e| format!("Failed to unhide item: {}", e))?;
+        current.retain(|exclusion| exclusion.path != path);
+    }
+    Ok(exclusions.list()?)
+}
+
+#[tauri::command]
+pub async fn list_session_exclusions(
+    exclusions: tauri::State<'_, SessionExclusions>,
+) -> Result<Vec<SessionExclusion>, Error> {
+    Ok(exclusions.list()?)
+}
@@ -0,0 +1,322 @@
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    delete_backend::{DeleteBackend, DeleteBackendSettings},
+    delete_retry::{DeleteRetrySettings, RetryPolicy},
+    drive_visibility::{DriveVisibility, DriveVisibilitySettings},
+    error::Error,
+    launch_scan::{LaunchScan, LaunchScanSettings},
+    legitimacy::{SafetyCheckOptions, SafetyCheckSettings},
+    notifications,
+    protected_projects::ProtectedProjects,
+    scan_settings::{ScanOptions, ScanSettings},
+    scheduler::{Schedule, Scheduler},
+    MAX_DELETE_PARALLELISM,
+};
+
+const SETTINGS_FILE: &str = "settings.json";
+// Bump with a step in MIGRATIONS whenever the layout changes
+const SETTINGS_VERSION: u64 = 2;
+
+// Each area used to keep a file of its own in the app data folder. These
+// moved into settings.json in version 1...
+const VERSION_1_FILES: [(&str, &str); 3] = [
+    ("scan", "scan_settings.json"),
+    ("delete_backend", "delete_backend.json"),
+    ("safety_checks", "safety_checks.json"),
+];
+
+// ...and these in version 2
+const VERSION_2_FILES: [(&str, &str); 5] = [
+    ("delete_retry", "delete_retry.json"),
+    ("drive_visibility", "drive_visibility.json"),
+    ("launch_scan", "launch_scan.json"),
+    ("protected_projects", "protected_projects.json"),
+    ("schedule", "schedule.json"),
+];
+
+type Migration = fn(&AppHandle, &mut Map<String, Value>);
+
+// MIGRATIONS[n] brings a version n file to version n + 1
+const MIGRATIONS: [Migration; 2] = [
+    |app, settings| from_legacy_files(app, settings, &VERSION_1_FILES),
+    |app, settings| from_legacy_files(app, settings, &VERSION_2_FILES),
+];
+
+// Every read-modify-write of the file goes through this
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+// Every setting in one place, for a settings page. Each area still owns its
+// part and checks it; this only gathers them up.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppSettings {
+    pub version: u64,
+    pub scan: ScanOptions,
+    pub delete_backend: DeleteBackend,
+    pub safety_checks: SafetyCheckOptions,
+    pub delete_retry: RetryPolicy,
+    // Deletes running at once on each drive; None picks by drive type
+    pub delete_parallelism: Option<usize>,
+    pub drive_visibility: DriveVisibility,
+    pub launch_scan: LaunchScanSettings,
+    pub protected_projects: Vec<String>,
+    pub schedule: Schedule,
+    // System notification when a scan or delete finishes out of view
+    pub notifications: bool,
+}
+
+// The parts to change; anything left out keeps its value
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SettingsUpdate {
+    pub scan: Option<ScanOptions>,
+    pub delete_backend: Option<DeleteBackend>,
+    pub safety_checks: Option<SafetyCheckOptions>,
+    pub delete_retry: Option<RetryPolicy>,
+    // 0 goes back to picking by drive type
+    pub delete_parallelism: Option<usize>,
+    pub drive_visibility: Option<DriveVisibility>,
+    pub launch_scan: Option<LaunchScanSettings>,
+    // Replaces the whole list
+    pub protected_projects: Option<Vec<String>>,
+    pub schedule: Option<Schedule>,
+    pub notifications: Option<bool>,
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config directory: {}", e))?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+// Gathers the per-area `files` into this one; sections already in it keep
+// their value. The files are left in place so an older build still finds
+// them.
+fn from_legacy_files(app: &AppHandle, settings: &mut Map<String, Value>, files: &[(&str, &str)]) {
+    let Ok(dir) = app.path().app_data_dir() else {
+        return;
+    };
+    for &(key, file) in files {
+        let legacy = fs::read_to_string(dir.join(file))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<Value>(&contents).ok());
+        if let Some(value) = legacy {
+            settings.entry(key).or_insert(value);
+        }
+    }
+}
+
+fn write(app: &AppHandle, settings: &Map<String, Value>) -> Result<(), String> {
+    let contents = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(settings_path(app)?, contents).map_err(|e| format!("Failed to save settings: {}", e))
+}
+
+// The saved file; none at all counts as empty. One that doesn't parse is
+// set aside, not overwritten, so a bad hand edit loses nothing, and the
+// legacy files fill in what they can.
+fn load(app: &AppHandle) -> Result<Map<String, Value>, String> {
+    let path = settings_path(app)?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Map::new()),
+        Err(e) => return Err(format!("Failed to read settings: {}", e)),
+    };
+    let invalid = match serde_json::from_str::<Map<String, Value>>(&contents) {
+        Ok(settings) => return Ok(settings),
+        Err(e) => e,
+    };
+
+    let backup = path.with_file_name(format!(
+        "settings.invalid-{}.json",
+        Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    fs::rename(&path, &backup).map_err(|e| {
+        format!(
+            "{} is not valid JSON ({}) and couldn't be set aside: {}",
+            path.display(),
+            invalid,
+            e
+        )
+    })?;
+    eprintln!(
+        "{} was not valid JSON ({}); moved it to {}",
+        path.display(),
+        invalid,
+        backup.display()
+    );
+    Ok(Map::new())
+}
+
+// The file brought up to the current version. No file at all counts as
+// version 0, so a first start picks up the legacy files.
+fn read(app: &AppHandle) -> Result<Map<String, Value>, String> {
+    let mut settings = load(app)?;
+    let version = settings.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+    // Written by a newer build; use what can be understood, leave the rest
+    if version >= SETTINGS_VERSION {
+        return Ok(settings);
+    }
+
+    for migrate in MIGRATIONS.iter().skip(version as usize) {
+        migrate(app, &mut settings);
+    }
+    settings.insert("version".to_string(), SETTINGS_VERSION.into());
+    if let Err(e) = write(app, &settings) {
+        eprintln!("Failed to migrate settings: {}", e);
+    }
+    Ok(settings)
+}
+
+// One area's part of the file, if there is a readable one
+pub fn section<T: DeserializeOwned>(app: &AppHandle, key: &str) -> Option<T> {
+    let _lock = FILE_LOCK.lock().ok()?;
+    let mut settings = read(app).map_err(|e| eprintln!("{}", e)).ok()?;
+    let value = settings.remove(key)?;
+    serde_json::from_value(value).ok()
+}
+
+// Replaces one area's part of the file, leaving the others alone
+pub fn save_section<T: Serialize + ?Sized>(
+    app: &AppHandle,
+    key: &str,
+    value: &T,
+) -> Result<(), String> {
+    let _lock = FILE_LOCK
+        .lock()
+        .map_err(|e| format!("Failed to update settings: {}", e))?;
+    let value =
+        serde_json::to_value(value).map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let mut settings = read(app)?;
+    settings.insert(key.to_string(), value);
+    write(app, &settings)
+}
+
+// The saved per-drive delete limit, for execute_delete calls that don't pass one
+pub fn delete_parallelism(app: &AppHandle) -> Option<usize> {
+    section::<Option<usize>>(app, "delete_parallelism")
+        .flatten()
+        .filter(|parallelism| (1..=MAX_DELETE_PARALLELISM).contains(parallelism))
+}
+
+fn current(app: &AppHandle) -> AppSettings {
+    AppSettings {
+        version: SETTINGS_VERSION,
+        scan: app
+            .try_state::<ScanSettings>()
+            .map(|settings| settings.current())
+            .unwrap_or_default(),
+        delete_backend: app
+            .try_state::<DeleteBackendSettings>()
+            .map(|settings| settings.selected())
+            .unwrap_or_default(),
+        safety_checks: app
+            .try_state::<SafetyCheckSettings>()
+            .map(|settings| settings.current())
+            .unwrap_or_default(),
+        delete_retry: app
+            .try_state::<DeleteRetrySettings>()
+            .map(|settings| settings.current())
+            .unwrap_or_default(),
+        delete_parallelism: delete_parallelism(app),
+        drive_visibility: app
+            .try_state::<DriveVisibilitySettings>()
+            .map(|settings| settings.current())
+            .unwrap_or_default(),
+        launch_scan: app
+            .try_state::<LaunchScan>()
+            .map(|launch_scan| launch_scan.current())
+            .unwrap_or_default(),
+        protected_projects: crate::protected_projects::rules(app).entries().to_vec(),
+        schedule: app
+            .try_state::<Scheduler>()
+            .map(|scheduler| scheduler.status().schedule)
+            .unwrap_or_default(),
+        notifications: notifications::enabled(app),
+    }
+}
+
+#[tauri::command]
+pub async fn get_settings(app: AppHandle) -> Result<AppSettings, Error> {
+    Ok(current(&app))
+}
+
+// Applied area by area; if one is rejected the ones before it stay changed,
+// and the error names the one that failed
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    scan: tauri::State<'_, ScanSettings>,
+    delete_backend: tauri::State<'_, DeleteBackendSettings>,
+    safety_checks: tauri::State<'_, SafetyCheckSettings>,
+    delete_retry: tauri::State<'_, DeleteRetrySettings>,
+    drive_visibility: tauri::State<'_, DriveVisibilitySettings>,
+    launch_scan: tauri::State<'_, LaunchScan>,
+    protected_projects: tauri::State<'_, ProtectedProjects>,
+    scheduler: tauri::State<'_, Scheduler>,
+    update: SettingsUpdate,
+) -> Result<AppSettings, Error> {
+    if let Some(options) = update.scan {
+        scan.update(&app, options)
+            .map_err(|e| Error::InvalidInput(format!("Scan settings: {}", e)))?;
+    }
+    if let Some(backend) = update.delete_backend {
+        delete_backend.select(&app, backend)?;
+    }
+    if let Some(options) = update.safety_checks {
+        safety_checks
+            .update(&app, options)
+            .map_err(|e| Error::InvalidInput(format!("Safety checks: {}", e)))?;
+    }
+    if let Some(policy) = update.delete_retry {
+        delete_retry
+            .update(&app, policy)
+            .map_err(|e| Error::InvalidInput(format!("Delete retries: {}", e)))?;
+    }
+    if let Some(parallelism) = update.delete_parallelism {
+        if parallelism > MAX_DELETE_PARALLELISM {
+            return Err(Error::InvalidInput(format!(
+                "Delete parallelism: at most {} deletes can run at once per drive",
+                MAX_DELETE_PARALLELISM
+            )));
+        }
+        let parallelism = (parallelism > 0).then_some(parallelism);
+        save_section(&app, "delete_parallelism", &parallelism)?;
+    }
+    if let Some(visibility) = update.drive_visibility {
+        drive_visibility.update(&app, visibility)?;
+    }
+    if let Some(settings) = update.launch_scan {
+        launch_scan.update(&app, settings)?;
+    }
+    if let Some(entries) = update.protected_projects {
+        let entries: Vec<String> = entries
+            .iter()
+            .map(|entry| entry.trim().to_string())
+            .filter(|entry| !entry.is_empty())
+            .collect();
+        protected_projects
+            .update(&app, |current| *current = entries)
+            .map_err(|e| Error::InvalidInput(format!("Protected projects: {}", e)))?;
+    }
+    if let Some(schedule) = update.schedule {
+        scheduler
+            .update(&app, schedule)
+            .map_err(|e| Error::InvalidInput(format!("Schedule: {}", e)))?;
+    }
+    if let Some(enabled) = update.notifications {
+        save_section(&app, "notifications", &enabled)?;
+    }
+    Ok(current(&app))
+}
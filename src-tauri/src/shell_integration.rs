@@ -0,0 +1,549 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::Error;
+
+// Folders handed to the app by the OS (Finder Quick Action, file manager
+// actions, ...) before the frontend had a chance to listen for them
+#[derive(Default)]
+pub struct PendingScanRequests(Mutex<Vec<String>>);
+
+pub fn queue_scan_request(app: &AppHandle, path: String) {
+    if let Some(pending) = app.try_state::<PendingScanRequests>() {
+        if let Ok(mut requests) = pending.0.lock() {
+            requests.push(path.clone());
+        }
+    }
+
+    if let Err(e) = app.emit("scan_requested", &path) {
+        eprintln!("Failed to emit scan request: {}", e);
+    }
+
+    // Bring the window to the front so the user sees the scan start
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+// Pulls folders out of `--scan <dir>` / `--scan=<dir>` arguments, resolving
+// relative paths against the invoking process' working directory
+pub fn scan_paths_from_args(args: &[String], cwd: &Path) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut iter = args.iter().skip(1);
+
+    while let Some(arg) = iter.next() {
+        let value = if arg == "--scan" {
+            iter.next().cloned()
+        } else {
+            arg.strip_prefix("--scan=").map(|v| v.to_string())
+        };
+
+        if let Some(value) = value {
+            let path = PathBuf::from(&value);
+            let path = if path.is_absolute() {
+                path
+            } else {
+                cwd.join(path)
+            };
+
+            if path.is_dir() {
+                paths.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    paths
+}
+
+pub fn handle_args(app: &AppHandle, args: &[String], cwd: &Path) {
+    for path in scan_paths_from_args(args, cwd) {
+        queue_scan_request(app, path);
+    }
+}
+
+#[tauri::command]
+pub async fn take_pending_scan_requests(
+    pending: tauri::State<'_, PendingScanRequests>,
+) -> Result<Vec<String>, Error> {
+    let mut requests = pending
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read pending scan requests: {}", e))?;
+
+    Ok(std::mem::take(&mut *requests))
+}
+
+#[cfg(target_os = "macos")]
+pub fn handle_opened_urls(app: &AppHandle, urls: Vec<tauri::Url>) {
+    for url in urls {
+        // Finder sends folders as file:// URLs through the 'odoc' Apple event
+        if url.scheme() == "file" {
+            if let Ok(path) = url.to_file_path() {
+                if path.is_dir() {
+                    queue_scan_request(app, path.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+const QUICK_ACTION_NAME: &str = "Scan with NodeModules Cleaner";
+
+#[cfg(target_os = "macos")]
+fn quick_action_path(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    let home = app
+        .path()
+        .home_dir()
+        .map_err(|e| format!("Failed to resolve home directory: {}", e))?;
+
+    Ok(home
+        .join("Library")
+        .join("Services")
+        .join(format!("{}.workflow", QUICK_ACTION_NAME)))
+}
+
+#[cfg(target_os = "macos")]
+fn quick_action_info_plist() -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{name}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict>
+				<key>NSApplicationIdentifier</key>
+				<string>com.apple.finder</string>
+			</dict>
+			<key>NSSendFileTypes</key>
+			<array>
+				<string>public.folder</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#,
+        name = QUICK_ACTION_NAME
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn quick_action_document(app_name: &str) -> String {
+    // `open -a` delivers each folder to the running app (or launches it) as an
+    // 'odoc' Apple event, which ends up in RunEvent::Opened
+    let command = format!(
+        "for f in \"$@\"; do\n\topen -a \"{}\" \"$f\"\ndone",
+        app_name
+    );
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>523</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMApplication</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>AMParameterProperties</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<dict/>
+					<key>CheckedForUserDefaultShell</key>
+					<dict/>
+					<key>inputMethod</key>
+					<dict/>
+					<key>shell</key>
+					<dict/>
+					<key>source</key>
+					<dict/>
+				</dict>
+				<key>AMProvides</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.string</string>
+					</array>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<string>{command}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>1</integer>
+					<key>shell</key>
+					<string>/bin/bash</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>CanShowSelectedItemsWhenRun</key>
+				<false/>
+				<key>CanShowWhenRun</key>
+				<true/>
+				<key>Category</key>
+				<array>
+					<string>AMCategoryUtilities</string>
+				</array>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+				<key>InputUUID</key>
+				<string>4A3E1F0C-9D7B-4E2A-8C1B-6F0D2A9E5B71</string>
+				<key>OutputUUID</key>
+				<string>8B2C7D14-3E6F-4A09-B5D8-1C7E9F2A4D63</string>
+				<key>UUID</key>
+				<string>E1D5A2B8-6C3F-47E9-9A0D-3B8F1C6E2A57</string>
+				<key>UnlocalizedApplications</key>
+				<array>
+					<string>Automator</string>
+				</array>
+				<key>isViewVisible</key>
+				<integer>1</integer>
+			</dict>
+		</dict>
+	</array>
+	<key>connectors</key>
+	<dict/>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>applicationBundleID</key>
+		<string>com.apple.finder</string>
+		<key>inputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject.folder</string>
+		<key>outputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>presentationMode</key>
+		<integer>15</integer>
+		<key>processesInput</key>
+		<false/>
+		<key>serviceApplicationBundleID</key>
+		<string>com.apple.finder</string>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject.folder</string>
+		<key>serviceOutputTypeIdentifier</key>
+		<string>com.apple.Automator.nothing</string>
+		<key>serviceProcessesInput</key>
+		<false/>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#,
+        command = command
+    )
+}
+
+#[tauri::command]
+pub async fn install_finder_quick_action(app: AppHandle) -> Result<String, Error> {
+    #[cfg(target_os = "macos")]
+    {
+        use std::fs;
+        use std::process::Command;
+
+        let workflow_path = quick_action_path(&app)?;
+        let contents = workflow_path.join("Contents");
+        fs::create_dir_all(&contents)
+            .map_err(|e| format!("Failed to create Quick Action: {}", e))?;
+
+        let app_name = app
+            .config()
+            .product_name
+            .clone()
+            .unwrap_or_else(|| "NodeModules Cleaner".to_string());
+
+        fs::write(contents.join("Info.plist"), quick_action_info_plist())
+            .map_err(|e| format!("Failed to write Quick Action: {}", e))?;
+        fs::write(
+            contents.join("document.wflow"),
+            quick_action_document(&app_name),
+        )
+        .map_err(|e| format!("Failed to write Quick Action: {}", e))?;
+
+        // Ask the pasteboard server to pick up the new service right away
+        let _ = Command::new("/System/Library/CoreServices/pbs")
+            .arg("-update")
+            .status();
+
+        Ok(workflow_path.to_string_lossy().to_string())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        Err(Error::Unsupported(
+            "Finder Quick Actions are only available on macOS".to_string(),
+        ))
+    }
+}
+
+#[tauri::command]
+pub async fn uninstall_finder_quick_action(app: AppHandle) -> Result<(), Error> {
+    #[cfg(target_os = "macos")]
+    {
+        let workflow_path = quick_action_path(&app)?;
+        if workflow_path.exists() {
+            std::fs::remove_dir_all(&workflow_path)
+                .map_err(|e| format!("Failed to remove Quick Action: {}", e))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        Err(Error::Unsupported(
+            "Finder Quick Actions are only available on macOS".to_string(),
+        ))
+    }
+}
+
+#[tauri::command]
+pub async fn is_finder_quick_action_installed(app: AppHandle) -> Result<bool, Error> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(quick_action_path(&app)?.exists())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+        Ok(false)
+    }
+}
+
+#[cfg(target_os = "linux")]
+const FILE_MANAGER_ACTION_NAME: &str = "Scan with node_modules cleaner";
+
+#[cfg(target_os = "linux")]
+fn desktop_exec_quote(value: &str) -> String {
+    // Quoting rules from the Desktop Entry specification: the argument is
+    // quoted, a literal % is written %%, and since Exec is a string value
+    // its backslashes are escaped once more on top of the quoting
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' | '`' | '$' => quoted.push_str(&format!("\\\\{}", c)),
+            '\\' => quoted.push_str("\\\\\\\\"),
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(target_os = "linux")]
+fn shell_quote(value: &str) -> String {
+    // Single quotes keep $, ` and \ literal; a ' itself has to close and reopen them
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(target_os = "linux")]
+fn app_executable() -> Result<PathBuf, String> {
+    // AppImages run from a temporary mount, point at the image itself instead
+    if let Ok(appimage) = std::env::var("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+
+    std::env::current_exe().map_err(|e| format!("Failed to resolve app executable: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn file_manager_action_paths(app: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    let data_dir = app
+        .path()
+        .data_dir()
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))?;
+
+    Ok(vec![
+        // Dolphin (KF6 and KF5 service menu locations)
+        data_dir
+            .join("kio")
+            .join("servicemenus")
+            .join("nodemodules-cleaner-scan.desktop"),
+        data_dir
+            .join("kservices5")
+            .join("ServiceMenus")
+            .join("nodemodules-cleaner-scan.desktop"),
+        // Nautilus scripts menu
+        data_dir
+            .join("nautilus")
+            .join("scripts")
+            .join(FILE_MANAGER_ACTION_NAME),
+    ])
+}
+
+#[tauri::command]
+pub async fn install_file_manager_action(app: AppHandle) -> Result<Vec<String>, Error> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let executable = app_executable()?;
+        let exec = desktop_exec_quote(&executable.to_string_lossy());
+
+        let service_menu = format!(
+            "[Desktop Entry]\n\
+             Type=Service\n\
+             MimeType=inode/directory;\n\
+             Actions=scanNodeModules;\n\
+             X-KDE-ServiceTypes=KonqPopupMenu/Plugin\n\
+             \n\
+             [Desktop Action scanNodeModules]\n\
+             Name={}\n\
+             Icon=folder-development\n\
+             Exec={} --scan %f\n",
+            FILE_MANAGER_ACTION_NAME, exec
+        );
+
+        let nautilus_script = format!(
+            "#!/bin/sh\n\
+             # Sends each selected folder to NodeModules Cleaner\n\
+             printf '%s' \"$NAUTILUS_SCRIPT_SELECTED_FILE_PATHS\" | while IFS= read -r dir; do\n\
+             \t[ -d \"$dir\" ] && {} --scan \"$dir\" &\n\
+             done\n",
+            shell_quote(&executable.to_string_lossy())
+        );
+
+        let mut installed = Vec::new();
+        for path in file_manager_action_paths(&app)? {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+
+            let is_script = path.extension().is_none();
+            let contents = if is_script {
+                &nautilus_script
+            } else {
+                &service_menu
+            };
+
+            fs::write(&path, contents)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+            // Both Dolphin service menus and Nautilus scripts must be executable
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("Failed to mark {} executable: {}", path.display(), e))?;
+
+            installed.push(path.to_string_lossy().to_string());
+        }
+
+        Ok(installed)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = app;
+        Err(Error::Unsupported(
+            "File manager actions are only available on Linux".to_string(),
+        ))
+    }
+}
+
+#[tauri::command]
+pub async fn uninstall_file_manager_action(app: AppHandle) -> Result<(), Error> {
+    #[cfg(target_os = "linux")]
+    {
+        for path in file_manager_action_paths(&app)? {
+            if path.exists() {
+                std::fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = app;
+        Err(Error::Unsupported(
+            "File manager actions are only available on Linux".to_string(),
+        ))
+    }
+}
+
+#[tauri::command]
+pub async fn is_file_manager_action_installed(app: AppHandle) -> Result<bool, Error> {
+    #[cfg(target_os = "linux")]
+    {
+        Ok(file_manager_action_paths(&app)?
+            .iter()
+            .any(|path| path.exists()))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = app;
+        Ok(false)
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_arguments_survive_both_unescaping_passes() {
+        assert_eq!(desktop_exec_quote("/opt/app"), r#""/opt/app""#);
+        assert_eq!(desktop_exec_quote("/opt/a b/app"), r#""/opt/a b/app""#);
+        // The string escape turns \\\\ into \\, then quoting turns \\ into \
+        assert_eq!(desktop_exec_quote(r"/opt/a\b"), r#""/opt/a\\\\b""#);
+        assert_eq!(desktop_exec_quote(r#"/opt/"$x`"#), r#""/opt/\\"\\$x\\`""#);
+        assert_eq!(desktop_exec_quote("/opt/100%/app"), r#""/opt/100%%/app""#);
+    }
+}
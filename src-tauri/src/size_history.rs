@@ -0,0 +1,147 @@
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{error::Error, ScanItem};
+
+const HISTORY_FILE: &str = "size_history.json";
+const MAX_SAMPLES_PER_PROJECT: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SizeSample {
+    pub timestamp: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectSizeTrend {
+    pub project_path: String,
+    pub samples: Vec<SizeSample>,
+    // Latest size minus the oldest recorded one
+    pub growth_bytes: i64,
+    // Never shrank across at least three samples
+    pub steadily_growing: bool,
+}
+
+// node_modules sizes seen per project path across scans
+pub struct SizeHistory {
+    path: Option<PathBuf>,
+    samples: Mutex<HashMap<String, Vec<SizeSample>>>,
+}
+
+impl SizeHistory {
+    pub fn load(app: &AppHandle) -> Self {
+        let path = app
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|dir| dir.join(HISTORY_FILE));
+        let samples = path
+            .as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        SizeHistory {
+            path,
+            samples: Mutex::new(samples),
+        }
+    }
+
+    fn save(&self, samples: &HashMap<String, Vec<SizeSample>>) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| "Failed to resolve app data directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        }
+
+        let contents = serde_json::to_string(samples)
+            .map_err(|e| format!("Failed to serialize size history: {}", e))?;
+        std::fs::write(path, contents).map_err(|e| format!("Failed to save size history: {}", e))
+    }
+
+    // Adds a sample for every sized item; unchanged sizes aren't repeated
+    pub fn record(&self, items: &[ScanItem]) {
+        let Ok(mut samples) = self.samples.lock() else {
+            return;
+        };
+
+        let timestamp = Utc::now().to_rfc3339();
+        let mut changed = false;
+        for item in items {
+            let Some(size) = item.size else {
+                continue;
+            };
+
+            let history = samples.entry(item.project_path.clone()).or_default();
+            if history.last().is_some_and(|last| last.size == size) {
+                continue;
+            }
+
+            history.push(SizeSample {
+                timestamp: timestamp.clone(),
+                size,
+            });
+            if history.len() > MAX_SAMPLES_PER_PROJECT {
+                history.remove(0);
+            }
+            changed = true;
+        }
+
+        if changed {
+            if let Err(e) = self.save(&samples) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+}
+
+fn trend(project_path: String, samples: Vec<SizeSample>) -> ProjectSizeTrend {
+    let growth_bytes = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) => last.size as i64 - first.size as i64,
+        _ => 0,
+    };
+    let steadily_growing =
+        samples.len() >= 3 && samples.windows(2).all(|pair| pair[1].size >= pair[0].size);
+
+    ProjectSizeTrend {
+        project_path,
+        samples,
+        growth_bytes,
+        steadily_growing,
+    }
+}
+
+#[tauri::command]
+pub async fn get_size_history(
+    history: tauri::State<'_, SizeHistory>,
+    project_paths: Option<Vec<String>>,
+) -> Result<Vec<ProjectSizeTrend>, Error> {
+    let samples = history
+        .samples
+        .lock()
+        .map_err(|e| format!("Failed to read size history: {}", e))?;
+
+    let mut trends: Vec<ProjectSizeTrend> = match project_paths {
+        Some(paths) => paths
+            .into_iter()
+            .filter_map(|path| {
+                let project_samples = samples.get(&path)?.clone();
+                Some(trend(path, project_samples))
+            })
+            .collect(),
+        None => samples
+            .iter()
+            .map(|(path, project_samples)| trend(path.clone(), project_samples.clone()))
+            .collect(),
+    };
+
+    // Fastest-growing projects first
+    trends.sort_by_key(|trend| std::cmp::Reverse(trend.growth_bytes));
+    Ok(trends)
+}
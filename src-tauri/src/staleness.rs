@@ -0,0 +1,32 @@
+use std::{path::Path, time::SystemTime};
+
+use nodemodules_cleaner_core::activity::is_stale;
+
+use crate::{error::Error, project_last_modified, ScanItem};
+
+async fn item_last_modified(item: &ScanItem) -> Option<SystemTime> {
+    let recorded = item
+        .last_modified
+        .as_deref()
+        .and_then(|modified| chrono::DateTime::parse_from_rfc3339(modified).ok())
+        .map(SystemTime::from);
+    match recorded {
+        Some(modified) => Some(modified),
+        None => project_last_modified(Path::new(&item.project_path)).await,
+    }
+}
+
+// Narrows earlier results to projects untouched for at least `min_age_days`
+#[tauri::command]
+pub async fn filter_results(
+    items: Vec<ScanItem>,
+    min_age_days: u64,
+) -> Result<Vec<ScanItem>, Error> {
+    let mut stale = Vec::with_capacity(items.len());
+    for item in items {
+        if is_stale(item_last_modified(&item).await, min_age_days) {
+            stale.push(item);
+        }
+    }
+    Ok(stale)
+}
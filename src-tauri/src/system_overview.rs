@@ -0,0 +1,186 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{current_drives, error::Error, jobs::JobRegistry, sizing, DriveInfo};
+
+// Keeps the wizard snappy; a cache bigger than this can take is reported as unknown
+const CACHE_SIZE_BUDGET: Duration = Duration::from_secs(10);
+
+// Folder names people commonly keep their code in, relative to the home directory
+const DEV_FOLDER_CANDIDATES: [&str; 14] = [
+    "projects",
+    "Projects",
+    "dev",
+    "Developer",
+    "code",
+    "Code",
+    "src",
+    "repos",
+    "workspace",
+    "Workspace",
+    "git",
+    "GitHub",
+    "Documents/GitHub",
+    "source/repos",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PackageManagerInfo {
+    pub name: String,
+    pub executable: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GlobalCacheInfo {
+    pub package_manager: String,
+    pub path: String,
+    // None when the cache couldn't be measured in time
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SystemOverview {
+    pub platform: String,
+    pub home_dir: Option<String>,
+    pub drives: Vec<DriveInfo>,
+    pub dev_folders: Vec<String>,
+    pub package_managers: Vec<PackageManagerInfo>,
+    pub global_caches: Vec<GlobalCacheInfo>,
+    // Where a first scan should start: the dev folders found, else the home directory
+    pub recommended_roots: Vec<String>,
+}
+
+fn find_executable(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+
+    #[cfg(windows)]
+    let candidates = [
+        format!("{}.cmd", name),
+        format!("{}.exe", name),
+        format!("{}.ps1", name),
+    ];
+    #[cfg(not(windows))]
+    let candidates = [name.to_string()];
+
+    std::env::split_paths(&path).find_map(|dir| {
+        candidates
+            .iter()
+            .map(|candidate| dir.join(candidate))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+// Default cache locations; custom ones set via config files aren't picked up
+fn cache_candidates(home: &Path) -> Vec<(&'static str, PathBuf)> {
+    let mut caches = vec![("bun", home.join(".bun/install/cache"))];
+
+    #[cfg(windows)]
+    {
+        if let Some(local) = std::env::var_os("LOCALAPPDATA").map(PathBuf::from) {
+            caches.push(("npm", local.join("npm-cache")));
+            caches.push(("yarn", local.join("Yarn/Cache")));
+            caches.push(("pnpm", local.join("pnpm/store")));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        caches.push(("npm", home.join(".npm")));
+        caches.push(("yarn", home.join("Library/Caches/Yarn")));
+        caches.push(("pnpm", home.join("Library/pnpm/store")));
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".cache"));
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local/share"));
+        caches.push(("npm", home.join(".npm")));
+        caches.push(("yarn", cache_home.join("yarn")));
+        caches.push(("pnpm", data_home.join("pnpm/store")));
+    }
+
+    caches
+}
+
+fn overview(home: Option<PathBuf>, cancel: &CancellationToken) -> SystemOverview {
+    let dev_folders: Vec<String> = home
+        .as_ref()
+        .map(|home| {
+            // "projects" and "Projects" are one folder on case-insensitive file systems
+            let mut seen = HashSet::new();
+            DEV_FOLDER_CANDIDATES
+                .iter()
+                .map(|candidate| home.join(candidate))
+                .filter(|path| path.is_dir())
+                .filter(|path| seen.insert(std::fs::canonicalize(path).unwrap_or(path.clone())))
+                .map(|path| path.to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let package_managers = ["npm", "yarn", "pnpm", "bun"]
+        .iter()
+        .map(|name| PackageManagerInfo {
+            name: name.to_string(),
+            executable: find_executable(name).map(|path| path.to_string_lossy().to_string()),
+        })
+        .collect();
+
+    let global_caches = home
+        .as_deref()
+        .map(cache_candidates)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, path)| path.is_dir())
+        .map(|(package_manager, path)| GlobalCacheInfo {
+            package_manager: package_manager.to_string(),
+            size: sizing::measure_tree(&path, CACHE_SIZE_BUDGET, cancel, &|_| {})
+                .map(|size| size.total),
+            path: path.to_string_lossy().to_string(),
+        })
+        .collect();
+
+    let recommended_roots = if dev_folders.is_empty() {
+        home.iter()
+            .map(|home| home.to_string_lossy().to_string())
+            .collect()
+    } else {
+        dev_folders.clone()
+    };
+
+    SystemOverview {
+        platform: std::env::consts::OS.to_string(),
+        home_dir: home.map(|home| home.to_string_lossy().to_string()),
+        drives: current_drives(),
+        dev_folders,
+        package_managers,
+        global_caches,
+        recommended_roots,
+    }
+}
+
+// Everything a first-run wizard needs to suggest where to scan
+#[tauri::command]
+pub async fn system_overview(app: AppHandle) -> Result<SystemOverview, Error> {
+    let home = app.path().home_dir().ok();
+    let cancel = app
+        .try_state::<JobRegistry>()
+        .map(|jobs| jobs.child_token())
+        .unwrap_or_default();
+
+    tokio::task::spawn_blocking(move || overview(home, &cancel))
+        .await
+        .map_err(|e| format!("Failed to inspect the system: {}", e).into())
+}
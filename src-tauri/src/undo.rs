@@ -0,0 +1,119 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::{delete_backend::DeleteBackend, error::Error, DeleteResult};
+
+// The last batch that went to the OS trash, so it can be put back
+// (there's no API to do that on macOS)
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+struct TrashedBatch {
+    paths: Vec<String>,
+    // Unix seconds just before the batch started; older trash entries for
+    // the same path belong to earlier deletes
+    started_at: i64,
+}
+
+#[derive(Default)]
+pub struct LastDelete(Mutex<Option<TrashedBatch>>);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestoreSummary {
+    pub restored: Vec<String>,
+    // No longer in the trash, e.g. because it was emptied
+    pub missing: Vec<String>,
+}
+
+// Remembers the folders of a finished delete that went to the trash.
+// Replaces the previous batch; one that trashed nothing leaves it alone.
+pub fn record(app: &AppHandle, results: &[DeleteResult], started_at: i64) {
+    let paths: Vec<String> = results
+        .iter()
+        .filter(|result| result.success && result.method == Some(DeleteBackend::Trash))
+        .map(|result| result.path.clone())
+        .collect();
+    if paths.is_empty() {
+        return;
+    }
+    let Some(last) = app.try_state::<LastDelete>() else {
+        return;
+    };
+    if let Ok(mut batch) = last.0.lock() {
+        *batch = Some(TrashedBatch { paths, started_at });
+    };
+}
+
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+fn restore(batch: &TrashedBatch) -> Result<RestoreSummary, String> {
+    use std::path::Path;
+
+    let items =
+        trash::os_limited::list().map_err(|e| format!("Failed to read the trash: {}", e))?;
+
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    for path in &batch.paths {
+        // The same project may have been trashed before; take this batch's copy
+        let latest = items
+            .iter()
+            .filter(|item| item.original_path() == Path::new(path))
+            // Trash timestamps only have second resolution
+            .filter(|item| item.time_deleted >= batch.started_at - 1)
+            .max_by_key(|item| item.time_deleted);
+        match latest {
+            Some(item) => found.push(item.clone()),
+            None => missing.push(path.clone()),
+        }
+    }
+
+    let restored = found
+        .iter()
+        .map(|item| item.original_path().to_string_lossy().to_string())
+        .collect();
+    trash::os_limited::restore_all(found).map_err(|e| match e {
+        // Typically a fresh install created a new node_modules in the meantime
+        trash::Error::RestoreCollision { path, .. } => format!(
+            "{} exists again; remove it first to restore the deleted one",
+            path.display()
+        ),
+        e => format!("Failed to restore from the trash: {}", e),
+    })?;
+
+    Ok(RestoreSummary { restored, missing })
+}
+
+#[cfg(target_os = "macos")]
+fn restore(_batch: &TrashedBatch) -> Result<RestoreSummary, String> {
+    Err("Restoring from the Trash isn't supported on macOS; use Put Back in Finder".to_string())
+}
+
+// Puts the folders of the last trash delete back where they were. Deletes
+// that went to quarantine are restored with restore_quarantined instead.
+#[tauri::command]
+pub async fn restore_last_delete(app: AppHandle) -> Result<RestoreSummary, Error> {
+    let last = app
+        .try_state::<LastDelete>()
+        .ok_or_else(|| "Nothing to restore".to_string())?;
+    let batch = last
+        .0
+        .lock()
+        .map_err(|e| format!("Failed to read the last delete: {}", e))?
+        .take()
+        .ok_or_else(|| "Nothing to restore".to_string())?;
+
+    let (batch, result) = tokio::task::spawn_blocking(move || {
+        let result = restore(&batch);
+        (batch, result)
+    })
+    .await
+    .map_err(|e| format!("Failed to restore: {}", e))?;
+
+    // Keep the batch around so a failed restore can be retried
+    if result.is_err() {
+        if let Ok(mut last) = last.0.lock() {
+            last.get_or_insert(batch);
+        }
+    }
+    Ok(result?)
+}
@@ -0,0 +1,265 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DriveType {
+    Fixed,
+    Removable,
+    Network,
+    Ram,
+    Optical,
+    // A WSL distro's filesystem, reached through \\wsl$
+    Wsl,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeDetails {
+    pub drive_type: DriveType,
+    pub label: Option<String>,
+    // None where the volume can't be asked without risking a stall
+    pub total_bytes: Option<u64>,
+    pub free_bytes: Option<u64>,
+}
+
+#[cfg(windows)]
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(Some(0)).collect()
+}
+
+#[cfg(windows)]
+pub fn drive_type(root: &str) -> Option<DriveType> {
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    let root = wide(root);
+    // SAFETY: root is a NUL-terminated drive root
+    let drive_type = unsafe { GetDriveTypeW(root.as_ptr()) };
+    // Values from WindowsProgramming, which isn't worth a feature
+    match drive_type {
+        1 => None,
+        2 => Some(DriveType::Removable),
+        3 => Some(DriveType::Fixed),
+        4 => Some(DriveType::Network),
+        5 => Some(DriveType::Optical),
+        6 => Some(DriveType::Ram),
+        _ => Some(DriveType::Unknown),
+    }
+}
+
+// Total and free bytes of the volume holding `path`; free is what the
+// current user may still write
+fn space(path: &Path) -> Option<(u64, u64)> {
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+        let root = wide(&path.to_string_lossy());
+        let (mut available, mut total) = (0u64, 0u64);
+        // SAFETY: root is NUL-terminated, the out-params are valid and the
+        // unused one may be null
+        let ok = unsafe {
+            GetDiskFreeSpaceExW(
+                root.as_ptr(),
+                &mut available,
+                &mut total,
+                std::ptr::null_mut(),
+            )
+        };
+        (ok != 0).then_some((total, available))
+    }
+
+    #[cfg(not(windows))]
+    {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        // SAFETY: statvfs is plain data, so all zeroes is a valid value
+        let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: path is NUL-terminated and stats is a valid out-param
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+            return None;
+        }
+        let block = stats.f_frsize as u64;
+        Some((stats.f_blocks as u64 * block, stats.f_bavail as u64 * block))
+    }
+}
+
+#[cfg(windows)]
+fn label(root: &str) -> Option<String> {
+    use windows_sys::Win32::Storage::FileSystem::GetVolumeInformationW;
+
+    let root = wide(root);
+    let mut name = [0u16; 261];
+    // SAFETY: root is NUL-terminated, name is a buffer of the given length
+    // and the other out-params may be null
+    let ok = unsafe {
+        GetVolumeInformationW(
+            root.as_ptr(),
+            name.as_mut_ptr(),
+            name.len() as u32,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+    let length = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    Some(String::from_utf16_lossy(&name[..length])).filter(|label| !label.is_empty())
+}
+
+// Label from /dev/disk/by-label pointing at the mount's device
+#[cfg(target_os = "linux")]
+fn linux_label(source: &str) -> Option<String> {
+    let device = std::fs::canonicalize(source).ok()?;
+    std::fs::read_dir("/dev/disk/by-label")
+        .ok()?
+        .flatten()
+        .find(|entry| std::fs::canonicalize(entry.path()).is_ok_and(|target| target == device))
+        // udev escapes spaces and other unsafe characters as \xNN
+        .map(|entry| entry.file_name().to_string_lossy().replace("\\x20", " "))
+}
+
+// USB sticks and card readers; USB disks don't always set the removable flag
+#[cfg(target_os = "linux")]
+fn linux_is_removable(path: &Path) -> bool {
+    let disk = crate::planner::physical_drive_of(path);
+    let Some(name) = disk.strip_prefix("/dev/") else {
+        return false;
+    };
+    let sys = Path::new("/sys/block").join(name);
+    std::fs::read_to_string(sys.join("removable")).is_ok_and(|flag| flag.trim() == "1")
+        || std::fs::canonicalize(&sys).is_ok_and(|device| device.to_string_lossy().contains("/usb"))
+}
+
+#[cfg(target_os = "macos")]
+const MACOS_NETWORK_FILESYSTEMS: [&str; 4] = ["smbfs", "nfs", "afpfs", "webdav"];
+
+// Filesystem type and mount point of the mount holding `path`, from the
+// kernel's list of mounts. Unlike statfs on the path, MNT_NOWAIT never
+// waits on a network server.
+#[cfg(target_os = "macos")]
+fn macos_mount(path: &Path) -> Option<(String, std::path::PathBuf)> {
+    use std::{
+        ffi::{CStr, OsStr},
+        os::unix::ffi::OsStrExt,
+    };
+
+    // SAFETY: a null buffer only asks for the number of mounts
+    let count = unsafe { libc::getfsstat(std::ptr::null_mut(), 0, libc::MNT_NOWAIT) };
+    if count <= 0 {
+        return None;
+    }
+    // SAFETY: statfs is plain data, so all zeroes is a valid value. A few
+    // spare entries cover volumes mounted in between the two calls.
+    let mut mounts: Vec<libc::statfs> = vec![unsafe { std::mem::zeroed() }; count as usize + 4];
+    let size = (mounts.len() * std::mem::size_of::<libc::statfs>()) as libc::c_int;
+    // SAFETY: the buffer holds `size` bytes of statfs entries
+    let count = unsafe { libc::getfsstat(mounts.as_mut_ptr(), size, libc::MNT_NOWAIT) };
+    if count < 0 {
+        return None;
+    }
+    mounts.truncate(count as usize);
+
+    mounts
+        .iter()
+        .map(|mount| {
+            // SAFETY: the kernel fills both fields with NUL-terminated strings
+            let (fs_type, mount_point) = unsafe {
+                (
+                    CStr::from_ptr(mount.f_fstypename.as_ptr()),
+                    CStr::from_ptr(mount.f_mntonname.as_ptr()),
+                )
+            };
+            (
+                fs_type.to_string_lossy().to_string(),
+                std::path::PathBuf::from(OsStr::from_bytes(mount_point.to_bytes())),
+            )
+        })
+        .filter(|(_, mount_point)| path.starts_with(mount_point))
+        .max_by_key(|(_, mount_point)| mount_point.as_os_str().len())
+}
+
+pub fn details(path: &Path) -> VolumeDetails {
+    #[cfg(windows)]
+    {
+        let root = path.to_string_lossy();
+        let drive_type = drive_type(&root).unwrap_or(DriveType::Unknown);
+        // A disconnected mapped drive can stall every call for seconds
+        if drive_type == DriveType::Network {
+            return VolumeDetails {
+                drive_type,
+                label: None,
+                total_bytes: None,
+                free_bytes: None,
+            };
+        }
+        let space = space(path);
+        VolumeDetails {
+            drive_type,
+            label: label(&root),
+            total_bytes: space.map(|(total, _)| total),
+            free_bytes: space.map(|(_, free)| free),
+        }
+    }
+
+    // Like Windows, network volumes are told apart from the mount table
+    // alone and never asked for their size: a statvfs on a hung NFS or SMB
+    // mount blocks until the server answers
+    #[cfg(target_os = "macos")]
+    {
+        let mount = macos_mount(path);
+        let drive_type = match &mount {
+            Some((fs_type, _)) if MACOS_NETWORK_FILESYSTEMS.contains(&fs_type.as_str()) => {
+                DriveType::Network
+            }
+            // The boot volume also shows up in /Volumes as a link to /
+            Some((_, mount_point)) if mount_point != Path::new("/") => DriveType::Removable,
+            _ => DriveType::Fixed,
+        };
+        let space = (drive_type != DriveType::Network)
+            .then(|| space(path))
+            .flatten();
+        VolumeDetails {
+            drive_type,
+            // Finder shows a volume by its mount name
+            label: (drive_type != DriveType::Fixed)
+                .then(|| path.file_name())
+                .flatten()
+                .map(|name| name.to_string_lossy().to_string()),
+            total_bytes: space.map(|(total, _)| total),
+            free_bytes: space.map(|(_, free)| free),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let (fs_type, source) =
+            crate::snapshots::mount_source_of_canonical(path).unwrap_or_default();
+        let drive_type = if crate::snapshots::NETWORK_FILESYSTEMS.contains(&fs_type.as_str()) {
+            DriveType::Network
+        } else if matches!(fs_type.as_str(), "tmpfs" | "ramfs") {
+            DriveType::Ram
+        } else if matches!(fs_type.as_str(), "iso9660" | "udf") {
+            DriveType::Optical
+        } else if linux_is_removable(path) {
+            DriveType::Removable
+        } else {
+            DriveType::Fixed
+        };
+        let space = (drive_type != DriveType::Network)
+            .then(|| space(path))
+            .flatten();
+        VolumeDetails {
+            drive_type,
+            label: linux_label(&source),
+            total_bytes: space.map(|(total, _)| total),
+            free_bytes: space.map(|(_, free)| free),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, ScanItem};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkspaceGroup {
+    pub workspace_root: String,
+    pub items: Vec<ScanItem>,
+    pub total_size: u64,
+}
+
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn string_list(value: &serde_json::Value) -> Option<Vec<String>> {
+    Some(
+        value
+            .as_array()?
+            .iter()
+            .filter_map(|pattern| pattern.as_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+// "workspaces": [...] or, as yarn also allows, "workspaces": { "packages": [...] }
+fn package_json_patterns(dir: &Path) -> Option<Vec<String>> {
+    let workspaces = read_json(&dir.join("package.json"))?
+        .get("workspaces")?
+        .clone();
+    string_list(&workspaces).or_else(|| string_list(workspaces.get("packages")?))
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(['\'', '"']).to_string()
+}
+
+// Only the `packages` list is needed, so this reads just enough YAML for it:
+// a block sequence or a flow sequence on the same line
+fn pnpm_patterns(dir: &Path) -> Option<Vec<String>> {
+    let contents = fs::read_to_string(dir.join("pnpm-workspace.yaml")).ok()?;
+    let mut lines = contents.lines();
+    let first = lines
+        .find_map(|line| line.strip_prefix("packages:"))?
+        .trim();
+
+    if let Some(flow) = first.strip_prefix('[') {
+        let flow = flow.trim_end().trim_end_matches(']');
+        return Some(
+            flow.split(',')
+                .map(unquote)
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+        );
+    }
+
+    let mut patterns = Vec::new();
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        // The next top-level key ends the list
+        if !line.starts_with([' ', '\t', '-']) {
+            break;
+        }
+        if let Some(item) = trimmed.strip_prefix('-') {
+            let item = item.split(" #").next().unwrap_or(item);
+            patterns.push(unquote(item));
+        }
+    }
+    Some(patterns)
+}
+
+fn lerna_patterns(dir: &Path) -> Option<Vec<String>> {
+    let lerna = read_json(&dir.join("lerna.json"))?;
+    match lerna.get("packages").and_then(string_list) {
+        Some(patterns) => Some(patterns),
+        // With useWorkspaces lerna defers to package.json
+        None => package_json_patterns(dir).or_else(|| Some(vec!["packages/*".to_string()])),
+    }
+}
+
+// Member globs declared by a workspace root at `dir`, if it is one
+fn workspace_patterns(dir: &Path) -> Option<Vec<String>> {
+    pnpm_patterns(dir)
+        .or_else(|| package_json_patterns(dir))
+        .or_else(|| lerna_patterns(dir))
+}
+
+fn build(patterns: &[&str]) -> Option<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .ok()?;
+        builder.add(glob);
+    }
+    builder.build().ok()
+}
+
+fn is_member(patterns: &[String], relative: &str) -> bool {
+    let (excluded, included): (Vec<&str>, Vec<&str>) = patterns
+        .iter()
+        .map(String::as_str)
+        .partition(|pattern| pattern.starts_with('!'));
+    let excluded: Vec<&str> = excluded
+        .iter()
+        .map(|pattern| pattern.trim_start_matches('!'))
+        .collect();
+
+    build(&included).is_some_and(|set| set.is_match(relative))
+        && build(&excluded).is_none_or(|set| !set.is_match(relative))
+}
+
+// Closest workspace root that lists the project as a member, including the
+// project itself when it is a root
+pub fn workspace_root(project_path: &Path) -> Option<PathBuf> {
+    project_path.ancestors().find_map(|dir| {
+        let patterns = workspace_patterns(dir)?;
+        if dir == project_path {
+            return Some(dir.to_path_buf());
+        }
+        let relative = project_path
+            .strip_prefix(dir)
+            .ok()?
+            .to_string_lossy()
+            .replace('\\', "/");
+        is_member(&patterns, &relative).then(|| dir.to_path_buf())
+    })
+}
+
+// Results grouped by workspace, largest first; items outside any workspace
+// are left out
+#[tauri::command]
+pub async fn group_by_workspace(items: Vec<ScanItem>) -> Result<Vec<WorkspaceGroup>, Error> {
+    let mut groups: BTreeMap<String, Vec<ScanItem>> = BTreeMap::new();
+    for item in items {
+        if let Some(root) = item.workspace_root.clone() {
+            groups.entry(root).or_default().push(item);
+        }
+    }
+
+    let mut groups: Vec<WorkspaceGroup> = groups
+        .into_iter()
+        .map(|(workspace_root, items)| WorkspaceGroup {
+            total_size: items.iter().filter_map(|item| item.size).sum(),
+            workspace_root,
+            items,
+        })
+        .collect();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.total_size));
+    Ok(groups)
+}
@@ -0,0 +1,104 @@
+use std::ptr;
+
+use windows_sys::Win32::{
+    Foundation::ERROR_SUCCESS,
+    System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegGetValueW, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, KEY_READ,
+        RRF_RT_REG_SZ,
+    },
+};
+
+use crate::{volumes::DriveType, DriveInfo};
+
+// Each installed distro has a GUID subkey here. Reading the registry is
+// cheap, unlike wsl.exe or touching \\wsl$, which can boot the WSL VM.
+const LXSS_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Lxss";
+
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(Some(0)).collect()
+}
+
+fn from_wide(value: &[u16]) -> String {
+    let length = value.iter().position(|&c| c == 0).unwrap_or(value.len());
+    String::from_utf16_lossy(&value[..length])
+}
+
+fn distribution_name(lxss: HKEY, subkey: &[u16]) -> Option<String> {
+    let value = wide("DistributionName");
+    let mut name = [0u16; 256];
+    let mut size = std::mem::size_of_val(&name) as u32;
+    // SAFETY: lxss is an open key, subkey and value are NUL-terminated and
+    // name is a buffer of `size` bytes
+    let status = unsafe {
+        RegGetValueW(
+            lxss,
+            subkey.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ,
+            ptr::null_mut(),
+            name.as_mut_ptr().cast(),
+            &mut size,
+        )
+    };
+    (status == ERROR_SUCCESS)
+        .then(|| from_wide(&name))
+        .filter(|name| !name.is_empty())
+}
+
+// Names of the installed WSL distributions
+pub fn distributions() -> Vec<String> {
+    let key = wide(LXSS_KEY);
+    let mut lxss: HKEY = ptr::null_mut();
+    // SAFETY: key is NUL-terminated and lxss is a valid out-param
+    if unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, key.as_ptr(), 0, KEY_READ, &mut lxss) }
+        != ERROR_SUCCESS
+    {
+        return Vec::new();
+    }
+
+    let mut distributions = Vec::new();
+    for index in 0.. {
+        let mut subkey = [0u16; 256];
+        let mut length = subkey.len() as u32;
+        // SAFETY: lxss is open, subkey holds `length` characters and the
+        // optional out-params may be null
+        let status = unsafe {
+            RegEnumKeyExW(
+                lxss,
+                index,
+                subkey.as_mut_ptr(),
+                &mut length,
+                ptr::null(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        if status != ERROR_SUCCESS {
+            break;
+        }
+        if let Some(name) = distribution_name(lxss, &subkey) {
+            distributions.push(name);
+        }
+    }
+
+    // SAFETY: lxss was opened above and isn't used afterwards
+    unsafe { RegCloseKey(lxss) };
+    distributions
+}
+
+// \\wsl$\<distro>\home for each distro. Nothing is probed: the path only
+// resolves once the distro is running, which the scan itself takes care of.
+pub fn home_roots() -> Vec<DriveInfo> {
+    distributions()
+        .into_iter()
+        .map(|distro| DriveInfo {
+            path: format!("\\\\wsl$\\{}\\home", distro),
+            name: format!("WSL {}", distro),
+            drive_type: DriveType::Wsl,
+            label: None,
+            total_bytes: None,
+            free_bytes: None,
+        })
+        .collect()
+}
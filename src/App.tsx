@@ -1,4 +1,4 @@
-import { useState, useEffect } from "react";
+import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import "./App.css";
 
@@ -6,26 +6,116 @@ interface ScanItem {
 	project_path: string;
 	node_modules_path: string;
 	size?: number | null;
+	is_cloud_synced?: boolean;
+	is_protected?: boolean;
+	is_pnpm?: boolean;
+	reclaimable_size?: number | null;
+	// "node_modules" unless another artifact was found; see ARTIFACT_LABELS
+	artifact_type?: string;
+}
+
+// Folder shown in the tree and badge text for each artifact type
+const ARTIFACT_LABELS: Record<string, { folder: string; badge: string }> = {
+	yarn_cache: { folder: ".yarn/cache", badge: "yarn pnp" },
+	yarn_unplugged: { folder: ".yarn/unplugged", badge: "yarn pnp" },
+	cargo_target: { folder: "target", badge: "cargo" },
+	python_venv: { folder: ".venv", badge: "venv" },
+	python_cache: { folder: "__pycache__", badge: "python" },
+	gradle_build: { folder: "build", badge: "gradle" },
+	gradle_cache: { folder: ".gradle", badge: "gradle" },
+	cocoa_pods: { folder: "Pods", badge: "cocoapods" },
+	xcode_derived_data: { folder: "DerivedData", badge: "xcode" },
+};
+
+interface ScanSummary {
+	directories_visited: number;
+	directories_skipped: number;
+	skipped_by_reason: Record<string, number>;
+	permission_errors: number;
+	elapsed_ms: number;
+	reclaimable_bytes: number;
+}
+
+interface ScanResults {
+	items: ScanItem[];
+	summary: ScanSummary;
 }
 
 interface ScanProgress {
+	session_id?: string;
 	current_folder: string;
 	folders_scanned: number;
 	total_folders_estimated: number;
 	node_modules_found: number;
 	directories_skipped: number;
 	is_complete: boolean;
+	eta_seconds?: number | null;
+	summary?: ScanSummary | null;
 }
 
 interface DriveInfo {
 	path: string;
 	name: string;
+	drive_type: "fixed" | "removable" | "network" | "ram" | "optical" | "wsl" | "unknown";
+	label?: string | null;
+	total_bytes?: number | null;
+	free_bytes?: number | null;
 }
 
+interface DeletePlan {
+	token: string;
+	expires_in_secs: number;
+	items: {
+		path: string;
+		is_legitimate: boolean;
+		size?: number | null;
+		risk?: "low" | "medium" | "high" | null;
+	}[];
+}
+
+// What commands fail with; `code` is e.g. "permission_denied" or "not_found"
+interface CommandError {
+	code: string;
+	message: string;
+}
+
+const errorMessage = (error: unknown): string =>
+	typeof error === "object" && error !== null && "message" in error
+		? (error as CommandError).message
+		: String(error);
+
 interface DeleteResult {
 	path: string;
 	success: boolean;
-	error?: string;
+	status: "deleted" | "failed" | "needs_override" | "partial" | "skipped";
+	error?: CommandError | null;
+	method?: "trash" | "quarantine" | "direct" | null;
+	bytes_freed?: number | null;
+	files_removed?: number | null;
+	attempts?: number;
+	can_elevate?: boolean;
+	elevated?: boolean;
+	// What survived a partial delete; only the first files are listed
+	leftovers?: { files: number; bytes?: number; listed: string[] } | null;
+}
+
+interface DeleteBatch {
+	results: DeleteResult[];
+	total_bytes_freed: number;
+	total_files_removed: number;
+	nested?: { path: string; parent: string; parent_selected: boolean }[];
+}
+
+interface DeleteProgress {
+	path: string;
+	files_removed: number;
+	bytes_removed: number;
+}
+
+interface DeleteItemCompleted {
+	result: DeleteResult;
+	completed: number;
+	total: number;
 }
 
 interface TreeNode {
@@ -35,6 +125,11 @@ interface TreeNode {
 	type: "folder" | "node_modules";
 	children: TreeNode[];
 	size?: number | null;
+	isCloudSynced?: boolean;
+	isProtected?: boolean;
+	isPnpm?: boolean;
+	reclaimableSize?: number | null;
+	artifactType?: string;
 	isExpanded: boolean;
 	isSelected: boolean;
 	level: number;
@@ -42,14 +137,33 @@ interface TreeNode {
 
 type ScanScope = "folder" | "drive" | "entire";
 
+type FolderSelection =
+	| { status: "picked"; paths: string[] }
+	| { status: "cancelled" }
+	| { status: "timed_out" };
+
 function App() {
 	const [scanScope, setScanScope] = useState<ScanScope>("folder");
+	// One or more folders, separated by semicolons
 	const [selectedFolder, setSelectedFolder] = useState("");
+	const [recentRoots, setRecentRoots] = useState<string[]>([]);
 	const [selectedDrive, setSelectedDrive] = useState("");
 	const [drives, setDrives] = useState<DriveInfo[]>([]);
+	const [showAllDrives, setShowAllDrives] = useState(false);
 	const [includeSizes, setIncludeSizes] = useState(false);
 	const [isScanning, setIsScanning] = useState(false);
 	const [isDeleting, setIsDeleting] = useState(false);
+	const [permanentDelete, setPermanentDelete] = useState(false);
+	const [permanentFallback, setPermanentFallback] = useState(false);
+	const [canUndoDelete, setCanUndoDelete] = useState(false);
+	const [clearReadOnly, setClearReadOnly] = useState(false);
+	const [fastRemove, setFastRemove] = useState(false);
+	const [deleteProgress, setDeleteProgress] = useState<{
+		completed: number;
+		total: number;
+	} | null>(null);
+	// Bytes removed so far per folder, during a permanent delete
+	const [deleteBytes, setDeleteBytes] = useState<Record<string, number>>({});
 	const [scanProgress, setScanProgress] = useState<ScanProgress>({
 		current_folder: "Starting scan...",
 		folders_scanned: 0,
@@ -70,10 +184,14 @@ function App() {
 	const [isDarkMode, setIsDarkMode] = useState(false);
 	const [scanStartTime, setScanStartTime] = useState<Date | null>(null);
 	const [scanDuration, setScanDuration] = useState<string>("");
+	// Id of the scan the user started last; progress from any other scan
+	// (the launch scan, or one still winding down) is ignored
+	const activeScanId = useRef<string | null>(null);
 
 	// Load drives on component mount
 	useEffect(() => {
 		loadDrives();
+		loadRecentRoots();
 		// Check system preference for dark mode
 		if (
 			window.matchMedia &&
@@ -100,6 +218,7 @@ function App() {
 				const { listen } = await import("@tauri-apps/api/event");
 				const unlisten = await listen("scan_progress", (event) => {
 					const progress = event.payload as ScanProgress;
+					if (progress.session_id !== activeScanId.current) return;
 					setScanProgress(progress);
 
 					// If scan is complete, update the results and show final duration
@@ -131,6 +250,44 @@ function App() {
 		};
 	}, []);
 
+	// Listen for each folder finishing during a delete
+	useEffect(() => {
+		const setupListener = async () => {
+			try {
+				const { listen } = await import("@tauri-apps/api/event");
+				const unlistenProgress = await listen("delete_progress", (event) => {
+					const { path, bytes_removed } = event.payload as DeleteProgress;
+					setDeleteBytes((prev) => ({ ...prev, [path]: bytes_removed }));
+				});
+				const unlistenCompleted = await listen(
+					"delete_item_completed",
+					(event) => {
+						const { completed, total } = event.payload as DeleteItemCompleted;
+						setDeleteProgress({ completed, total });
+					}
+				);
+				return () => {
+					unlistenProgress();
+					unlistenCompleted();
+				};
+			} catch (error) {
+				console.error("Failed to setup event listener:", error);
+				return null;
+			}
+		};
+
+		let unlistenFn: (() => void) | null = null;
+		setupListener().then((unlisten) => {
+			unlistenFn = unlisten;
+		});
+
+		return () => {
+			if (unlistenFn) {
+				unlistenFn();
+			}
+		};
+	}, []);
+
 	// Timer effect for scan duration
 	useEffect(() => {
 		let interval: number | null = null;
@@ -170,9 +327,9 @@ function App() {
 		}
 	}, [scanResults]);
 
-	const loadDrives = async () => {
+	const loadDrives = async (includeAll: boolean = showAllDrives) => {
 		try {
-			const drivesList = await invoke("list_drives");
+			const drivesList = await invoke("list_drives", { includeAll });
 			setDrives(drivesList as DriveInfo[]);
 		} catch (error) {
 			console.error("Failed to load drives:", error);
@@ -229,15 +386,21 @@ function App() {
 				parentNode = treeMap.get(nodeId)!;
 			}
 
-			// Add the node_modules folder
+			// Add the node_modules folder (or other artifact)
 			const nodeModulesId = `node_modules-${item.node_modules_path}`;
 			const nodeModulesNode: TreeNode = {
 				id: nodeModulesId,
-				name: "node_modules",
+				name:
+					ARTIFACT_LABELS[item.artifact_type ?? ""]?.folder ?? "node_modules",
 				path: item.node_modules_path,
 				type: "node_modules",
 				children: [],
 				size: item.size,
+				isCloudSynced: item.is_cloud_synced,
+				isProtected: item.is_protected,
+				isPnpm: item.is_pnpm,
+				reclaimableSize: item.reclaimable_size,
+				artifactType: item.artifact_type,
 				isExpanded: false,
 				isSelected: false,
 				level: projectPathParts.length,
@@ -374,19 +537,19 @@ function App() {
 		});
 	};
 
+	const loadRecentRoots = async () => {
+		try {
+			setRecentRoots((await invoke("get_recent_roots")) as string[]);
+		} catch (error) {
+			console.error("Failed to load recent folders:", error);
+		}
+	};
+
 	const handleBrowseFolder = async () => {
 		try {
-			const result = await invoke("open_folder_dialog");
-			if (result) {
-				setSelectedFolder(result as string);
-			} else {
-				// If dialog returns None, use the fallback prompt
-				const input = prompt(
-					"Enter folder path (e.g., C:\\Users\\YourName\\Projects):"
-				);
-				if (input && input.trim()) {
-					setSelectedFolder(input.trim());
-				}
+			const result = (await invoke("open_folders_dialog")) as FolderSelection;
+			if (result.status === "picked") {
+				setSelectedFolder(result.paths.join("; "));
 			}
 		} catch (error) {
 			console.error("Failed to open folder dialog:", error);
@@ -407,11 +570,14 @@ function App() {
 
 		switch (scanScope) {
 			case "folder":
-				if (!selectedFolder.trim()) {
+				roots = selectedFolder
+					.split(";")
+					.map((folder) => folder.trim())
+					.filter((folder) => folder.length > 0);
+				if (roots.length === 0) {
 					alert("Please select a folder to scan");
 					return;
 				}
-				roots = [selectedFolder.trim()];
 				break;
 			case "drive":
 				if (!selectedDrive) {
@@ -421,8 +587,9 @@ function App() {
 				roots = [selectedDrive];
 				break;
 			case "entire":
-				// Get all available drives for entire computer scan
-				roots = drives.map((d) => d.path);
+				// Get all local drives for entire computer scan; network
+				// shares are slow and can be scanned on their own
+				roots = drives.filter((d) => d.drive_type !== "network").map((d) => d.path);
 				break;
 		}
 
@@ -431,6 +598,8 @@ function App() {
 			return;
 		}
 
+		const sessionId = crypto.randomUUID();
+		activeScanId.current = sessionId;
 		setIsScanning(true);
 		setScanStartTime(new Date());
 		setScanResults([]);
@@ -450,12 +619,14 @@ function App() {
 			const results = (await invoke("start_scan_with_progress", {
 				roots,
 				includeSizes,
-			})) as ScanItem[];
-			setScanResults(results);
+				sessionId,
+			})) as ScanResults;
+			setScanResults(results.items);
+			loadRecentRoots();
 			// Progress updates will come through the event listener
 		} catch (error) {
 			console.error("Scan failed:", error);
-			alert("Scan failed: " + error);
+			alert("Scan failed: " + errorMessage(error));
 			setIsScanning(false);
 		}
 	};
@@ -465,7 +636,69 @@ function App() {
 			await invoke("open_folder_in_explorer", { path });
 		} catch (error) {
 			console.error("Failed to open folder:", error);
-			alert("Failed to open folder: " + error);
+			alert("Failed to open folder: " + errorMessage(error));
+		}
+	};
+
+	// Protects or unprotects the project a node_modules belongs to
+	const handleToggleProtected = async (nodeModulesPath: string) => {
+		const item = scanResults.find(
+			(result) => result.node_modules_path === nodeModulesPath
+		);
+		if (!item) return;
+		const isProtected = !item.is_protected;
+		try {
+			await invoke(isProtected ? "protect_project" : "unprotect_project", {
+				entry: item.project_path,
+			});
+			setScanResults((prev) =>
+				prev.map((result) =>
+					result.node_modules_path === nodeModulesPath
+						? { ...result, is_protected: isProtected }
+						: result
+				)
+			);
+			if (isProtected) {
+				setSelectedItems((prev) => {
+					const next = new Set(prev);
+					next.delete(nodeModulesPath);
+					return next;
+				});
+			}
+		} catch (error) {
+			console.error("Failed to update protected projects:", error);
+			alert("Failed to update protected projects: " + errorMessage(error));
+		}
+	};
+
+	// Size of the folders being deleted, from the scan, for the progress bar
+	const deleteTotalBytes = (): number => {
+		const paths =
+			deleteTarget?.type === "single" && deleteTarget.path
+				? new Set([deleteTarget.path])
+				: selectedItems;
+		return scanResults
+			.filter((item) => paths.has(item.node_modules_path))
+			.reduce((total, item) => total + (item.size || 0), 0);
+	};
+
+	const handleUndoDelete = async () => {
+		try {
+			const summary = (await invoke("restore_last_delete")) as {
+				restored: string[];
+				missing: string[];
+			};
+			setCanUndoDelete(false);
+			alert(
+				`Restored ${summary.restored.length} folders.${
+					summary.missing.length > 0
+						? ` ${summary.missing.length} were no longer in the Recycle Bin.`
+						: ""
+				} Rescan to see them again.`
+			);
+		} catch (error) {
+			console.error("Restore failed:", error);
+			alert("Restore failed: " + errorMessage(error));
 		}
 	};
 
@@ -491,16 +724,47 @@ function App() {
 
 			if (paths.length === 0) return;
 
-			const results = (await invoke("delete_node_modules", {
-				paths,
-			})) as DeleteResult[];
+			const confirmPermanent =
+				permanentDelete &&
+				window.confirm(
+					`Permanently delete ${paths.length} node_modules folder(s)? This can't be undone.`
+				);
+			if (permanentDelete && !confirmPermanent) return;
+
+			const plan = (await invoke("prepare_delete", { paths })) as DeletePlan;
+			// Left out of the delete unless confirmed here
+			const highRisk = plan.items.filter((item) => item.risk === "high");
+			const confirmHighRisk =
+				highRisk.length > 0 &&
+				window.confirm(
+					`${highRisk.length} folder(s) were rated high risk (recent changes, uncommitted work or running processes):\n\n${highRisk
+						.map((item) => item.path)
+						.join("\n")}\n\nDelete them too?`
+				);
+			const batch = (await invoke("execute_delete", {
+				token: plan.token,
+				confirmHighRisk,
+				deleteMode: permanentDelete ? "permanent" : "trash",
+				confirmPermanent,
+				permanentFallback: !permanentDelete && permanentFallback,
+				clearReadOnly,
+				fastRemove,
+			})) as DeleteBatch;
+			const results = batch.results;
+			if (results.some((r) => r.success && r.method === "trash")) {
+				setCanUndoDelete(true);
+			}
+			const reclaimedNote =
+				batch.total_bytes_freed > 0
+					? ` You just reclaimed ${formatFileSize(batch.total_bytes_freed)}.`
+					: "";
 
 			// Remove successfully deleted items
-			const failedPaths = new Set(
-				results.filter((r) => !r.success).map((r) => r.path)
+			const deletedPaths = new Set(
+				results.filter((r) => r.success).map((r) => r.path)
 			);
 			setScanResults((prev) =>
-				prev.filter((item) => !failedPaths.has(item.node_modules_path))
+				prev.filter((item) => !deletedPaths.has(item.node_modules_path))
 			);
 
 			// Clear selection
@@ -508,20 +772,88 @@ function App() {
 
 			// Show results summary
 			const successCount = results.filter((r) => r.success).length;
-			const failCount = results.filter((r) => !r.success).length;
+			const skippedCount = results.filter((r) => r.status === "skipped").length;
+			const failCount = results.filter(
+				(r) => !r.success && r.status !== "skipped"
+			).length;
+			const fellBackCount = permanentDelete
+				? 0
+				: results.filter((r) => r.success && r.method === "direct").length;
+			let fallbackNote =
+				fellBackCount > 0
+					? ` ${fellBackCount} couldn't go to the Recycle Bin and were deleted permanently.`
+					: "";
+			const retriedCount = results.filter(
+				(r) => r.success && (r.attempts ?? 1) > 1
+			).length;
+			if (retriedCount > 0) {
+				fallbackNote += ` ${retriedCount} only went through after a retry.`;
+			}
+			const refusedNested = (batch.nested ?? []).filter(
+				(n) => !n.parent_selected
+			).length;
+			if (refusedNested > 0) {
+				fallbackNote += ` ${refusedNested} nested inside another node_modules were left alone; select the outer one to delete them.`;
+			}
+			for (const partial of results.filter((r) => r.status === "partial")) {
+				const leftovers = partial.leftovers;
+				fallbackNote += `\n\n${partial.path} was only partly deleted; ${
+					leftovers?.files ?? 0
+				} file(s) survived${
+					leftovers && leftovers.listed.length > 0
+						? `:\n${leftovers.listed.join("\n")}`
+						: "."
+				}`;
+			}
 
-			if (failCount > 0) {
+			if (skippedCount > 0) {
 				alert(
-					`Deleted ${successCount} folders successfully. ${failCount} failed.`
+					`Cancelled: deleted ${successCount} folders, ${skippedCount} were left untouched${
+						failCount > 0 ? `, ${failCount} failed` : ""
+					}.${reclaimedNote}${fallbackNote}`
+				);
+			} else if (failCount > 0) {
+				alert(
+					`Deleted ${successCount} folders successfully. ${failCount} failed.${reclaimedNote}${fallbackNote}`
 				);
 			} else {
-				alert(`Successfully deleted ${successCount} folders.`);
+				alert(
+					`Successfully deleted ${successCount} folders.${reclaimedNote}${fallbackNote}`
+				);
+			}
+
+			const denied = results.filter((r) => r.can_elevate);
+			if (
+				denied.length > 0 &&
+				window.confirm(
+					`${denied.length} folder(s) couldn't be deleted for lack of permissions. Retry as administrator?`
+				)
+			) {
+				const elevatedResults: DeleteResult[] = [];
+				for (const result of denied) {
+					elevatedResults.push(
+						(await invoke("delete_elevated", {
+							path: result.path,
+						})) as DeleteResult
+					);
+				}
+				const elevatedPaths = new Set(
+					elevatedResults.filter((r) => r.elevated).map((r) => r.path)
+				);
+				setScanResults((prev) =>
+					prev.filter((item) => !elevatedPaths.has(item.node_modules_path))
+				);
+				alert(
+					`Deleted ${elevatedPaths.size} of ${denied.length} folders as administrator.`
+				);
 			}
 		} catch (error) {
 			console.error("Delete failed:", error);
-			alert("Delete failed: " + error);
+			alert("Delete failed: " + errorMessage(error));
 		} finally {
 			setIsDeleting(false);
+			setDeleteProgress(null);
+			setDeleteBytes({});
 			setShowDeleteModal(false);
 			setDeleteTarget(null);
 		}
@@ -673,6 +1005,61 @@ function App() {
 										{formatFileSize(node.size)}
 									</span>
 								)}
+
+							{node.isCloudSynced && (
+								<span
+									title="Cloud-synced folder: files may be online-only, so its size isn't measured"
+									className={`text-xs px-1.5 py-0.5 rounded ${
+										isDarkMode
+											? "text-sky-300 bg-sky-900/40"
+											: "text-sky-700 bg-sky-100"
+									}`}
+								>
+									cloud
+								</span>
+							)}
+
+							{node.artifactType && ARTIFACT_LABELS[node.artifactType] && (
+								<span
+									title="A build or install puts this folder back"
+									className={`text-xs px-1.5 py-0.5 rounded ${
+										isDarkMode
+											? "text-cyan-300 bg-cyan-900/40"
+											: "text-cyan-700 bg-cyan-100"
+									}`}
+								>
+									{ARTIFACT_LABELS[node.artifactType].badge}
+								</span>
+							)}
+
+							{node.isPnpm && (
+								<span
+									title="pnpm links packages from its shared store; only the part that isn't shared is freed"
+									className={`text-xs px-1.5 py-0.5 rounded ${
+										isDarkMode
+											? "text-amber-300 bg-amber-900/40"
+											: "text-amber-700 bg-amber-100"
+									}`}
+								>
+									{node.reclaimableSize !== null &&
+									node.reclaimableSize !== undefined
+										? `pnpm · frees ${formatFileSize(node.reclaimableSize)}`
+										: "pnpm"}
+								</span>
+							)}
+
+							{node.isProtected && (
+								<span
+									title="Protected project: it can't be deleted until it's unprotected"
+									className={`text-xs px-1.5 py-0.5 rounded ${
+										isDarkMode
+											? "text-emerald-300 bg-emerald-900/40"
+											: "text-emerald-700 bg-emerald-100"
+									}`}
+								>
+									protected
+								</span>
+							)}
 						</div>
 
 						{/* Full Path (for node_modules) */}
@@ -701,18 +1088,30 @@ function App() {
 								Open
 							</button>
 							<button
-								onClick={() => {
-									setDeleteTarget({ type: "single", path: node.path });
-									setShowDeleteModal(true);
-								}}
+								onClick={() => handleToggleProtected(node.path)}
 								className={`text-xs font-medium hover:underline transition-colors duration-150 ${
 									isDarkMode
-										? "text-red-400 hover:text-red-300"
-										: "text-red-600 hover:text-red-800"
+										? "text-emerald-400 hover:text-emerald-300"
+										: "text-emerald-600 hover:text-emerald-800"
 								}`}
 							>
-								Delete
+								{node.isProtected ? "Unprotect" : "Protect"}
 							</button>
+							{!node.isProtected && (
+								<button
+									onClick={() => {
+										setDeleteTarget({ type: "single", path: node.path });
+										setShowDeleteModal(true);
+									}}
+									className={`text-xs font-medium hover:underline transition-colors duration-150 ${
+										isDarkMode
+											? "text-red-400 hover:text-red-300"
+											: "text-red-600 hover:text-red-800"
+									}`}
+								>
+									Delete
+								</button>
+							)}
 						</div>
 					)}
 				</div>
@@ -907,7 +1306,8 @@ function App() {
 										type="text"
 										value={selectedFolder}
 										onChange={(e) => setSelectedFolder(e.target.value)}
-										placeholder="Choose a folder or paste a path"
+										list="recent-roots"
+										placeholder="Choose folders or paste paths, separated by ;"
 										className="flex-1 px-3 py-2 text-sm bg-white rounded border border-slate-300 dark:border-slate-600 dark:bg-slate-700 text-slate-900 dark:text-white placeholder-slate-500 dark:placeholder-slate-400 focus:ring-2 focus:ring-blue-500 focus:border-transparent"
 									/>
 									<button
@@ -916,6 +1316,11 @@ function App() {
 									>
 										Browse...
 									</button>
+									<datalist id="recent-roots">
+										{recentRoots.map((root) => (
+											<option key={root} value={root} />
+										))}
+									</datalist>
 								</div>
 							)}
 						</div>
@@ -943,19 +1348,66 @@ function App() {
 								<div className="flex-1 ml-4">
 									<div className="mb-2 text-xs text-slate-600 dark:text-slate-400">
 										Pick a drive to scan
+										<label className="ml-3 inline-flex items-center gap-1">
+											<input
+												type="checkbox"
+												checked={showAllDrives}
+												onChange={(e) => {
+													setShowAllDrives(e.target.checked);
+													loadDrives(e.target.checked);
+												}}
+											/>
+											Show removable and network drives
+										</label>
 									</div>
 									<div className="flex flex-wrap gap-2">
 										{drives.map((drive) => (
 											<button
 												key={drive.path}
 												onClick={() => setSelectedDrive(drive.path)}
+												title={
+													drive.drive_type === "network"
+														? "Network drive: scanning it can be slow"
+														: undefined
+												}
 												className={`px-3 py-2 rounded text-xs font-medium transition-colors border ${
 													selectedDrive === drive.path
 														? "bg-blue-600 text-white border-blue-600"
 														: "bg-white dark:bg-slate-700 text-slate-700 dark:text-slate-300 hover:bg-slate-50 dark:hover:bg-slate-600 border-slate-300 dark:border-slate-600"
 												}`}
 											>
-												{drive.name}
+												<div>
+													{drive.name}
+													{drive.label && ` – ${drive.label}`}
+													{drive.drive_type !== "fixed" &&
+														drive.drive_type !== "wsl" &&
+														drive.drive_type !== "unknown" &&
+														` (${drive.drive_type})`}
+												</div>
+												{drive.total_bytes != null &&
+													drive.total_bytes > 0 &&
+													drive.free_bytes != null && (
+														<div className="mt-1">
+															<div className="h-1 w-full rounded bg-slate-200 dark:bg-slate-600 overflow-hidden">
+																<div
+																	className={`h-full ${
+																		drive.free_bytes / drive.total_bytes < 0.1
+																			? "bg-red-500"
+																			: "bg-blue-400"
+																	}`}
+																	style={{
+																		width: `${Math.round(
+																			(1 - drive.free_bytes / drive.total_bytes) * 100
+																		)}%`,
+																	}}
+																/>
+															</div>
+															<div className="mt-0.5 text-[10px] opacity-75">
+																{formatFileSize(drive.free_bytes)} free of{" "}
+																{formatFileSize(drive.total_bytes)}
+															</div>
+														</div>
+													)}
 											</button>
 										))}
 									</div>
@@ -1092,7 +1544,29 @@ function App() {
 									>
 										{scanProgress.folders_scanned.toLocaleString()} /{" "}
 										{scanProgress.total_folders_estimated.toLocaleString()}{" "}
-										folders
+										folders (
+										{Math.min(
+											Math.round(
+												(scanProgress.folders_scanned /
+													Math.max(scanProgress.total_folders_estimated, 1)) *
+													100
+											),
+											100
+										)}
+										%)
+									</span>
+								)}
+								{scanProgress.eta_seconds != null && !scanProgress.is_complete && (
+									<span
+										className={`text-sm ${
+											isDarkMode ? "text-slate-400" : "text-slate-600"
+										}`}
+									>
+										~
+										{scanProgress.eta_seconds >= 60
+											? `${Math.ceil(scanProgress.eta_seconds / 60)}m`
+											: `${scanProgress.eta_seconds}s`}{" "}
+										left
 									</span>
 								)}
 							</div>
@@ -1189,6 +1663,14 @@ function App() {
 											Delete Selected ({selectedItems.size})
 										</button>
 									)}
+									{canUndoDelete && (
+										<button
+											onClick={handleUndoDelete}
+											className="px-4 py-1.5 bg-slate-100 dark:bg-zinc-800 text-slate-700 dark:text-zinc-300 rounded hover:bg-slate-200 transition-colors text-sm font-medium"
+										>
+											Undo Last Delete
+										</button>
+									)}
 								</div>
 							</div>
 						</div>
@@ -1307,6 +1789,39 @@ function App() {
 								</div>
 							</div>
 
+							{/* Permanent delete progress */}
+							{isDeleting && Object.keys(deleteBytes).length > 0 && (
+								<div className="mb-6 text-left">
+									{(() => {
+										const removed = Object.values(deleteBytes).reduce(
+											(total, bytes) => total + bytes,
+											0
+										);
+										const total = deleteTotalBytes();
+										return (
+											<>
+												<div className="h-2 w-full rounded bg-slate-200 dark:bg-zinc-700 overflow-hidden">
+													<div
+														className="h-full bg-red-500"
+														style={{
+															width: `${
+																total > 0
+																	? Math.min(100, Math.round((removed / total) * 100))
+																	: 0
+															}%`,
+														}}
+													/>
+												</div>
+												<div className="mt-1 text-xs text-slate-600 dark:text-zinc-400">
+													Removed {formatFileSize(removed)}
+													{total > 0 && ` of ${formatFileSize(total)}`}
+												</div>
+											</>
+										);
+									})()}
+								</div>
+							)}
+
 							{/* What Will Be Deleted */}
 							{deleteTarget.type === "single" && deleteTarget.path && (
 								<div className="p-3 mb-6 text-left rounded-lg border bg-slate-50 dark:bg-zinc-800 border-slate-200 dark:border-zinc-700">
@@ -1320,17 +1835,66 @@ function App() {
 							)}
 
 							<p className="mb-6 text-sm text-slate-600 dark:text-zinc-400">
-								This action will move the selected node_modules folders to your
-								system's Recycle Bin. You can recover them later if needed.
+								{permanentDelete
+									? "The selected node_modules folders will be removed permanently and can't be recovered."
+									: "This action will move the selected node_modules folders to your system's Recycle Bin. You can recover them later if needed."}
 							</p>
 
+							<label className="flex justify-center items-center mb-6 space-x-2 text-sm text-slate-700 dark:text-zinc-300">
+								<input
+									type="checkbox"
+									checked={permanentDelete}
+									onChange={(e) => setPermanentDelete(e.target.checked)}
+									disabled={isDeleting}
+								/>
+								<span>Delete permanently (faster, frees space right away)</span>
+							</label>
+							<label className="flex justify-center items-center -mt-4 mb-6 space-x-2 text-sm text-slate-700 dark:text-zinc-300">
+								<input
+									type="checkbox"
+									checked={clearReadOnly}
+									onChange={(e) => setClearReadOnly(e.target.checked)}
+									disabled={isDeleting}
+								/>
+								<span>Clear read-only and hidden file attributes first</span>
+							</label>
+							{permanentDelete && (
+								<label className="flex justify-center items-center -mt-4 mb-6 space-x-2 text-sm text-slate-700 dark:text-zinc-300">
+									<input
+										type="checkbox"
+										checked={fastRemove}
+										onChange={(e) => setFastRemove(e.target.checked)}
+										disabled={isDeleting}
+									/>
+									<span>Fast delete for huge folders (Windows, no progress)</span>
+								</label>
+							)}
+							{!permanentDelete && (
+								<label className="flex justify-center items-center -mt-4 mb-6 space-x-2 text-sm text-slate-700 dark:text-zinc-300">
+									<input
+										type="checkbox"
+										checked={permanentFallback}
+										onChange={(e) => setPermanentFallback(e.target.checked)}
+										disabled={isDeleting}
+									/>
+									<span>
+										Delete permanently if a folder can't go to the Recycle Bin
+									</span>
+								</label>
+							)}
+
 							<div className="flex justify-center space-x-3">
 								<button
-									onClick={() => setShowDeleteModal(false)}
-									disabled={isDeleting}
+									onClick={() =>
+										isDeleting
+											? invoke("cancel_delete").catch((error) =>
+													console.error("Failed to cancel delete:", error)
+												)
+											: setShowDeleteModal(false)
+									}
 									className="px-4 py-2 text-sm font-medium rounded transition-colors bg-slate-100 dark:bg-zinc-800 text-slate-700 dark:text-zinc-300 hover:bg-slate-200 dark:hover:bg-slate-200 disabled:opacity-50 disabled:cursor-not-allowed"
 								>
-									Cancel
+									{isDeleting ? "Stop" : "Cancel"}
 								</button>
 								<button
 									onClick={confirmDelete}
@@ -1340,8 +1904,14 @@ function App() {
 									{isDeleting ? (
 										<>
 											<div className="w-3 h-3 rounded-full border-2 border-white animate-spin border-t-transparent"></div>
-											<span>Deleting...</span>
+											<span>
+												{deleteProgress
+													? `Deleting ${deleteProgress.completed}/${deleteProgress.total}...`
+													: "Deleting..."}
+											</span>
 										</>
+									) : permanentDelete ? (
+										"Delete Permanently"
 									) : (
 										"Move to Recycle Bin"
 									)}